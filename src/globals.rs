@@ -2,6 +2,13 @@ use std::sync::Mutex;
 
 use once_cell::sync::Lazy;
 
+use crate::consts;
+use crate::utils::{
+    device_cfg::DeviceCfg,
+    theme::{Theme, ThemeName},
+    weather::{Locale, Units},
+};
+
 const DEFFAULT_SYS_NAME: &str = "default";
 const DEFFAULT_SERVER_NAME: &str = "default";
 
@@ -9,12 +16,28 @@ struct Global {
     pub sys_name: String,
     pub server: String,
     pub server_ip: Option<String>,
+    pub devices: Vec<DeviceCfg>,
+    pub web_roots: Vec<String>,
+    pub locale: Locale,
+    pub units: Units,
+    // a built-in theme or a theme file
+    pub theme_name: String,
+    pub theme: Theme,
+    // inserted plugins, e.g. for completion
+    pub plugins: Vec<String>,
 }
 static SYS_INFO: Lazy<Mutex<Global>> = Lazy::new(|| {
     Mutex::new(Global {
         sys_name: DEFFAULT_SYS_NAME.to_string(),
         server: DEFFAULT_SERVER_NAME.to_string(),
         server_ip: None,
+        devices: Vec::new(),
+        web_roots: vec![consts::NAS_FOLDER.to_string()],
+        locale: Locale::ZhTw,
+        units: Units::Metric,
+        theme_name: ThemeName::Dark.to_string(),
+        theme: Theme::default(),
+        plugins: Vec::new(),
     })
 });
 
@@ -47,3 +70,78 @@ pub fn set_server_ip(ip: &str) {
     let mut g = SYS_INFO.lock().unwrap();
     g.server_ip = Some(ip.to_string());
 }
+
+pub fn get_devices() -> Vec<DeviceCfg> {
+    let g = SYS_INFO.lock().unwrap();
+    g.devices.clone()
+}
+
+pub fn set_devices(devices: Vec<DeviceCfg>) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.devices = devices;
+}
+
+// resolve an alias from the inventory to the device name
+pub fn get_device_name(name: &str) -> String {
+    let g = SYS_INFO.lock().unwrap();
+    g.devices
+        .iter()
+        .find(|device| device.alias.as_deref() == Some(name))
+        .map_or(name.to_string(), |device| device.name.clone())
+}
+
+pub fn get_web_roots() -> Vec<String> {
+    let g = SYS_INFO.lock().unwrap();
+    g.web_roots.clone()
+}
+
+pub fn set_web_roots(web_roots: Vec<String>) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.web_roots = web_roots;
+}
+
+pub fn get_locale() -> Locale {
+    let g = SYS_INFO.lock().unwrap();
+    g.locale
+}
+
+pub fn set_locale(locale: Locale) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.locale = locale;
+}
+
+pub fn get_units() -> Units {
+    let g = SYS_INFO.lock().unwrap();
+    g.units
+}
+
+pub fn set_units(units: Units) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.units = units;
+}
+
+pub fn get_theme_name() -> String {
+    let g = SYS_INFO.lock().unwrap();
+    g.theme_name.clone()
+}
+
+pub fn get_theme() -> Theme {
+    let g = SYS_INFO.lock().unwrap();
+    g.theme
+}
+
+pub fn set_theme(name: &str, theme: Theme) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.theme_name = name.to_string();
+    g.theme = theme;
+}
+
+pub fn get_plugins() -> Vec<String> {
+    let g = SYS_INFO.lock().unwrap();
+    g.plugins.clone()
+}
+
+pub fn set_plugins(plugins: Vec<String>) {
+    let mut g = SYS_INFO.lock().unwrap();
+    g.plugins = plugins;
+}
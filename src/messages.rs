@@ -1,7 +1,7 @@
 use std::fmt;
 
 use log::Level::{Error, Info, Warn};
-use strum_macros::{AsRefStr, Display, EnumString};
+use strum_macros::{AsRefStr, Display, EnumIter, EnumString};
 use tokio::sync::{
     broadcast,
     mpsc::{Receiver, Sender},
@@ -20,6 +20,12 @@ pub enum WeatherKey {
     Summary,
     #[strum(serialize = "daily")]
     Daily,
+    #[strum(serialize = "hourly")]
+    Hourly,
+    #[strum(serialize = "error")]
+    Error,
+    #[strum(serialize = "air_quality")]
+    AirQuality,
 }
 
 // for infos
@@ -44,6 +50,14 @@ pub enum DeviceKey {
     Temperature,
     #[strum(serialize = "app_uptime")]
     AppUptime,
+    #[strum(serialize = "state")]
+    State,
+    #[strum(serialize = "stale")]
+    Stale,
+    #[strum(serialize = "upgrade")]
+    Upgrade,
+    #[strum(serialize = "discovery")]
+    Discovery,
 }
 
 // for Key
@@ -81,13 +95,67 @@ pub enum Key {
     ControlX,
     #[strum(serialize = "ctrl_s")]
     ControlS,
+    #[strum(serialize = "ctrl_f")]
+    ControlF,
     #[strum(serialize = "home")]
     Home,
     #[strum(serialize = "end")]
     End,
+    #[strum(serialize = "page_up")]
+    PageUp,
+    #[strum(serialize = "page_down")]
+    PageDown,
+    #[strum(serialize = "enter")]
+    Enter,
+    #[strum(serialize = "alt_1")]
+    Alt1,
+    #[strum(serialize = "alt_2")]
+    Alt2,
+    #[strum(serialize = "alt_3")]
+    Alt3,
+    #[strum(serialize = "alt_4")]
+    Alt4,
+    #[strum(serialize = "alt_5")]
+    Alt5,
+    #[strum(serialize = "alt_6")]
+    Alt6,
+    #[strum(serialize = "alt_7")]
+    Alt7,
+    #[strum(serialize = "alt_8")]
+    Alt8,
+    #[strum(serialize = "alt_9")]
+    Alt9,
 }
 
-#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
+// p plugins mouse <mouse_kind> <column> <row>
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Copy, Debug)]
+pub enum MouseKind {
+    #[strum(serialize = "down")]
+    Down,
+    #[strum(serialize = "up")]
+    Up,
+    #[strum(serialize = "drag")]
+    Drag,
+    #[strum(serialize = "scroll_up")]
+    ScrollUp,
+    #[strum(serialize = "scroll_down")]
+    ScrollDown,
+}
+
+// Alt+1..9, e.g. to switch workspaces
+pub const ALT_DIGITS: [Key; 9] = [
+    Key::Alt1,
+    Key::Alt2,
+    Key::Alt3,
+    Key::Alt4,
+    Key::Alt5,
+    Key::Alt6,
+    Key::Alt7,
+    Key::Alt8,
+    Key::Alt9,
+];
+
+#[derive(EnumString, EnumIter, AsRefStr, Display, PartialEq, Clone, Debug)]
 pub enum Action {
     #[strum(serialize = "log")]
     Log,
@@ -101,6 +169,22 @@ pub enum Action {
     Download,
     #[strum(serialize = "help")]
     Help,
+    #[strum(serialize = "history")]
+    History,
+    #[strum(serialize = "release")]
+    Release,
+    #[strum(serialize = "ls")]
+    Ls,
+    #[strum(serialize = "get")]
+    Get,
+    #[strum(serialize = "put")]
+    Put,
+    #[strum(serialize = "rm")]
+    Rm,
+    #[strum(serialize = "upgrade")]
+    Upgrade,
+    #[strum(serialize = "rollout")]
+    Rollout,
     #[strum(serialize = "gui")]
     Gui,
     #[strum(serialize = "output_update")]
@@ -131,6 +215,18 @@ pub enum Action {
     Popup,
     #[strum(serialize = "insert_panel")]
     InsertPanel,
+    #[strum(serialize = "layout")]
+    Layout,
+    #[strum(serialize = "workspace")]
+    Workspace,
+    #[strum(serialize = "mouse")]
+    Mouse,
+    #[strum(serialize = "search")]
+    Search,
+    #[strum(serialize = "follow")]
+    Follow,
+    #[strum(serialize = "theme")]
+    Theme,
     #[strum(serialize = "redraw")]
     Redraw,
     #[strum(serialize = "sync")]
@@ -139,6 +235,36 @@ pub enum Action {
     Wake,
     #[strum(serialize = "ping")]
     Ping,
+    #[strum(serialize = "record")]
+    Record,
+    #[strum(serialize = "inspect")]
+    Inspect,
+    #[strum(serialize = "pause")]
+    Pause,
+    #[strum(serialize = "filter")]
+    Filter,
+    #[strum(serialize = "collect")]
+    Collect,
+    #[strum(serialize = "remote")]
+    Remote,
+    #[strum(serialize = "check")]
+    Check,
+    #[strum(serialize = "stale")]
+    Stale,
+    #[strum(serialize = "alert")]
+    Alert,
+    #[strum(serialize = "rename")]
+    Rename,
+    #[strum(serialize = "move")]
+    Move,
+    #[strum(serialize = "geocoder")]
+    Geocoder,
+    #[strum(serialize = "air_quality")]
+    AirQuality,
+    #[strum(serialize = "locale")]
+    Locale,
+    #[strum(serialize = "units")]
+    Units,
 }
 
 #[derive(Debug, Clone)]
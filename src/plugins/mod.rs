@@ -2,6 +2,7 @@ pub mod plugin_cfg;
 pub mod plugin_cli;
 pub mod plugin_command;
 pub mod plugin_devices;
+pub mod plugin_discovery;
 pub mod plugin_editor;
 pub mod plugin_gui;
 pub mod plugin_infos;
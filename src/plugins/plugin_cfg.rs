@@ -9,7 +9,12 @@ use crate::consts;
 use crate::globals;
 use crate::messages::{Action, Msg};
 use crate::plugins::plugins_main::{self, Plugin};
-use crate::utils::common;
+use crate::utils::{
+    common,
+    device_cfg::DeviceCfg,
+    theme,
+    weather::{Locale, Units},
+};
 
 pub const MODULE: &str = "cfg";
 const CFG_FILE: &str = "cfg.toml";
@@ -18,6 +23,19 @@ const CFG_FILE: &str = "cfg.toml";
 struct Config {
     name: String,
     server: String,
+    #[serde(default)]
+    devices: Vec<DeviceCfg>,
+    #[serde(default = "default_web_roots")]
+    web_roots: Vec<String>,
+    // weather text and units, e.g. "en" and "imperial"
+    locale: Option<String>,
+    units: Option<String>,
+    // "dark", "light", "high_contrast" or the path of a theme file
+    theme: Option<String>,
+}
+
+fn default_web_roots() -> Vec<String> {
+    vec![consts::NAS_FOLDER.to_string()]
 }
 
 #[derive(Debug)]
@@ -46,8 +64,60 @@ impl PluginUnit {
 
         self.info(format!("  Name: {}", config.name)).await;
         self.info(format!("  Server: {}", config.server)).await;
+        self.info(format!("  Devices: {}", config.devices.len()))
+            .await;
         globals::set_sys_name(&config.name);
         globals::set_server(&config.server);
+        globals::set_devices(self.check_devices(config.devices).await);
+        self.info(format!("  Web roots: {}", config.web_roots.join(", ")))
+            .await;
+        globals::set_web_roots(config.web_roots);
+
+        if let Some(locale) = &config.locale {
+            match locale.parse::<Locale>() {
+                Ok(locale) => globals::set_locale(locale),
+                Err(_) => self.warn(format!("Unknown locale `{locale}`")).await,
+            }
+        }
+        if let Some(units) = &config.units {
+            match units.parse::<Units>() {
+                Ok(units) => globals::set_units(units),
+                Err(_) => self.warn(format!("Unknown units `{units}`")).await,
+            }
+        }
+        self.info(format!(
+            "  Locale: {}, units: {}",
+            globals::get_locale(),
+            globals::get_units()
+        ))
+        .await;
+
+        if let Some(theme_name) = &config.theme {
+            match theme::load(theme_name) {
+                Ok(theme) => globals::set_theme(theme_name, theme),
+                Err(e) => self.warn(format!("Theme: {e}")).await,
+            }
+        }
+        self.info(format!("  Theme: {}", globals::get_theme_name()))
+            .await;
+    }
+
+    // an invalid MAC is dropped, so it is reported once
+    async fn check_devices(&self, mut devices: Vec<DeviceCfg>) -> Vec<DeviceCfg> {
+        for device in devices.iter_mut() {
+            if let Some(mac) = &device.mac
+                && let Err(e) = common::parse_mac(mac)
+            {
+                self.warn(format!(
+                    "Invalid MAC address `{mac}` of `{}`: {e}",
+                    device.name
+                ))
+                .await;
+                device.mac = None;
+            }
+        }
+
+        devices
     }
 
     async fn handle_action_show(&self) {
@@ -62,6 +132,21 @@ impl PluginUnit {
             globals::get_server_ip().unwrap_or_default()
         ))
         .await;
+        self.info(format!("  Devices: {}", globals::get_devices().len()))
+            .await;
+        self.info(format!(
+            "  Web roots: {}",
+            globals::get_web_roots().join(", ")
+        ))
+        .await;
+        self.info(format!(
+            "  Locale: {}, units: {}",
+            globals::get_locale(),
+            globals::get_units()
+        ))
+        .await;
+        self.info(format!("  Theme: {}", globals::get_theme_name()))
+            .await;
     }
 
     async fn handle_action_help(&self) {
@@ -3,11 +3,11 @@ use async_trait::async_trait;
 use ratatui::{
     Frame,
     layout::Position,
-    style::{Color, Style},
     text::Text,
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 use tokio::sync::mpsc::Sender;
+use unicode_width::UnicodeWidthStr;
 
 use crate::consts;
 use crate::globals;
@@ -24,6 +24,8 @@ pub const MODULE: &str = "command";
 pub struct PluginUnit {
     msg_tx: Sender<Msg>,
     output: String,
+    // display width before the cursor
+    cursor: Option<u16>,
     panel_info: panel::PanelInfo,
 }
 
@@ -32,12 +34,15 @@ impl PluginUnit {
         let myself = Self {
             msg_tx: msg_tx.clone(),
             output: String::new(),
+            cursor: None,
             panel_info: panel::PanelInfo {
                 panel_type: panel::PanelType::Normal,
                 x: 0,
                 y: 0,
                 w: 0,
                 h: 0,
+                rect: None,
+                area: ratatui::layout::Rect::default(),
             },
         };
 
@@ -86,9 +91,13 @@ impl PluginUnit {
         }
     }
 
+    // p command output_update <output> [cursor]
     async fn handle_action_output_update(&mut self, cmd_parts: &[String]) {
         if let Some(output) = cmd_parts.get(3) {
             self.output = output.clone();
+            self.cursor = cmd_parts
+                .get(4)
+                .and_then(|cursor| cursor.parse::<u16>().ok());
             self.cmd(format!(
                 "{} {} {}",
                 consts::P,
@@ -121,7 +130,13 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         let width = frame.area().width;
         let height = frame.area().height - 3;
 
@@ -131,6 +146,7 @@ impl plugins_main::Plugin for PluginUnit {
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
 
         // Draw the panel block
         let sys_name = globals::get_sys_name();
@@ -149,19 +165,20 @@ impl plugins_main::Plugin for PluginUnit {
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
-        let text = Paragraph::new(Text::from(self.output.as_str()))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+        let text = Paragraph::new(Text::from(self.output.as_str())).style(theme.text(active));
         frame.render_widget(text, panel_block.inner(panel_area));
 
         // cursor for panel command
-        frame.set_cursor_position(Position::new(
-            panel_x + self.output.len() as u16 + 1,
-            panel_y + 1,
-        ));
+        let cursor = self
+            .cursor
+            .unwrap_or(UnicodeWidthStr::width(self.output.as_str()) as u16);
+        frame.set_cursor_position(Position::new(panel_x + cursor + 1, panel_y + 1));
     }
 
     async fn handle_action(&mut self, action: Action, cmd_parts: &[String], _msg: &Msg) {
@@ -1,18 +1,77 @@
+use std::path::{Path, PathBuf};
+
 use anyhow::Result;
 use async_trait::async_trait;
+use ratatui::{
+    Frame,
+    crossterm::{cursor::SetCursorStyle, execute},
+    style::Style,
+    text::{Line, Span, Text},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+};
+use strum_macros::{AsRefStr, Display, EnumString};
 use tokio::sync::mpsc::Sender;
+use tokio::task::JoinSet;
+use tokio::time::Duration;
+use unicode_width::UnicodeWidthStr;
 
 use crate::arguments::Mode;
 use crate::consts;
 use crate::globals;
-use crate::messages::{Action, DeviceKey, InfoKey, Msg};
+use crate::messages::{self as msgs, Action, DeviceKey, InfoKey, Key, Msg};
 use crate::plugins::{
-    plugin_infos, plugin_system,
+    plugin_discovery::Beacon,
+    plugin_gui, plugin_infos, plugin_ping, plugin_system, plugin_wol,
     plugins_main::{self, Plugin},
 };
-use crate::utils::{self, api, common};
+use crate::utils::{
+    self, api, common,
+    device_cfg::DeviceCfg,
+    device_history::{DeviceHistory, DeviceRecord},
+    device_state::{self, DeviceState},
+    nas, panel,
+    release::{self, UpgradeReport},
+    store,
+};
 
 pub const MODULE: &str = "devices";
+const CHECK_INTERVAL: u64 = 60;
+const DEFAULT_STALE_FACTOR: u64 = 3;
+const ALL: &str = "all";
+const ROLLOUT_TIMEOUT: u64 = 600;
+// a device that does not answer does not hold up the summary of a group
+const GROUP_CMD_TIMEOUT: u64 = 10;
+const DEFAULT_HISTORY_DAYS: u64 = 7;
+const HISTORY_RETENTION_DAYS: u64 = 30;
+const PRUNE_INTERVAL: u64 = 24 * 60 * 60;
+const POPUP_BUTTONS: [Action; 3] = [Action::Wake, Action::Ping, Action::Cmd];
+const POPUP_HISTORY_SECS: u64 = 24 * 60 * 60;
+const POPUP_KEY_WIDTH: usize = 13;
+const ALERT_PARAMS: &str = "add <online|offline> \"<cmd>\" | remove <idx>";
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
+enum AlertEvent {
+    #[strum(serialize = "online")]
+    Online,
+    #[strum(serialize = "offline")]
+    Offline,
+}
+
+// canary first, the rest once the canary reports the new version
+#[derive(Debug)]
+struct Rollout {
+    ts: u64,
+    version: String,
+    canary: Option<String>,
+    pending: Vec<String>,
+    failed: Vec<String>,
+}
+
+#[derive(Debug)]
+struct Alert {
+    event: AlertEvent,
+    cmd: String,
+}
 
 // DevInfo
 #[derive(Debug, Clone)]
@@ -24,6 +83,24 @@ pub struct DevInfo {
     pub tailscale_ip: Option<String>,
     pub temperature: Option<f32>,
     pub app_uptime: Option<u64>,
+    pub state: Option<DeviceState>,
+    pub stale: bool,
+    // last onboard reported over mqtt, e.g. Some(false) from the last will
+    pub mqtt_onboard: Option<bool>,
+    // from LAN discovery
+    pub lan_ip: Option<String>,
+    pub web_port: Option<u16>,
+}
+
+impl DevInfo {
+    pub fn is_online(&self) -> bool {
+        self.onboard && !self.stale
+    }
+
+    // Tailscale first, LAN when the device is only discovered locally
+    pub fn ip(&self) -> Option<String> {
+        self.tailscale_ip.clone().or(self.lan_ip.clone())
+    }
 }
 
 #[derive(Debug)]
@@ -31,48 +108,768 @@ pub struct PluginUnit {
     msg_tx: Sender<Msg>,
     mode: Mode,
     devices: Vec<DevInfo>,
+    stale_factor: u64,
+    alerts: Vec<Alert>,
+    rollout: Option<Rollout>,
+    last_prune: u64,
+    panel_info: panel::PanelInfo,
+    // popup
+    popup: Option<String>,
+    popup_button: usize,
+    popup_temperatures: Vec<f32>,
 }
 
 impl PluginUnit {
     pub async fn new(msg_tx: Sender<Msg>, mode: Mode) -> Result<Self> {
         let myself = Self {
-            msg_tx,
+            msg_tx: msg_tx.clone(),
             mode,
             devices: Vec::new(),
+            stale_factor: DEFAULT_STALE_FACTOR,
+            alerts: Vec::new(),
+            rollout: None,
+            last_prune: 0,
+            panel_info: panel::PanelInfo::new(panel::PanelType::Popup),
+            popup: None,
+            popup_button: 0,
+            popup_temperatures: Vec::new(),
         };
 
         myself.info(consts::NEW.to_string()).await;
 
+        tokio::spawn(async move {
+            msgs::info(
+                &msg_tx,
+                MODULE,
+                &format!("  Starting to check staleness every {CHECK_INTERVAL} secs..."),
+            )
+            .await;
+            loop {
+                tokio::time::sleep(Duration::from_secs(CHECK_INTERVAL)).await;
+                msgs::cmd(
+                    &msg_tx,
+                    MODULE,
+                    &format!("{} {MODULE} {}", consts::P, Action::Check),
+                )
+                .await;
+            }
+        });
+
         Ok(myself)
     }
 
-    async fn handle_action_show(&self) {
+    // p devices show [@group]
+    async fn handle_action_show(&self, cmd_parts: &[String]) {
         self.info(Action::Show.to_string()).await;
+
+        let group = cmd_parts.get(3).and_then(|group| group.strip_prefix('@'));
+        let devices: Vec<&DevInfo> = self
+            .devices
+            .iter()
+            .filter(|device| group.is_none_or(|group| in_group(&device.name, group)))
+            .collect();
+
         self.info(format!(
-            "  {:<12} {:<7} {:<8} {:<15} {:<6} {:<12} {:<16}",
-            "Name", "Onboard", "Version", "Tailscale IP", "Temp", "App uptime", "Last update"
+            "  {:<12} {:<7} {:<8} {:<15} {:<15} {:<6} {:<12} {:<16}",
+            "Name",
+            "Onboard",
+            "Version",
+            "Tailscale IP",
+            "LAN IP",
+            "Temp",
+            "App uptime",
+            "Last update"
         ))
         .await;
 
-        for device in &self.devices {
+        for device in &devices {
             self.info(format!(
-                "  {:<12} {:<7} {:<8} {:<15} {:<6} {:<12} {:<16}",
+                "  {:<12} {:<7} {:<8} {:<15} {:<15} {:<6} {:<12} {:<16}",
                 device.name,
-                onboard_str(device.onboard),
+                status_str(device),
                 version_str(&device.version),
                 tailscale_ip_str(&device.tailscale_ip),
+                tailscale_ip_str(&device.lan_ip),
                 common::temperature_str(device.temperature),
                 app_uptime_str(device.app_uptime),
-                utils::time::ts_str_local(device.ts)
+                last_update_str(device.ts)
+            ))
+            .await;
+        }
+
+        self.info(format!(
+            "  {:<12} {:<5} {:<6} {:<6} {:<16} {:<8} {}",
+            "Name", "CPU", "Memory", "Disk", "Load average", "Arch", "OS"
+        ))
+        .await;
+
+        for device in &devices {
+            if let Some(state) = &device.state {
+                self.info(format!(
+                    "  {:<12} {:<5} {:<6} {:<6} {:<16} {:<8} {}",
+                    device.name,
+                    state.cpu_load_str(),
+                    state.memory_str(),
+                    state.disk_str(),
+                    state.load_average_str(),
+                    state.arch,
+                    state.os
+                ))
+                .await;
+                self.info(format!("  {:<12} IPs: {}", "", state.ips.join(", ")))
+                    .await;
+            }
+        }
+
+        self.info(format!(
+            "  {:<12} {:<8} {:<16} {:<12} {:<17} {:<5} {:<8}",
+            "Name", "Alias", "Groups", "Location", "MAC", "Port", "Expected"
+        ))
+        .await;
+
+        for device_cfg in globals::get_devices()
+            .iter()
+            .filter(|device_cfg| devices.iter().any(|device| device.name == device_cfg.name))
+        {
+            self.info(format!(
+                "  {:<12} {:<8} {:<16} {:<12} {:<17} {:<5} {:<8}",
+                device_cfg.name,
+                device_cfg.alias.as_deref().unwrap_or(consts::NA),
+                device_cfg.groups.join(","),
+                device_cfg.location.as_deref().unwrap_or(consts::NA),
+                device_cfg.mac.as_deref().unwrap_or(consts::NA),
+                device_cfg.web_port,
+                onboard_str(device_cfg.expected_online),
+            ))
+            .await;
+        }
+
+        self.info(format!(
+            "  Stale after: {} secs ({} x {} secs)",
+            self.stale_timeout(),
+            self.stale_factor,
+            plugin_system::UPDATE_INTERVAL
+        ))
+        .await;
+        if let Some(rollout) = &self.rollout {
+            self.info(format!(
+                "  Rollout v{}: canary {}, pending [{}], failed [{}]",
+                rollout.version,
+                rollout.canary.as_deref().unwrap_or(consts::NA),
+                rollout.pending.join(", "),
+                rollout.failed.join(", "),
             ))
             .await;
         }
+        self.info("  Alerts:".to_string()).await;
+        if self.alerts.is_empty() {
+            self.info("    <none>".to_string()).await;
+        }
+        for (idx, alert) in self.alerts.iter().enumerate() {
+            self.info(format!("    {idx}: {:<7} `{}`", alert.event, alert.cmd))
+                .await;
+        }
     }
 
     async fn handle_action_help(&self) {
         self.info(Action::Help.to_string()).await;
-        self.info(format!("  {} <device_name> \"<cmd>\"", Action::Cmd))
+        self.info(format!("  {} [@group]", Action::Show)).await;
+        self.info(format!("  {} <device_name|@group>", Action::Wake))
+            .await;
+        self.info(format!(
+            "  {} <device_name|@group|@{ALL}> \"<cmd>\"",
+            Action::Cmd
+        ))
+        .await;
+        self.info("    device_name can also be an alias from cfg.toml".to_string())
+            .await;
+        self.info(format!("  {} <device_name> [days]", Action::History))
+            .await;
+        self.info(format!(
+            "    availability, outages and temperature, default {DEFAULT_HISTORY_DAYS} days"
+        ))
+        .await;
+        self.info(format!("  {} <device_name|@group|@{ALL}>", Action::Rollout))
+            .await;
+        self.info(
+            "    roll out the release of `p system release` on the server, canary first"
+                .to_string(),
+        )
+        .await;
+        self.info(format!("  {} <device_name> <path>", Action::Ls))
+            .await;
+        self.info(format!(
+            "  {} <device_name> <remote_path> [local_path]",
+            Action::Get
+        ))
+        .await;
+        self.info(format!(
+            "  {} <device_name> <local_path> [remote_path]",
+            Action::Put
+        ))
+        .await;
+        self.info(format!("  {} <device_name> <remote_path>", Action::Rm))
             .await;
+        self.info("    remote paths must be under the web roots of the device".to_string())
+            .await;
+        self.info(format!("  {} <factor>", Action::Stale)).await;
+        self.info(format!(
+            "    stale when no update within factor x {} secs",
+            plugin_system::UPDATE_INTERVAL
+        ))
+        .await;
+        self.info(format!("  {} {ALERT_PARAMS}", Action::Alert))
+            .await;
+        self.info("    `{name}` in cmd is replaced by the device name".to_string())
+            .await;
+    }
+
+    // `@all` expands to all devices, `@group` to the devices of the group,
+    // otherwise a name or an alias
+    fn targets(&self, target: &str) -> Vec<String> {
+        match target.strip_prefix('@') {
+            Some(ALL) => self
+                .devices
+                .iter()
+                .map(|device| device.name.clone())
+                .collect(),
+            Some(group) => globals::get_devices()
+                .into_iter()
+                .filter(|device_cfg| device_cfg.groups.iter().any(|g| g == group))
+                .map(|device_cfg| device_cfg.name)
+                .collect(),
+            None => vec![globals::get_device_name(target)],
+        }
+    }
+
+    fn stale_timeout(&self) -> u64 {
+        self.stale_factor * plugin_system::UPDATE_INTERVAL
+    }
+
+    fn is_online(&self, name: &str) -> bool {
+        self.devices
+            .iter()
+            .find(|device| device.name == *name)
+            .is_some_and(|device| device.is_online())
+    }
+
+    async fn update_infos_stale(&self, name: &str, stale: bool) {
+        if self.mode == Mode::Gui {
+            self.cmd(format!(
+                "{} {} {} {} {} {name} {}",
+                consts::P,
+                plugin_infos::MODULE,
+                Action::Update,
+                InfoKey::Devices,
+                DeviceKey::Stale,
+                if stale { "1" } else { "0" },
+            ))
+            .await;
+        }
+    }
+
+    async fn record(&self, name: &str, record: DeviceRecord) {
+        if let Err(e) = history_path(name).and_then(|path| store::append(&path, &record)) {
+            self.warn(format!("Failed to record history of `{name}`: {e}"))
+                .await;
+        }
+    }
+
+    // the state repeats both metrics, they are recorded from their own keys
+    async fn record_metric(&self, name: &str, device_key: &DeviceKey) {
+        let ts = utils::time::ts();
+        let Some(device) = self.devices.iter().find(|device| device.name == *name) else {
+            return;
+        };
+
+        let record = match device_key {
+            DeviceKey::Temperature => device
+                .temperature
+                .map(|value| DeviceRecord::Temperature { ts, value }),
+            DeviceKey::AppUptime => device
+                .app_uptime
+                .map(|value| DeviceRecord::AppUptime { ts, value }),
+            _ => None,
+        };
+        if let Some(record) = record {
+            self.record(name, record).await;
+        }
+    }
+
+    // records older than the retention, once a day
+    async fn prune_history(&mut self, now: u64) {
+        if self.last_prune + PRUNE_INTERVAL > now {
+            return;
+        }
+        self.last_prune = now;
+
+        let from = now.saturating_sub(HISTORY_RETENTION_DAYS * 24 * 60 * 60);
+        for device in &self.devices {
+            if let Err(e) = history_path(&device.name)
+                .and_then(|path| store::retain::<DeviceRecord>(&path, |record| record.ts() >= from))
+            {
+                self.warn(format!("Failed to prune history of `{}`: {e}", device.name))
+                    .await;
+            }
+        }
+    }
+
+    // `seen` is false on the first update of a device, e.g. every node at startup
+    async fn handle_online_changed(&self, name: &str, online: bool, seen: bool) {
+        self.record(
+            name,
+            DeviceRecord::Onboard {
+                ts: utils::time::ts(),
+                online,
+            },
+        )
+        .await;
+
+        let event = if online {
+            AlertEvent::Online
+        } else {
+            AlertEvent::Offline
+        };
+
+        // e.g. a laptop is expected to go offline
+        if !seen || (!online && !expected_online(name)) {
+            return;
+        }
+
+        // the name comes from the MQTT topic
+        for alert in self.alerts.iter().filter(|alert| alert.event == event) {
+            self.cmd(alert.cmd.replace("{name}", &shell_words::quote(name)))
+                .await;
+        }
+    }
+
+    // p devices check
+    async fn handle_action_check(&mut self) {
+        let now = utils::time::ts();
+        let stale_timeout = self.stale_timeout();
+
+        let mut stale_names = vec![];
+        for device in self.devices.iter_mut() {
+            if device.is_online() && device.ts + stale_timeout < now {
+                device.stale = true;
+                stale_names.push(device.name.clone());
+            }
+        }
+
+        for name in stale_names {
+            self.warn(format!(
+                "`{name}` is stale, no update for {stale_timeout} secs"
+            ))
+            .await;
+            self.update_infos_stale(&name, true).await;
+            self.handle_online_changed(&name, false, true).await;
+        }
+
+        if let Some(rollout) = &self.rollout
+            && rollout.ts + ROLLOUT_TIMEOUT < now
+        {
+            self.warn(format!(
+                "Rollout v{} timed out, canary: {}, pending: [{}]",
+                rollout.version,
+                rollout.canary.as_deref().unwrap_or(consts::NA),
+                rollout.pending.join(", ")
+            ))
+            .await;
+            self.rollout = None;
+        }
+
+        self.prune_history(now).await;
+    }
+
+    async fn upgrade(&self, name: &str) {
+        self.cmd(format!(
+            "{} {MODULE} {} {name} {}",
+            consts::P,
+            Action::Cmd,
+            shell_words::quote(&format!(
+                "{} {} {}",
+                consts::P,
+                plugin_system::MODULE,
+                Action::Upgrade
+            ))
+        ))
+        .await;
+    }
+
+    // p devices rollout <device_name|@group|@all>
+    async fn handle_action_rollout(&mut self, cmd_parts: &[String]) {
+        let Some(target) = cmd_parts.get(3) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                &format!("<device_name|@group|@{ALL}>"),
+                Action::Rollout.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        if let Some(rollout) = &self.rollout {
+            self.warn(format!("Rollout v{} is in progress", rollout.version))
+                .await;
+            return;
+        }
+
+        // the version the nodes will download, not the one of this binary
+        let Some(server_ip) = globals::get_server_ip() else {
+            self.warn(consts::SERVER_IP_NOT_SET.to_string()).await;
+            return;
+        };
+        let version = match release::manifest(&self.msg_tx, MODULE, &server_ip).await {
+            Ok(manifest) => manifest.version,
+            Err(e) => {
+                self.warn(format!("Failed to get the release manifest: {e}"))
+                    .await;
+                return;
+            }
+        };
+        // the manifest is the one of this platform, its binary does not run elsewhere
+        let platform = release::platform();
+        let (mut names, skipped): (Vec<String>, Vec<String>) = self
+            .targets(target)
+            .into_iter()
+            .filter(|name| {
+                self.devices.iter().any(|device| {
+                    device.name == *name
+                        && device.is_online()
+                        && device.version.as_ref() != Some(&version)
+                })
+            })
+            .partition(|name| {
+                self.devices.iter().any(|device| {
+                    device.name == *name
+                        && device
+                            .state
+                            .as_ref()
+                            .is_some_and(|state| state.platform == platform)
+                })
+            });
+
+        if !skipped.is_empty() {
+            self.warn(format!(
+                "Rollout skips devices not on {platform}: [{}]",
+                skipped.join(", ")
+            ))
+            .await;
+        }
+
+        if names.is_empty() {
+            self.info(format!(
+                "No online devices in `{target}` to update to v{version}"
+            ))
+            .await;
+            return;
+        }
+
+        let canary = names.remove(0);
+        self.info(format!(
+            "Rollout v{version}: canary `{canary}`, then [{}]",
+            names.join(", ")
+        ))
+        .await;
+        self.upgrade(&canary).await;
+
+        self.rollout = Some(Rollout {
+            ts: utils::time::ts(),
+            version,
+            canary: Some(canary),
+            pending: names,
+            failed: Vec::new(),
+        });
+    }
+
+    async fn handle_rollout_progress(&mut self, name: &str) {
+        let Some(rollout) = &mut self.rollout else {
+            return;
+        };
+        let upgraded = self.devices.iter().any(|device| {
+            device.name == *name && device.version.as_ref() == Some(&rollout.version)
+        });
+        if !upgraded {
+            return;
+        }
+
+        if rollout.canary.as_deref() == Some(name) {
+            rollout.canary = None;
+            rollout.ts = utils::time::ts();
+            let pending = rollout.pending.clone();
+
+            self.info(format!("Rollout: canary `{name}` is upgraded, continuing"))
+                .await;
+            for name in pending {
+                self.upgrade(&name).await;
+            }
+        } else if rollout.canary.is_none() && rollout.pending.iter().any(|p| p == name) {
+            rollout.pending.retain(|p| p != name);
+            self.info(format!("Rollout: `{name}` is upgraded")).await;
+        }
+
+        self.handle_rollout_done().await;
+    }
+
+    async fn handle_rollout_done(&mut self) {
+        if let Some(rollout) = &self.rollout
+            && rollout.canary.is_none()
+            && rollout.pending.is_empty()
+        {
+            if rollout.failed.is_empty() {
+                self.info(format!("Rollout v{} done", rollout.version))
+                    .await;
+            } else {
+                self.warn(format!(
+                    "Rollout v{} done, failed: [{}]",
+                    rollout.version,
+                    rollout.failed.join(", ")
+                ))
+                .await;
+            }
+            self.rollout = None;
+        }
+    }
+
+    async fn handle_update_upgrade(&mut self, name: &str, value: &str) {
+        let report = match serde_json::from_str::<UpgradeReport>(value) {
+            Ok(report) => report,
+            Err(e) => {
+                self.warn(format!("Invalid upgrade report from `{name}`: {e}"))
+                    .await;
+                return;
+            }
+        };
+
+        if let Some(device) = self.devices.iter_mut().find(|device| device.name == *name) {
+            device.ts = utils::time::ts();
+        }
+
+        if report.ok {
+            self.info(format!(
+                "`{name}` upgrade: v{} {}",
+                report.version, report.msg
+            ))
+            .await;
+            return;
+        }
+
+        self.warn(format!("`{name}` upgrade failed: {}", report.msg))
+            .await;
+
+        let Some(rollout) = &mut self.rollout else {
+            return;
+        };
+        if rollout.canary.as_deref() == Some(name) {
+            let version = rollout.version.clone();
+            self.rollout = None;
+            self.warn(format!(
+                "Rollout v{version} aborted, canary `{name}` failed"
+            ))
+            .await;
+        } else if rollout.pending.iter().any(|p| p == name) {
+            rollout.pending.retain(|p| p != name);
+            rollout.failed.push(name.to_string());
+            self.handle_rollout_done().await;
+        }
+    }
+
+    // p devices wake <device_name|@group>
+    async fn handle_action_wake(&self, cmd_parts: &[String]) {
+        let Some(target) = cmd_parts.get(3) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name|@group>",
+                Action::Wake.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        let names = self.targets(target);
+        if names.is_empty() {
+            self.warn(format!("No devices in `{target}`")).await;
+        }
+
+        for name in names {
+            self.cmd(format!(
+                "{} {} {} {name}",
+                consts::P,
+                plugin_wol::MODULE,
+                Action::Wake
+            ))
+            .await;
+        }
+    }
+
+    // p devices history <device_name> [days]
+    async fn handle_action_history(&self, cmd_parts: &[String]) {
+        let Some(name) = cmd_parts.get(3).map(|name| globals::get_device_name(name)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name> [days]",
+                Action::History.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        let days = match cmd_parts.get(4).map(|days| days.parse::<u64>()) {
+            None => DEFAULT_HISTORY_DAYS,
+            Some(Ok(days)) if days > 0 => days,
+            Some(_) => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "[days]",
+                    Action::History.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        };
+
+        let records = match history_path(&name).and_then(|path| store::load::<DeviceRecord>(&path))
+        {
+            Ok(records) => records,
+            Err(e) => {
+                self.warn(format!("Failed to load history of `{name}`: {e}"))
+                    .await;
+                return;
+            }
+        };
+
+        let to = utils::time::ts();
+        let from = to.saturating_sub(days * 24 * 60 * 60);
+        let history = DeviceHistory::new(&records, from, to);
+
+        self.info(format!("{} {name} (last {days} days)", Action::History))
+            .await;
+        self.info(format!(
+            "  Availability: {}",
+            match history.availability() {
+                Some(availability) => format!(
+                    "{availability:.2}% (known {})",
+                    utils::time::uptime_str(history.known)
+                ),
+                None => consts::NA.to_string(),
+            }
+        ))
+        .await;
+        self.info(format!(
+            "  Temperature:  {}",
+            match history.temperature {
+                Some((min, avg, max)) => format!(
+                    "min {} / avg {} / max {}",
+                    common::temperature_str(Some(min)),
+                    common::temperature_str(Some(avg)),
+                    common::temperature_str(Some(max)),
+                ),
+                None => consts::NA.to_string(),
+            }
+        ))
+        .await;
+        self.info(format!("  App restarts: {}", history.restarts))
+            .await;
+        self.info(format!("  Outages:      {}", history.outages.len()))
+            .await;
+        for outage in &history.outages {
+            let end = outage.end.unwrap_or(to);
+            self.info(format!(
+                "    {} ~ {} ({})",
+                utils::time::ts_str_full(outage.start),
+                match outage.end {
+                    Some(end) => utils::time::ts_str_full(end),
+                    None => "now".to_string(),
+                },
+                utils::time::uptime_str(end - outage.start),
+            ))
+            .await;
+        }
+    }
+
+    // p devices stale <factor>
+    async fn handle_action_stale(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|factor| factor.parse::<u64>()) {
+            Some(Ok(factor)) if factor > 0 => {
+                self.stale_factor = factor;
+                self.info(format!("Stale after {} secs", self.stale_timeout()))
+                    .await;
+            }
+            Some(_) => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "<factor>",
+                    Action::Stale.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+            None => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    "<factor>",
+                    Action::Stale.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p devices alert add <online|offline> "<cmd>"
+    // p devices alert remove <idx>
+    async fn handle_action_alert(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|action| action.parse::<Action>()) {
+            Some(Ok(Action::Add)) => {
+                if let (Some(event), Some(cmd)) = (cmd_parts.get(4), cmd_parts.get(5)) {
+                    match event.parse::<AlertEvent>() {
+                        Ok(event) => {
+                            self.info(format!("Alert added: {event} `{cmd}`")).await;
+                            self.alerts.push(Alert {
+                                event,
+                                cmd: cmd.to_string(),
+                            });
+                        }
+                        Err(_) => {
+                            self.warn(common::MsgTemplate::InvalidParameters.format(
+                                &format!("<event> (`{event}`)"),
+                                Action::Alert.as_ref(),
+                                &cmd_parts.join(" "),
+                            ))
+                            .await;
+                        }
+                    }
+                } else {
+                    self.warn(common::MsgTemplate::MissingParameters.format(
+                        "<online|offline> \"<cmd>\"",
+                        Action::Alert.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            }
+            Some(Ok(Action::Remove)) => match cmd_parts.get(4).map(|idx| idx.parse::<usize>()) {
+                Some(Ok(idx)) if idx < self.alerts.len() => {
+                    let alert = self.alerts.remove(idx);
+                    self.info(format!("Alert removed: {} `{}`", alert.event, alert.cmd))
+                        .await;
+                }
+                _ => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        "<idx>",
+                        Action::Alert.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            },
+            _ => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    ALERT_PARAMS,
+                    Action::Alert.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
     }
 
     async fn handle_update_onboard(&mut self, name: &str, value: &str) {
@@ -97,6 +894,11 @@ impl PluginUnit {
                     tailscale_ip: None,
                     temperature: None,
                     app_uptime: None,
+                    state: None,
+                    stale: false,
+                    mqtt_onboard: None,
+                    lan_ip: None,
+                    web_port: None,
                 };
                 self.devices.push(device_add);
 
@@ -248,12 +1050,111 @@ impl PluginUnit {
         }
     }
 
+    async fn handle_update_state(&mut self, name: &str, value: &str) {
+        let ts = utils::time::ts();
+
+        let state = match serde_json::from_str::<DeviceState>(value) {
+            Ok(state) => state,
+            Err(e) => {
+                self.warn(format!("Invalid state from `{name}`: {e}")).await;
+                return;
+            }
+        };
+
+        if state.schema > device_state::SCHEMA {
+            self.warn(format!(
+                "State schema v{} from `{name}` is newer than v{}",
+                state.schema,
+                device_state::SCHEMA
+            ))
+            .await;
+        }
+
+        if let Some(device) = self.devices.iter_mut().find(|device| device.name == *name) {
+            device.ts = ts;
+            device.version = Some(state.version.clone());
+            device.tailscale_ip = state.tailscale_ip.clone();
+            device.temperature = state.temperature;
+            device.app_uptime = Some(state.app_uptime);
+            device.state = Some(state);
+
+            // update globals
+            if name == globals::get_server()
+                && let Some(tailscale_ip) = &device.tailscale_ip
+            {
+                globals::set_server_ip(tailscale_ip);
+            }
+
+            // update infos
+            if self.mode == Mode::Gui {
+                self.cmd(format!(
+                    "{} {} {} {} {} {name} {}",
+                    consts::P,
+                    plugin_infos::MODULE,
+                    Action::Update,
+                    InfoKey::Devices,
+                    DeviceKey::State,
+                    shell_words::quote(value),
+                ))
+                .await;
+            }
+        }
+    }
+
+    async fn handle_update_discovery(&mut self, name: &str, value: &str) {
+        let beacon = match serde_json::from_str::<Beacon>(value) {
+            Ok(beacon) => beacon,
+            Err(e) => {
+                self.warn(format!("Invalid beacon from `{name}`: {e}"))
+                    .await;
+                return;
+            }
+        };
+
+        if !self.devices.iter().any(|device| device.name == *name) {
+            self.info(format!(
+                "`{name}` discovered at {}",
+                tailscale_ip_str(&beacon.lan_ip)
+            ))
+            .await;
+        }
+
+        // a beacon counts as onboard, unless mqtt reported the device offline,
+        // e.g. by its last will, then only mqtt brings it back
+        let mqtt_offline = self
+            .devices
+            .iter()
+            .any(|device| device.name == *name && device.mqtt_onboard == Some(false));
+        if !mqtt_offline {
+            self.handle_update_onboard(name, "1").await;
+        }
+        self.handle_update_version(name, &beacon.version).await;
+
+        if let Some(device) = self.devices.iter_mut().find(|device| device.name == *name) {
+            device.lan_ip = beacon.lan_ip;
+            device.web_port = Some(beacon.web_port);
+        }
+    }
+
     async fn handle_action_update(&mut self, cmd_parts: &[String]) {
         if let (Some(device_key), Some(name), Some(value)) =
             (cmd_parts.get(3), cmd_parts.get(4), cmd_parts.get(5))
         {
+            let was_online = self.is_online(name);
+            let seen = self
+                .devices
+                .iter()
+                .any(|device| device.name == *name && device.ts != 0);
+
             match device_key.parse::<DeviceKey>() {
-                Ok(_k @ DeviceKey::Onboard) => self.handle_update_onboard(name, value).await,
+                Ok(_k @ DeviceKey::Onboard) => {
+                    self.handle_update_onboard(name, value).await;
+                    if let Some(device) =
+                        self.devices.iter_mut().find(|device| device.name == *name)
+                    {
+                        device.mqtt_onboard = Some(value == "1");
+                    }
+                }
                 Ok(_k @ DeviceKey::Version) => self.handle_update_version(name, value).await,
                 Ok(_k @ DeviceKey::TailscaleIp) => {
                     self.handle_update_tailscale_ip(name, value).await
@@ -262,15 +1163,51 @@ impl PluginUnit {
                     self.handle_update_temperature(name, value).await
                 }
                 Ok(_k @ DeviceKey::AppUptime) => self.handle_update_app_uptime(name, value).await,
-                Err(_) => {
+                Ok(_k @ DeviceKey::State) => self.handle_update_state(name, value).await,
+                Ok(_k @ DeviceKey::Upgrade) => self.handle_update_upgrade(name, value).await,
+                Ok(_k @ DeviceKey::Discovery) => self.handle_update_discovery(name, value).await,
+                _ => {
                     self.warn(common::MsgTemplate::InvalidParameters.format(
-                        "<device_key> (`{device_key}`)",
+                        &format!("<device_key> (`{device_key}`)"),
                         Action::Update.as_ref(),
                         &cmd_parts.join(" "),
                     ))
                     .await;
+                    return;
                 }
             }
+
+            // any update means the device is alive again
+            let mut refreshed = false;
+            if let Some(device) = self.devices.iter_mut().find(|device| device.name == *name)
+                && device.stale
+            {
+                device.stale = false;
+                refreshed = true;
+            }
+            if refreshed {
+                self.update_infos_stale(name, false).await;
+            }
+
+            let is_online = self.is_online(name);
+            if was_online != is_online {
+                self.handle_online_changed(name, is_online, seen).await;
+            }
+
+            if let Ok(device_key) = device_key.parse::<DeviceKey>() {
+                self.record_metric(name, &device_key).await;
+            }
+
+            if matches!(
+                device_key.parse::<DeviceKey>(),
+                Ok(DeviceKey::Version | DeviceKey::State | DeviceKey::Discovery)
+            ) {
+                self.handle_rollout_progress(name).await;
+            }
+
+            if self.popup.as_deref() == Some(name) {
+                self.redraw().await;
+            }
         } else {
             self.warn(common::MsgTemplate::MissingParameters.format(
                 "<key> <name> <value>",
@@ -281,32 +1218,594 @@ impl PluginUnit {
         }
     }
 
+    async fn device_addr(&self, device_name: &str) -> Option<(String, u16)> {
+        match self
+            .devices
+            .iter()
+            .find(|device| device.name == *device_name)
+        {
+            Some(device) => {
+                if device.ip().is_none() {
+                    self.warn(format!("Device `{device_name}` has no IP")).await;
+                }
+                device.ip().map(|ip| (ip, web_port(device)))
+            }
+            None => {
+                self.warn(format!("Device `{device_name}` not found")).await;
+                None
+            }
+        }
+    }
+
+    async fn redraw(&self) {
+        self.cmd(format!(
+            "{} {} {}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Redraw
+        ))
+        .await;
+    }
+
+    // p devices open <device_name>
+    async fn handle_action_open(&mut self, cmd_parts: &[String]) {
+        let Some(name) = cmd_parts.get(3).map(|name| globals::get_device_name(name)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name>",
+                Action::Open.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        if self.mode != Mode::Gui {
+            self.warn(format!("`{}` is for GUI only", Action::Open))
+                .await;
+            return;
+        }
+
+        if !self.devices.iter().any(|device| device.name == name) {
+            self.warn(format!("Device `{name}` not found")).await;
+            return;
+        }
+
+        // temperature of the last day for the sparkline
+        let from = utils::time::ts().saturating_sub(POPUP_HISTORY_SECS);
+        self.popup_temperatures = history_path(&name)
+            .and_then(|path| store::load::<DeviceRecord>(&path))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|record| match record {
+                DeviceRecord::Temperature { ts, value } if *ts >= from => Some(*value),
+                _ => None,
+            })
+            .collect();
+        self.popup = Some(name);
+        self.popup_button = 0;
+
+        self.cmd(format!(
+            "{} {} {} {MODULE}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Popup
+        ))
+        .await;
+    }
+
+    async fn handle_popup_close(&mut self) {
+        self.popup = None;
+
+        self.cmd(format!(
+            "{} {} {}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Popup
+        ))
+        .await;
+    }
+
+    async fn handle_popup_enter(&mut self) {
+        let Some(name) = self.popup.clone() else {
+            return;
+        };
+
+        match POPUP_BUTTONS[self.popup_button] {
+            Action::Wake => {
+                self.cmd(format!(
+                    "{} {} {} {name}",
+                    consts::P,
+                    plugin_wol::MODULE,
+                    Action::Wake
+                ))
+                .await;
+            }
+            Action::Ping => {
+                let ip = self
+                    .devices
+                    .iter()
+                    .find(|device| device.name == name)
+                    .and_then(|device| device.ip());
+                match ip {
+                    Some(ip) => {
+                        self.cmd(format!(
+                            "{} {} {} {ip}",
+                            consts::P,
+                            plugin_ping::MODULE,
+                            Action::Ping
+                        ))
+                        .await;
+                    }
+                    None => {
+                        self.warn(format!("Device `{name}` has no IP")).await;
+                    }
+                }
+            }
+            _ => {
+                // prefill the prompt, the operator types the command
+                self.handle_popup_close().await;
+                self.cmd(format!(
+                    "{} {} {} {}",
+                    consts::P,
+                    plugin_gui::MODULE,
+                    Action::OutputUpdate,
+                    shell_words::quote(&format!("{} {MODULE} {} {name} ", consts::P, Action::Cmd))
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p devices key <key>
+    async fn handle_action_key(&mut self, cmd_parts: &[String]) {
+        if let Some(key) = cmd_parts.get(3) {
+            match key.parse::<Key>() {
+                Ok(Key::ControlX) => self.handle_popup_close().await,
+                Ok(Key::Enter) => self.handle_popup_enter().await,
+                Ok(Key::Left) => {
+                    self.popup_button = self.popup_button.saturating_sub(1);
+                    self.redraw().await;
+                }
+                Ok(Key::Right) => {
+                    if self.popup_button + 1 < POPUP_BUTTONS.len() {
+                        self.popup_button += 1;
+                    }
+                    self.redraw().await;
+                }
+                Ok(k @ Key::AltUp)
+                | Ok(k @ Key::AltDown)
+                | Ok(k @ Key::AltLeft)
+                | Ok(k @ Key::AltRight)
+                | Ok(k @ Key::AltW)
+                | Ok(k @ Key::AltS)
+                | Ok(k @ Key::AltA)
+                | Ok(k @ Key::AltD) => {
+                    (
+                        self.panel_info.x,
+                        self.panel_info.y,
+                        self.panel_info.w,
+                        self.panel_info.h,
+                    ) = self
+                        .handle_action_key_position(
+                            k,
+                            self.panel_info.x,
+                            self.panel_info.y,
+                            self.panel_info.w,
+                            self.panel_info.h,
+                        )
+                        .await;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    // `width` of the popup, the sparkline is downsampled to fit
+    fn popup_lines(&self, width: usize) -> Vec<Line<'static>> {
+        let Some(device) = self
+            .popup
+            .as_ref()
+            .and_then(|name| self.devices.iter().find(|device| device.name == *name))
+        else {
+            return vec![];
+        };
+        let device_cfg = device_cfg(&device.name);
+        let state = device.state.clone().unwrap_or_default();
+
+        let last_seen = if device.ts == 0 {
+            last_update_str(device.ts)
+        } else {
+            format!(
+                "{} ({} ago)",
+                last_update_str(device.ts),
+                utils::time::uptime_str(utils::time::ts().saturating_sub(device.ts))
+            )
+        };
+        let temperatures = match (
+            self.popup_temperatures.iter().cloned().reduce(f32::min),
+            self.popup_temperatures.iter().cloned().reduce(f32::max),
+        ) {
+            (Some(min), Some(max)) => {
+                let range = format!(
+                    " ({} ~ {})",
+                    common::temperature_str(Some(min)),
+                    common::temperature_str(Some(max))
+                );
+                let points = width
+                    .saturating_sub(POPUP_KEY_WIDTH + 1 + range.width())
+                    .max(1);
+                format!(
+                    "{}{range}",
+                    common::sparkline(&common::downsample(&self.popup_temperatures, points))
+                )
+            }
+            _ => consts::NA.to_string(),
+        };
+
+        let fields = [
+            ("Status", status_str(device).to_string()),
+            ("Last seen", last_seen),
+            ("Version", version_str(&device.version).to_string()),
+            (
+                "Tailscale IP",
+                tailscale_ip_str(&device.tailscale_ip).to_string(),
+            ),
+            ("LAN IP", tailscale_ip_str(&device.lan_ip).to_string()),
+            ("Temperature", common::temperature_str(device.temperature)),
+            ("Temp 24h", temperatures),
+            ("App uptime", app_uptime_str(device.app_uptime)),
+            ("CPU", cpu_load_str(&device.state)),
+            ("Memory", memory_str(&device.state)),
+            ("Disk", state.disk_str()),
+            ("Load average", state.load_average_str()),
+            ("OS", format!("{} {}", state.os, state.arch)),
+            ("IPs", state.ips.join(", ")),
+            (
+                "Alias",
+                device_cfg
+                    .as_ref()
+                    .and_then(|device_cfg| device_cfg.alias.clone())
+                    .unwrap_or(consts::NA.to_string()),
+            ),
+            (
+                "Groups",
+                device_cfg
+                    .as_ref()
+                    .map(|device_cfg| device_cfg.groups.join(", "))
+                    .unwrap_or_default(),
+            ),
+            (
+                "Location",
+                device_cfg
+                    .as_ref()
+                    .and_then(|device_cfg| device_cfg.location.clone())
+                    .unwrap_or(consts::NA.to_string()),
+            ),
+            (
+                "MAC",
+                device_cfg
+                    .as_ref()
+                    .and_then(|device_cfg| device_cfg.mac.clone())
+                    .unwrap_or(consts::NA.to_string()),
+            ),
+        ];
+
+        let mut lines: Vec<Line> = fields
+            .into_iter()
+            .map(|(key, value)| Line::from(format!("{key:<POPUP_KEY_WIDTH$} {value}")))
+            .collect();
+
+        let theme = globals::get_theme();
+        let buttons: Vec<Span> = POPUP_BUTTONS
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, button)| {
+                let style = if idx == self.popup_button {
+                    theme.selected
+                } else {
+                    Style::default()
+                };
+                [Span::raw(" "), Span::styled(format!("[ {button} ]"), style)]
+            })
+            .collect();
+        lines.push(Line::from(""));
+        lines.push(Line::from(buttons));
+
+        lines
+    }
+
+    // p devices ls <device_name> <path>
+    async fn handle_action_ls(&self, cmd_parts: &[String]) {
+        let (Some(device_name), Some(path)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name> <path>",
+                Action::Ls.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+        let device_name = globals::get_device_name(device_name);
+        let Some((ip, port)) = self.device_addr(&device_name).await else {
+            return;
+        };
+
+        let msg_tx = self.msg_tx.clone();
+        let path = path.to_string();
+        tokio::spawn(async move {
+            match api::post_get_folder_meta(
+                &msg_tx,
+                MODULE,
+                &ip,
+                port,
+                &api::GetFolderMetaRequest {
+                    foldername: path.clone(),
+                },
+            )
+            .await
+            {
+                Ok(folder_meta) => {
+                    msgs::info(
+                        &msg_tx,
+                        MODULE,
+                        &format!("`{device_name}`:{path} ({} files)", folder_meta.files.len()),
+                    )
+                    .await;
+                    for file in &folder_meta.files {
+                        msgs::info(
+                            &msg_tx,
+                            MODULE,
+                            &format!("  {:<25} {}", file.mtime, file.filename),
+                        )
+                        .await;
+                    }
+                }
+                Err(e) => msgs::warn(&msg_tx, MODULE, &e.to_string()).await,
+            }
+        });
+    }
+
+    // p devices get <device_name> <remote_path> [local_path]
+    async fn handle_action_get(&self, cmd_parts: &[String]) {
+        let (Some(device_name), Some(remote_path)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name> <remote_path> [local_path]",
+                Action::Get.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+        let device_name = globals::get_device_name(device_name);
+        let Some((ip, port)) = self.device_addr(&device_name).await else {
+            return;
+        };
+
+        let msg_tx = self.msg_tx.clone();
+        let remote_path = remote_path.to_string();
+        let local_path = cmd_parts.get(5).unwrap_or(&remote_path).to_string();
+        tokio::spawn(async move {
+            let ret = api::post_download(
+                &msg_tx,
+                MODULE,
+                &ip,
+                port,
+                &api::DownloadRequest {
+                    data: api::DownloadData {
+                        filename: remote_path.clone(),
+                    },
+                },
+            )
+            .await;
+
+            match ret {
+                Ok(response) => {
+                    match nas::write_file(&local_path, &response.data.content, &response.data.mtime)
+                        .await
+                    {
+                        Ok(_) => {
+                            msgs::info(
+                                &msg_tx,
+                                MODULE,
+                                &format!("`{device_name}`:{remote_path} -> {local_path}"),
+                            )
+                            .await
+                        }
+                        Err(e) => {
+                            msgs::warn(
+                                &msg_tx,
+                                MODULE,
+                                &format!("Failed to write `{local_path}`: {e}"),
+                            )
+                            .await
+                        }
+                    }
+                }
+                Err(e) => msgs::warn(&msg_tx, MODULE, &e.to_string()).await,
+            }
+        });
+    }
+
+    // p devices put <device_name> <local_path> [remote_path]
+    async fn handle_action_put(&self, cmd_parts: &[String]) {
+        let (Some(device_name), Some(local_path)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name> <local_path> [remote_path]",
+                Action::Put.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+        if !Path::new(local_path).is_file() {
+            self.warn(format!("File `{local_path}` not found")).await;
+            return;
+        }
+        let device_name = globals::get_device_name(device_name);
+        let Some((ip, port)) = self.device_addr(&device_name).await else {
+            return;
+        };
+
+        let msg_tx = self.msg_tx.clone();
+        let local_path = local_path.to_string();
+        let remote_path = cmd_parts.get(5).unwrap_or(&local_path).to_string();
+        tokio::spawn(async move {
+            api::upload_file(&msg_tx, MODULE, &ip, port, &local_path, &remote_path).await;
+        });
+    }
+
+    // p devices rm <device_name> <remote_path>
+    async fn handle_action_rm(&self, cmd_parts: &[String]) {
+        let (Some(device_name), Some(remote_path)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<device_name> <remote_path>",
+                Action::Rm.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+        let device_name = globals::get_device_name(device_name);
+        let Some((ip, port)) = self.device_addr(&device_name).await else {
+            return;
+        };
+
+        let msg_tx = self.msg_tx.clone();
+        let remote_path = remote_path.to_string();
+        tokio::spawn(async move {
+            api::post_remove(
+                &msg_tx,
+                MODULE,
+                &ip,
+                port,
+                &api::RemoveRequest {
+                    filename: remote_path,
+                },
+            )
+            .await;
+        });
+    }
+
+    // p devices cmd <@group|@all> "<cmd>"
+    async fn handle_cmd_group(&self, target: &str, cmd: &str) {
+        let names = self.targets(target);
+        if names.is_empty() {
+            self.warn(format!("No devices in `{target}`")).await;
+            return;
+        }
+
+        self.info(format!("-> `{target}` ({} devices): `{cmd}`", names.len()))
+            .await;
+
+        let mut join_set = JoinSet::new();
+        let mut skipped = vec![];
+        for name in names {
+            let target = self
+                .devices
+                .iter()
+                .find(|device| device.name == name)
+                .and_then(|device| device.ip().map(|ip| (ip, web_port(device))));
+
+            match target {
+                Some((ip, port)) => {
+                    let cmd = api::CmdRequest {
+                        cmd: cmd.to_string(),
+                    };
+                    join_set.spawn(async move {
+                        let ret = api::request_cmd(&ip, port, &cmd, Some(GROUP_CMD_TIMEOUT)).await;
+                        (name, ret)
+                    });
+                }
+                None => skipped.push(name),
+            }
+        }
+
+        let msg_tx = self.msg_tx.clone();
+        let target = target.to_string();
+        tokio::spawn(async move {
+            let mut rows = vec![];
+            while let Some(res) = join_set.join_next().await {
+                match res {
+                    Ok((name, Ok((status, text)))) => rows.push((
+                        name,
+                        if status.is_success() { "OK" } else { "Failed" },
+                        status.as_u16().to_string(),
+                        text,
+                    )),
+                    Ok((name, Err(e))) => {
+                        rows.push((name, "Error", consts::NA.to_string(), e.to_string()))
+                    }
+                    Err(e) => {
+                        msgs::warn(&msg_tx, MODULE, &format!("A cmd task failed: {e}")).await;
+                    }
+                }
+            }
+            for name in skipped {
+                rows.push((name, "Skipped", consts::NA.to_string(), "no IP".to_string()));
+            }
+            rows.sort_by(|a, b| a.0.cmp(&b.0));
+
+            let succeeded = rows.iter().filter(|row| row.1 == "OK").count();
+            msgs::info(
+                &msg_tx,
+                MODULE,
+                &format!("<- `{target}`: {succeeded}/{} succeeded", rows.len()),
+            )
+            .await;
+            msgs::info(
+                &msg_tx,
+                MODULE,
+                &format!(
+                    "  {:<12} {:<7} {:<4} {}",
+                    "Name", "Result", "HTTP", "Output"
+                ),
+            )
+            .await;
+            for (name, result, status, output) in rows {
+                msgs::info(
+                    &msg_tx,
+                    MODULE,
+                    &format!(
+                        "  {name:<12} {result:<7} {status:<4} {}",
+                        common::shorten(&output.replace('\n', " "), 40, 0)
+                    ),
+                )
+                .await;
+            }
+        });
+    }
+
     pub async fn handle_cmd(&mut self, cmd_parts: &[String]) {
-        if let (Some(device_name), Some(cmd)) = (cmd_parts.get(3), cmd_parts.get(4)) {
+        if let (Some(target), Some(cmd)) = (cmd_parts.get(3), cmd_parts.get(4))
+            && target.starts_with('@')
+        {
+            self.handle_cmd_group(target, cmd).await;
+        } else if let (Some(device_name), Some(cmd)) = (cmd_parts.get(3), cmd_parts.get(4)) {
+            let device_name = &globals::get_device_name(device_name);
             if let Some(device) = self
                 .devices
                 .iter()
                 .find(|device| device.name == *device_name)
             {
-                if let Some(ip) = &device.tailscale_ip {
+                if let Some(ip) = device.ip() {
                     api::post_cmd(
                         &self.msg_tx,
                         device_name,
                         MODULE,
-                        ip,
+                        &ip,
+                        web_port(device),
                         &api::CmdRequest { cmd: cmd.clone() },
                     )
                     .await;
                 } else {
-                    self.warn(format!("Device `{device_name}` has no Tailscale IP"))
-                        .await;
+                    self.warn(format!("Device `{device_name}` has no IP")).await;
                 }
             } else {
                 self.warn(format!("Device `{device_name}` not found")).await;
             }
         } else {
             self.warn(common::MsgTemplate::MissingParameters.format(
-                "<device_name> \"<cmd>\"",
+                "<device_name|@group|@all> \"<cmd>\"",
                 Action::Cmd.as_ref(),
                 &cmd_parts.join(" "),
             ))
@@ -325,24 +1824,161 @@ impl plugins_main::Plugin for PluginUnit {
         &self.msg_tx
     }
 
+    fn panel_info(&self) -> &panel::PanelInfo {
+        &self.panel_info
+    }
+
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     async fn handle_action(&mut self, action: Action, cmd_parts: &[String], _msg: &Msg) {
+        sync_inventory(&mut self.devices);
+
         match action {
             Action::Help => self.handle_action_help().await,
-            Action::Show => self.handle_action_show().await,
+            Action::Show => self.handle_action_show(cmd_parts).await,
+            Action::Gui => {
+                if let Ok(panel_info) = self.handle_action_gui(cmd_parts).await {
+                    self.panel_info = panel_info;
+                }
+            }
+            Action::Open => self.handle_action_open(cmd_parts).await,
+            Action::Key => self.handle_action_key(cmd_parts).await,
             Action::Update => self.handle_action_update(cmd_parts).await,
+            Action::Rollout => self.handle_action_rollout(cmd_parts).await,
             Action::Cmd => self.handle_cmd(cmd_parts).await,
+            Action::Check => self.handle_action_check().await,
+            Action::Stale => self.handle_action_stale(cmd_parts).await,
+            Action::Alert => self.handle_action_alert(cmd_parts).await,
+            Action::History => self.handle_action_history(cmd_parts).await,
+            Action::Wake => self.handle_action_wake(cmd_parts).await,
+            Action::Ls => self.handle_action_ls(cmd_parts).await,
+            Action::Get => self.handle_action_get(cmd_parts).await,
+            Action::Put => self.handle_action_put(cmd_parts).await,
+            Action::Rm => self.handle_action_rm(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
             }
         }
     }
+
+    fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
+        // Clear the panel area
+        let (panel_x, panel_y, panel_width, panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
+
+        let panel_area =
+            panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
+        frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
+
+        // Draw the panel block
+        let panel_block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(
+                "{MODULE} - {}",
+                self.popup.as_deref().unwrap_or(consts::NA)
+            ))
+            .padding(ratatui::widgets::Padding::new(0, 0, 0, 0))
+            .border_type(if active {
+                BorderType::Double
+            } else {
+                BorderType::Plain
+            })
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
+        frame.render_widget(panel_block.clone(), panel_area);
+
+        // Draw the panel content
+        let inner_area = panel_block.inner(panel_area);
+        let text = Paragraph::new(Text::from(self.popup_lines(inner_area.width as usize)))
+            .style(theme.text(active));
+
+        frame.render_widget(text, inner_area);
+
+        // cursor for popup panel
+        let mut stdout = std::io::stdout();
+        execute!(stdout, SetCursorStyle::DefaultUserShape).unwrap();
+    }
 }
 
 pub fn onboard_str(onboard: bool) -> &'static str {
     if onboard { "On" } else { "Off" }
 }
 
+// never-seen devices declared in cfg.toml
+// devices of the inventory in cfg.toml not known yet, read when needed
+// rather than copied once, as cfg may be loaded or changed later
+pub fn sync_inventory(devices: &mut Vec<DevInfo>) {
+    for device_cfg in globals::get_devices() {
+        if !devices.iter().any(|device| device.name == device_cfg.name) {
+            devices.push(DevInfo {
+                ts: 0,
+                name: device_cfg.name,
+                onboard: false,
+                version: None,
+                tailscale_ip: None,
+                temperature: None,
+                app_uptime: None,
+                state: None,
+                stale: false,
+                mqtt_onboard: None,
+                lan_ip: None,
+                web_port: None,
+            });
+        }
+    }
+}
+
+fn device_cfg(name: &str) -> Option<DeviceCfg> {
+    globals::get_devices()
+        .into_iter()
+        .find(|device_cfg| device_cfg.name == name)
+}
+
+fn in_group(name: &str, group: &str) -> bool {
+    device_cfg(name).is_some_and(|device_cfg| device_cfg.groups.iter().any(|g| g == group))
+}
+
+fn expected_online(name: &str) -> bool {
+    device_cfg(name).is_none_or(|device_cfg| device_cfg.expected_online)
+}
+
+// cfg.toml first, then the port announced on the LAN
+fn web_port(device: &DevInfo) -> u16 {
+    match device_cfg(&device.name) {
+        Some(device_cfg) => device_cfg.web_port,
+        None => device.web_port.unwrap_or(consts::WEB_PORT),
+    }
+}
+
+pub fn last_update_str(ts: u64) -> String {
+    if ts == 0 {
+        "Never".to_string()
+    } else {
+        utils::time::ts_str_local(ts)
+    }
+}
+
+fn history_path(name: &str) -> Result<PathBuf> {
+    Ok(PathBuf::from(consts::DATA_FOLDER)
+        .join(MODULE)
+        .join(format!("{}.jsonl", common::check_file_name(name)?)))
+}
+
+pub fn status_str(device: &DevInfo) -> &'static str {
+    if device.stale {
+        "Stale"
+    } else {
+        onboard_str(device.onboard)
+    }
+}
+
 pub fn version_str(version: &Option<String>) -> &str {
     version.as_deref().unwrap_or(consts::NA)
 }
@@ -351,6 +1987,20 @@ pub fn tailscale_ip_str(tailscale_ip: &Option<String>) -> &str {
     tailscale_ip.as_deref().unwrap_or(consts::NA)
 }
 
+pub fn cpu_load_str(state: &Option<DeviceState>) -> String {
+    match state {
+        Some(state) => state.cpu_load_str(),
+        None => consts::NA.to_owned(),
+    }
+}
+
+pub fn memory_str(state: &Option<DeviceState>) -> String {
+    match state {
+        Some(state) => state.memory_str(),
+        None => consts::NA.to_owned(),
+    }
+}
+
 pub fn app_uptime_str(app_uptime: Option<u64>) -> String {
     if let Some(t) = app_uptime {
         utils::time::uptime_str(t)
@@ -0,0 +1,194 @@
+use std::net::{Ipv4Addr, SocketAddr};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::net::UdpSocket;
+use tokio::sync::mpsc::Sender;
+use tokio::time::Duration;
+
+use crate::consts;
+use crate::globals;
+use crate::messages::{self as msgs, Action, DeviceKey, Msg};
+use crate::plugins::{
+    plugin_devices,
+    plugins_main::{self, Plugin},
+};
+use crate::utils::common;
+
+pub const MODULE: &str = "discovery";
+const DISCOVERY_PORT: u16 = 9760;
+const BEACON_INTERVAL: u64 = 30;
+const BEACON_SIZE: usize = 1024;
+const RECV_RETRY_DELAY: u64 = 5;
+const MAX_NAME_LEN: usize = 32;
+
+// announced by every node on the LAN, no broker needed
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Beacon {
+    pub name: String,
+    pub version: String,
+    pub web_port: u16,
+    // filled in by the receiver from the source address
+    pub lan_ip: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct PluginUnit {
+    msg_tx: Sender<Msg>,
+    is_running: bool,
+}
+
+impl PluginUnit {
+    pub async fn new(msg_tx: Sender<Msg>) -> Result<Self> {
+        let mut myself = Self {
+            msg_tx,
+            is_running: false,
+        };
+
+        myself.info(consts::NEW.to_string()).await;
+        myself.init().await;
+
+        Ok(myself)
+    }
+
+    async fn init(&mut self) {
+        self.info(consts::INIT.to_string()).await;
+
+        let socket = match UdpSocket::bind((Ipv4Addr::UNSPECIFIED, DISCOVERY_PORT)).await {
+            Ok(socket) => socket,
+            Err(e) => {
+                self.warn(format!("Failed to bind UDP port {DISCOVERY_PORT}: {e}"))
+                    .await;
+                return;
+            }
+        };
+        if let Err(e) = socket.set_broadcast(true) {
+            self.warn(format!("Failed to enable broadcast: {e}")).await;
+            return;
+        }
+        self.is_running = true;
+
+        self.info(format!(
+            "  Broadcasting beacons on UDP port {DISCOVERY_PORT} every {BEACON_INTERVAL} secs..."
+        ))
+        .await;
+
+        let socket = std::sync::Arc::new(socket);
+
+        // beacons
+        let socket_clone = socket.clone();
+        let msg_tx_clone = self.msg_tx.clone();
+        tokio::spawn(async move {
+            let broadcast = SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT));
+            loop {
+                let beacon = Beacon {
+                    name: globals::get_sys_name(),
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    web_port: consts::WEB_PORT,
+                    lan_ip: None,
+                };
+                if let Ok(beacon) = serde_json::to_vec(&beacon)
+                    && let Err(e) = socket_clone.send_to(&beacon, broadcast).await
+                {
+                    msgs::warn(
+                        &msg_tx_clone,
+                        MODULE,
+                        &format!("Failed to send beacon: {e}"),
+                    )
+                    .await;
+                }
+                tokio::time::sleep(Duration::from_secs(BEACON_INTERVAL)).await;
+            }
+        });
+
+        // peers
+        let msg_tx_clone = self.msg_tx.clone();
+        tokio::spawn(async move {
+            let mut buf = [0u8; BEACON_SIZE];
+            loop {
+                let (len, addr) = match socket.recv_from(&mut buf).await {
+                    Ok(ret) => ret,
+                    Err(e) => {
+                        msgs::warn(
+                            &msg_tx_clone,
+                            MODULE,
+                            &format!("Failed to receive beacon: {e}"),
+                        )
+                        .await;
+                        tokio::time::sleep(Duration::from_secs(RECV_RETRY_DELAY)).await;
+                        continue;
+                    }
+                };
+
+                let Ok(mut beacon) = serde_json::from_slice::<Beacon>(&buf[..len]) else {
+                    continue;
+                };
+                if !valid_name(&beacon.name) || beacon.name == globals::get_sys_name() {
+                    continue;
+                }
+                beacon.lan_ip = Some(addr.ip().to_string());
+
+                if let Ok(payload) = serde_json::to_string(&beacon) {
+                    msgs::cmd(
+                        &msg_tx_clone,
+                        MODULE,
+                        &format!(
+                            "{} {} {} {} {} {}",
+                            consts::P,
+                            plugin_devices::MODULE,
+                            Action::Update,
+                            DeviceKey::Discovery,
+                            shell_words::quote(&beacon.name),
+                            shell_words::quote(&payload)
+                        ),
+                    )
+                    .await;
+                }
+            }
+        });
+    }
+
+    async fn handle_action_show(&self) {
+        self.info(Action::Show.to_string()).await;
+        self.info(format!("  Running: {}", self.is_running)).await;
+        self.info(format!("  UDP port: {DISCOVERY_PORT}")).await;
+        self.info(format!("  Beacon interval: {BEACON_INTERVAL} secs"))
+            .await;
+    }
+
+    async fn handle_action_help(&self) {
+        self.info(Action::Help.to_string()).await;
+    }
+}
+
+#[async_trait]
+impl plugins_main::Plugin for PluginUnit {
+    fn name(&self) -> &str {
+        MODULE
+    }
+
+    fn msg_tx(&self) -> &Sender<Msg> {
+        &self.msg_tx
+    }
+
+    async fn handle_action(&mut self, action: Action, _cmd_parts: &[String], _msg: &Msg) {
+        match action {
+            Action::Help => self.handle_action_help().await,
+            Action::Show => self.handle_action_show().await,
+            _ => {
+                self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
+                    .await
+            }
+        }
+    }
+}
+
+// anyone on the LAN can send a beacon, the name ends up in commands and file names
+fn valid_name(name: &str) -> bool {
+    (1..=MAX_NAME_LEN).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
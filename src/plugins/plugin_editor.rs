@@ -7,7 +7,6 @@ use ratatui::{
     Frame,
     crossterm::{cursor::SetCursorStyle, execute},
     layout::Position,
-    style::{Color, Style},
     text::{Line, Span, Text},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
@@ -42,6 +41,8 @@ impl PluginUnit {
                 y: 0,
                 w: 0,
                 h: 0,
+                rect: None,
+                area: ratatui::layout::Rect::default(),
             },
             cursor_position: (0, 0),
         };
@@ -261,6 +262,7 @@ impl PluginUnit {
                 &self.msg_tx,
                 MODULE,
                 &server_ip,
+                consts::WEB_PORT,
                 &api::GetFolderMetaRequest {
                     foldername: consts::NAS_EDITOR_FOLDER
                         .trim_start_matches('/')
@@ -298,8 +300,15 @@ impl PluginUnit {
                     None => {
                         self.info(format!("Server <= Local: `{}`", local_file.filename))
                             .await;
-                        api::upload_file(&self.msg_tx, MODULE, &server_ip, &file_path, &file_path)
-                            .await;
+                        api::upload_file(
+                            &self.msg_tx,
+                            MODULE,
+                            &server_ip,
+                            consts::WEB_PORT,
+                            &file_path,
+                            &file_path,
+                        )
+                        .await;
                     }
                     Some(remote_file) => {
                         if remote_file.hash != local_file.hash {
@@ -311,6 +320,7 @@ impl PluginUnit {
                                     &self.msg_tx,
                                     MODULE,
                                     &server_ip,
+                                    consts::WEB_PORT,
                                     &file_path,
                                     &file_path,
                                 )
@@ -319,8 +329,14 @@ impl PluginUnit {
                                 self.info(format!("Server => Local: `{}`", local_file.filename))
                                     .await;
 
-                                api::download_file(&self.msg_tx, MODULE, &server_ip, &file_path)
-                                    .await;
+                                api::download_file(
+                                    &self.msg_tx,
+                                    MODULE,
+                                    &server_ip,
+                                    consts::WEB_PORT,
+                                    &file_path,
+                                )
+                                .await;
                             }
                         } else {
                             self.info(format!("Server == Local: `{}`", local_file.filename))
@@ -341,7 +357,14 @@ impl PluginUnit {
 
                     self.info(format!("Server => Local: `{}`", remote_file.filename))
                         .await;
-                    api::download_file(&self.msg_tx, MODULE, &server_ip, &file_path).await;
+                    api::download_file(
+                        &self.msg_tx,
+                        MODULE,
+                        &server_ip,
+                        consts::WEB_PORT,
+                        &file_path,
+                    )
+                    .await;
                 }
             }
         } else {
@@ -384,6 +407,7 @@ impl PluginUnit {
             &self.msg_tx,
             MODULE,
             &globals::get_server_ip().unwrap_or_default(),
+            consts::WEB_PORT,
             &api::RemoveRequest {
                 filename: full_path.trim_start_matches('/').to_string(),
             },
@@ -406,19 +430,21 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         // Clear the panel area
-        let (panel_x, panel_y, panel_width, panel_height) = panel::caculate_position(
-            frame,
-            self.panel_info.x,
-            self.panel_info.y,
-            self.panel_info.w,
-            self.panel_info.h,
-        );
+        let (panel_x, panel_y, panel_width, panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
 
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
 
         // Draw the panel block
         let panel_block = Block::default()
@@ -430,7 +456,9 @@ impl plugins_main::Plugin for PluginUnit {
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
@@ -454,7 +482,7 @@ impl plugins_main::Plugin for PluginUnit {
             .collect();
 
         let text = Paragraph::new(Text::from(lines))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }))
+            .style(theme.text(active))
             .scroll((scroll_offset, 0));
 
         frame.render_widget(text, panel_block.inner(panel_area));
@@ -1,27 +1,36 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::crossterm::event::{
+    self, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use strum::IntoEnumIterator;
 use tokio::sync::{Mutex, broadcast, mpsc::Sender};
 use tokio::task;
 
 use crate::consts;
-use crate::messages::{self as msgs, Action, Key, Msg};
+use crate::globals;
+use crate::messages::{self as msgs, Action, Key, MouseKind, Msg};
 use crate::plugins::plugins_main::{self, Plugin};
-use crate::utils::common;
+use crate::utils::{
+    common,
+    prompt::{self, Prompt},
+    store,
+};
 
 pub const MODULE: &str = "gui";
 const PROMPT: &str = "> ";
 const OUTPUT_PANEL: &str = "command";
+const SEARCH: &str = "/";
+const HISTORY_FILE: &str = "history.jsonl";
 
 #[derive(Debug)]
 pub struct PluginUnit {
     msg_tx: Sender<Msg>,
     shutdown_tx: broadcast::Sender<()>,
-    output: Arc<Mutex<String>>,
-    history: Arc<Mutex<Vec<String>>>,
-    history_index: Arc<Mutex<usize>>,
+    prompt: Arc<Mutex<Prompt>>,
 }
 
 impl PluginUnit {
@@ -29,9 +38,7 @@ impl PluginUnit {
         let myself = Self {
             msg_tx: msg_tx.clone(),
             shutdown_tx,
-            output: Arc::new(Mutex::new(String::new())),
-            history: Arc::new(Mutex::new(vec![])),
-            history_index: Arc::new(Mutex::new(0)),
+            prompt: Arc::new(Mutex::new(Prompt::new(vec![]))),
         };
 
         myself.info(consts::NEW.to_string()).await;
@@ -43,67 +50,66 @@ impl PluginUnit {
     async fn init(&self) {
         self.info(consts::INIT.to_string()).await;
 
+        match store::load::<String>(&history_path()) {
+            Ok(mut history) => {
+                // the file only grows while running, trimmed at startup
+                if history.len() > prompt::HISTORY_CAPACITY {
+                    history.drain(..history.len() - prompt::HISTORY_CAPACITY);
+                    if let Err(e) = store::rewrite(&history_path(), &history) {
+                        self.warn(format!("Failed to trim history: {e}")).await;
+                    }
+                }
+                self.info(format!("  History: {}", history.len())).await;
+                *self.prompt.lock().await = Prompt::new(history);
+            }
+            Err(e) => self.warn(format!("Failed to load history: {e}")).await,
+        }
+
         // update prompt
-        output_update(&self.msg_tx, PROMPT).await;
+        output_update(&self.msg_tx, &*self.prompt.lock().await).await;
 
         let shutdown_rx = self.shutdown_tx.subscribe();
-        let output_clone = Arc::clone(&self.output);
-        let history_clone = Arc::clone(&self.history);
-        let history_index_clone = Arc::clone(&self.history_index);
+        let prompt_clone = Arc::clone(&self.prompt);
         tokio::spawn(start_input_loop(
             self.msg_tx.clone(),
             shutdown_rx,
-            output_clone,
-            history_clone,
-            history_index_clone,
+            prompt_clone,
         ));
     }
 
     async fn handle_action_show(&self) {
         self.info(Action::Show.to_string()).await;
+        self.info(format!("  History: {}", history_path().display()))
+            .await;
     }
 
     async fn handle_action_help(&self) {
         self.info(Action::Help.to_string()).await;
     }
 
-    async fn handle_action_key_up(&mut self) {
-        let mut output = self.output.lock().await;
-        let history = self.history.lock().await;
-        let mut history_index = self.history_index.lock().await;
+    // p gui output_update "<text>", prefill the prompt
+    async fn handle_action_output_update(&mut self, cmd_parts: &[String]) {
+        let mut prompt = self.prompt.lock().await;
+        prompt.set(&cmd_parts.get(3).cloned().unwrap_or_default());
 
-        if *history_index > 0 {
-            *history_index -= 1;
-            *output = history[*history_index].clone();
-        }
-
-        output_update(&self.msg_tx, &format!("{PROMPT}{output}")).await;
-    }
-
-    async fn handle_action_key_down(&mut self) {
-        let mut output = self.output.lock().await;
-        let history = self.history.lock().await;
-        let mut history_index = self.history_index.lock().await;
-
-        if *history_index < history.len() {
-            *history_index += 1;
-            if *history_index < history.len() {
-                *output = history[*history_index].clone();
-            } else {
-                output.clear();
-            }
-        }
-
-        output_update(&self.msg_tx, &format!("{PROMPT}{output}")).await;
+        output_update(&self.msg_tx, &prompt).await;
     }
 
+    // from the command panel when it is active
     async fn handle_action_key(&mut self, cmd_parts: &[String]) {
         if let Some(key) = cmd_parts.get(3) {
+            let mut prompt = self.prompt.lock().await;
             match key.parse::<Key>() {
-                Ok(Key::Up) => self.handle_action_key_up().await,
-                Ok(Key::Down) => self.handle_action_key_down().await,
-                _ => (),
+                Ok(Key::Up) => prompt.history_up(),
+                Ok(Key::Down) => prompt.history_down(),
+                Ok(Key::Left) => prompt.left(),
+                Ok(Key::Right) => prompt.right(),
+                Ok(Key::Home) => prompt.home(),
+                Ok(Key::End) => prompt.end(),
+                _ => return,
             }
+
+            output_update(&self.msg_tx, &prompt).await;
         }
     }
 }
@@ -123,6 +129,7 @@ impl plugins_main::Plugin for PluginUnit {
             Action::Help => self.handle_action_help().await,
             Action::Show => self.handle_action_show().await,
             Action::Key => self.handle_action_key(cmd_parts).await,
+            Action::OutputUpdate => self.handle_action_output_update(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
@@ -131,45 +138,151 @@ impl plugins_main::Plugin for PluginUnit {
     }
 }
 
+fn history_path() -> PathBuf {
+    PathBuf::from(consts::DATA_FOLDER)
+        .join(MODULE)
+        .join(HISTORY_FILE)
+}
+
 //
 // CLI input handling
 //
 
-async fn handle_keycode_char(output: &Arc<Mutex<String>>, msg_tx: &Sender<Msg>, key: char) {
-    let mut output = output.lock().await;
-    output.push(key);
-    output_update(msg_tx, &format!("{PROMPT}{output}")).await;
+// `/<pattern>` searches in the active panel as it is typed
+async fn search(msg_tx: &Sender<Msg>, pattern: &str) {
+    msgs::cmd(
+        msg_tx,
+        MODULE,
+        &format!(
+            "{} {} {} {}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Search,
+            shell_words::quote(pattern)
+        ),
+    )
+    .await;
+}
+
+// `p <plugin> <action>`
+fn completions(words: &[&str]) -> Vec<String> {
+    match words {
+        [] => [consts::P, consts::Q, consts::QUIT, consts::EXIT]
+            .iter()
+            .map(|command| command.to_string())
+            .collect(),
+        [consts::P] => {
+            let mut plugins = globals::get_plugins();
+            plugins.push(plugins_main::MODULE.to_string());
+            plugins.sort();
+            plugins
+        }
+        [consts::P, _] => Action::iter().map(|action| action.to_string()).collect(),
+        _ => vec![],
+    }
 }
 
-async fn handle_keycode_backspace(output: &Arc<Mutex<String>>, msg_tx: &Sender<Msg>) {
-    let mut output = output.lock().await;
-    output.pop();
-    output_update(msg_tx, &format!("{PROMPT}{output}")).await;
+async fn handle_keycode_char(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>, key: char) {
+    let mut prompt = prompt.lock().await;
+    prompt.insert(key);
+    output_update(msg_tx, &prompt).await;
+
+    if !prompt.is_reverse_search()
+        && let Some(pattern) = prompt.input().strip_prefix(SEARCH)
+    {
+        search(msg_tx, pattern).await;
+    }
 }
 
-async fn handle_keycode_enter(
-    output: &Arc<Mutex<String>>,
-    msg_tx: &Sender<Msg>,
-    history: &Arc<Mutex<Vec<String>>>,
-    history_index: &Arc<Mutex<usize>>,
-) {
-    let mut output = output.lock().await;
-    let mut history = history.lock().await;
-    let mut history_index = history_index.lock().await;
-
-    // ignore if the input is as the same as the last one
-    if history.is_empty() || *history.last().unwrap() != *output {
-        // ignore enter only
-        if !output.is_empty() {
-            history.push(output.clone());
-            *history_index = history.len();
+async fn handle_keycode_backspace(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>) {
+    let mut prompt = prompt.lock().await;
+    let searching = !prompt.is_reverse_search() && prompt.input().starts_with(SEARCH);
+    prompt.backspace();
+    output_update(msg_tx, &prompt).await;
+
+    if searching {
+        search(
+            msg_tx,
+            prompt.input().strip_prefix(SEARCH).unwrap_or_default(),
+        )
+        .await;
+    }
+}
+
+// leaves the reverse search or the search, if any
+async fn handle_keycode_esc(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>) {
+    let mut prompt = prompt.lock().await;
+    if prompt.is_reverse_search() {
+        prompt.reverse_search_cancel();
+    } else {
+        if prompt.input().starts_with(SEARCH) {
+            search(msg_tx, "").await;
+        }
+        prompt.clear();
+    }
+    output_update(msg_tx, &prompt).await;
+}
+
+async fn handle_keycode_enter(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>) {
+    let mut prompt = prompt.lock().await;
+    prompt.reverse_search_accept();
+    let input = prompt.input().to_string();
+
+    // enter only goes to the active panel, e.g. to open the selected item
+    if input.is_empty() {
+        handle_keycode_key(msg_tx, Key::Enter).await;
+        return;
+    }
+
+    // the search stays, Enter on the empty prompt goes to the next match
+    if !input.starts_with(SEARCH) {
+        // ignore if the input is as the same as the last one
+        if prompt.push_history(&input)
+            && let Err(e) = store::append(&history_path(), &input)
+        {
+            msgs::warn(msg_tx, MODULE, &format!("Failed to save history: {e}")).await;
         }
+
+        msgs::cmd(msg_tx, MODULE, &input).await;
     }
 
-    msgs::cmd(msg_tx, MODULE, &output).await;
+    prompt.clear();
+    output_update(msg_tx, &prompt).await;
+}
+
+// completes the prompt if any, else the next panel
+async fn handle_keycode_tab(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>) {
+    let mut prompt = prompt.lock().await;
+    if prompt.input().is_empty() || prompt.input().starts_with(SEARCH) {
+        handle_keycode_key(msg_tx, Key::Tab).await;
+        return;
+    }
 
-    output.clear();
-    output_update(msg_tx, &format!("{PROMPT}{output}")).await;
+    prompt.reverse_search_accept();
+    let matches = prompt.complete(completions);
+    if !matches.is_empty() {
+        msgs::info(msg_tx, MODULE, &matches.join(" ")).await;
+    }
+    output_update(msg_tx, &prompt).await;
+}
+
+// moves in the prompt if any, else goes to the active panel
+async fn handle_keycode_move(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>, key: Key) {
+    let mut prompt = prompt.lock().await;
+    if prompt.input().is_empty() && !prompt.is_reverse_search() {
+        handle_keycode_key(msg_tx, key).await;
+        return;
+    }
+
+    prompt.reverse_search_accept();
+    match key {
+        Key::Left => prompt.left(),
+        Key::Right => prompt.right(),
+        Key::Home => prompt.home(),
+        Key::End => prompt.end(),
+        _ => (),
+    }
+    output_update(msg_tx, &prompt).await;
 }
 
 async fn handle_keycode_key(msg_tx: &Sender<Msg>, key: Key) {
@@ -186,27 +299,29 @@ async fn handle_keycode_key(msg_tx: &Sender<Msg>, key: Key) {
     .await;
 }
 
-async fn handle_keycode(
-    output: &Arc<Mutex<String>>,
-    msg_tx: &Sender<Msg>,
-    key: KeyCode,
-    history: &Arc<Mutex<Vec<String>>>,
-    history_index: &Arc<Mutex<usize>>,
-) {
+async fn handle_keycode(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>, key: KeyCode) {
     match key {
         // Normal character input
-        KeyCode::Char(c) => handle_keycode_char(output, msg_tx, c).await,
-        KeyCode::Backspace => handle_keycode_backspace(output, msg_tx).await,
-        KeyCode::Enter => handle_keycode_enter(output, msg_tx, history, history_index).await,
+        KeyCode::Char(c) => handle_keycode_char(prompt, msg_tx, c).await,
+        KeyCode::Backspace => handle_keycode_backspace(prompt, msg_tx).await,
+        KeyCode::Delete => {
+            let mut prompt = prompt.lock().await;
+            prompt.delete();
+            output_update(msg_tx, &prompt).await;
+        }
+        KeyCode::Enter => handle_keycode_enter(prompt, msg_tx).await,
+        KeyCode::Esc => handle_keycode_esc(prompt, msg_tx).await,
+        KeyCode::Tab => handle_keycode_tab(prompt, msg_tx).await,
+        KeyCode::Left => handle_keycode_move(prompt, msg_tx, Key::Left).await,
+        KeyCode::Right => handle_keycode_move(prompt, msg_tx, Key::Right).await,
+        KeyCode::Home => handle_keycode_move(prompt, msg_tx, Key::Home).await,
+        KeyCode::End => handle_keycode_move(prompt, msg_tx, Key::End).await,
 
         // Special keys (send to panels plugin)
-        KeyCode::Tab => handle_keycode_key(msg_tx, Key::Tab).await,
         KeyCode::Up => handle_keycode_key(msg_tx, Key::Up).await,
         KeyCode::Down => handle_keycode_key(msg_tx, Key::Down).await,
-        KeyCode::Left => handle_keycode_key(msg_tx, Key::Left).await,
-        KeyCode::Right => handle_keycode_key(msg_tx, Key::Right).await,
-        KeyCode::Home => handle_keycode_key(msg_tx, Key::Home).await,
-        KeyCode::End => handle_keycode_key(msg_tx, Key::End).await,
+        KeyCode::PageUp => handle_keycode_key(msg_tx, Key::PageUp).await,
+        KeyCode::PageDown => handle_keycode_key(msg_tx, Key::PageDown).await,
         _ => {}
     }
 }
@@ -222,27 +337,72 @@ async fn handle_keycode_alt(msg_tx: &Sender<Msg>, key: KeyCode) {
         KeyCode::Char('s') => handle_keycode_key(msg_tx, Key::AltS).await,
         KeyCode::Char('a') => handle_keycode_key(msg_tx, Key::AltA).await,
         KeyCode::Char('d') => handle_keycode_key(msg_tx, Key::AltD).await,
+        KeyCode::Char(c @ '1'..='9') => {
+            handle_keycode_key(msg_tx, msgs::ALT_DIGITS[(c as u8 - b'1') as usize].clone()).await
+        }
         _ => (),
     };
 }
 
-async fn handle_keycode_control(msg_tx: &Sender<Msg>, key: KeyCode) {
+// Ctrl-A/E/W/U/R edit the prompt
+async fn handle_keycode_control(prompt: &Arc<Mutex<Prompt>>, msg_tx: &Sender<Msg>, key: KeyCode) {
     match key {
         KeyCode::Char('x') => handle_keycode_key(msg_tx, Key::ControlX).await,
         KeyCode::Char('s') => handle_keycode_key(msg_tx, Key::ControlS).await,
+        KeyCode::Char('f') => handle_keycode_key(msg_tx, Key::ControlF).await,
+        KeyCode::Char(c @ ('a' | 'e' | 'w' | 'u' | 'r')) => {
+            let mut prompt = prompt.lock().await;
+            match c {
+                'r' => prompt.reverse_search(),
+                c => {
+                    prompt.reverse_search_accept();
+                    match c {
+                        'a' => prompt.home(),
+                        'e' => prompt.end(),
+                        'w' => prompt.delete_word(),
+                        _ => prompt.delete_to_start(),
+                    }
+                }
+            }
+            output_update(msg_tx, &prompt).await;
+        }
         _ => (),
     };
 }
 
+// moves without a button are not sent
+async fn handle_mouse(msg_tx: &Sender<Msg>, mouse: MouseEvent) {
+    let kind = match mouse.kind {
+        MouseEventKind::Down(MouseButton::Left) => MouseKind::Down,
+        MouseEventKind::Up(MouseButton::Left) => MouseKind::Up,
+        MouseEventKind::Drag(MouseButton::Left) => MouseKind::Drag,
+        MouseEventKind::ScrollUp => MouseKind::ScrollUp,
+        MouseEventKind::ScrollDown => MouseKind::ScrollDown,
+        _ => return,
+    };
+
+    msgs::cmd(
+        msg_tx,
+        MODULE,
+        &format!(
+            "{} {} {} {kind} {} {}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Mouse,
+            mouse.column,
+            mouse.row
+        ),
+    )
+    .await;
+}
+
 async fn start_input_loop(
     msg_tx: Sender<Msg>,
     mut shutdown_rx: broadcast::Receiver<()>,
-    output: Arc<Mutex<String>>,
-    history: Arc<Mutex<Vec<String>>>,
-    history_index: Arc<Mutex<usize>>,
+    prompt: Arc<Mutex<Prompt>>,
 ) {
-    // 建立 channel 傳送 key event（spawn_blocking 到 async）
-    let (input_tx, mut input_rx) = tokio::sync::mpsc::channel::<KeyEvent>(32);
+    // 建立 channel 傳送 key/mouse event（spawn_blocking 到 async）
+    let (input_tx, mut input_rx) = tokio::sync::mpsc::channel::<Event>(32);
     use std::sync::{
         Arc,
         atomic::{AtomicBool, Ordering},
@@ -256,9 +416,9 @@ async fn start_input_loop(
             // 非同步 poll，避免卡住
             if event::poll(std::time::Duration::from_millis(100)).unwrap_or(false) {
                 #[allow(clippy::collapsible_if)]
-                if let Ok(Event::Key(key)) = event::read() {
-                    // 把 key 傳出去給 async task 處理
-                    if input_tx.blocking_send(key).is_err() {
+                if let Ok(event @ (Event::Key(_) | Event::Mouse(_))) = event::read() {
+                    // 把 event 傳出去給 async task 處理
+                    if input_tx.blocking_send(event).is_err() {
                         break;
                     }
                 }
@@ -273,13 +433,19 @@ async fn start_input_loop(
 
     loop {
         tokio::select! {
-            Some(key) = input_rx.recv() => {
-                if key.modifiers == KeyModifiers::ALT {
-                    handle_keycode_alt(&msg_tx, key.code).await;
-                } else if key.modifiers == KeyModifiers::CONTROL {
-                    handle_keycode_control(&msg_tx, key.code).await;
-                } else {
-                    handle_keycode(&output, &msg_tx, key.code, &history, &history_index).await;
+            Some(event) = input_rx.recv() => {
+                match event {
+                    Event::Key(key) if key.modifiers == KeyModifiers::ALT => {
+                        handle_keycode_alt(&msg_tx, key.code).await;
+                    }
+                    Event::Key(key) if key.modifiers == KeyModifiers::CONTROL => {
+                        handle_keycode_control(&prompt, &msg_tx, key.code).await;
+                    }
+                    Event::Key(key) => {
+                        handle_keycode(&prompt, &msg_tx, key.code).await;
+                    }
+                    Event::Mouse(mouse) => handle_mouse(&msg_tx, mouse).await,
+                    _ => (),
                 }
             }
             _ = shutdown_rx.recv() => {
@@ -293,14 +459,16 @@ async fn start_input_loop(
     let _ = input_task.await;
 }
 
-async fn output_update(msg_tx: &Sender<Msg>, msg: &str) {
+async fn output_update(msg_tx: &Sender<Msg>, prompt: &Prompt) {
+    let (output, cursor) = prompt.display(PROMPT);
     msgs::cmd(
         msg_tx,
         MODULE,
         &format!(
-            "{} {OUTPUT_PANEL} {} '{msg}'",
+            "{} {OUTPUT_PANEL} {} {} {cursor}",
             consts::P,
             Action::OutputUpdate,
+            shell_words::quote(&output)
         ),
     )
     .await;
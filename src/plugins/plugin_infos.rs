@@ -1,29 +1,36 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{Datelike, NaiveDate};
 use ratatui::{
     Frame,
-    style::{Color, Style},
     text::{Line, Span, Text},
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 use tokio::sync::mpsc::Sender;
 
 use crate::consts;
-use crate::messages::{Action, DeviceKey, InfoKey, Key, Msg, WeatherKey};
+use crate::globals;
+use crate::messages::{Action, DeviceKey, InfoKey, Key, MouseKind, Msg, WeatherKey};
 use crate::plugins::{
     plugin_devices, plugin_weather,
     plugins_main::{self, Plugin},
 };
 use crate::utils::{
-    self, common, panel,
-    weather::{self, City, Weather, WeatherDaily},
+    self, astro, common,
+    device_state::DeviceState,
+    panel, store,
+    weather::{self, AirQuality, City, Weather, WeatherDaily, WeatherHourly},
+    weather_history::{self, WeatherHistory, WeatherRecord},
 };
 
 pub const MODULE: &str = "infos";
-const PAGES: usize = 3;
+const PAGES: usize = 6;
+const HISTORY_PAGE: usize = 4;
+const HISTORY_DAYS: u64 = 7;
+const HISTORY_TREND_POINTS: usize = 40;
 const ADD_PARAMS: &str = "<name> <latitude> <longitude>";
 const NO_DATA: &str = "No data";
+// the city name is above the table
+const HOURLY_PAGE: usize = 3;
 
 #[derive(Debug)]
 pub struct PluginUnit {
@@ -32,10 +39,17 @@ pub struct PluginUnit {
     output: String,
     page_idx: usize,
     sub_title: Vec<String>,
+    // lines from the top, by the mouse wheel
+    scroll: usize,
     // page 0
     devices: Vec<plugin_devices::DevInfo>,
-    // page 1, 2
+    device_idx: usize,
+    // page 1, 2, 3, 5
     cities: Vec<City>,
+    // page 3
+    city_idx: usize,
+    // page 4, loaded from the store as the page is shown
+    histories: Vec<(String, WeatherHistory)>,
 }
 
 impl PluginUnit {
@@ -45,13 +59,20 @@ impl PluginUnit {
             panel_info: panel::PanelInfo::new(panel::PanelType::Normal),
             output: String::new(),
             page_idx: 0,
+            scroll: 0,
             sub_title: vec![
                 plugin_devices::MODULE.to_string(),
                 format!("{} current", plugin_weather::MODULE.to_string()),
                 format!("{} daily", plugin_weather::MODULE.to_string()),
+                format!("{} hourly", plugin_weather::MODULE.to_string()),
+                format!("{} history", plugin_weather::MODULE.to_string()),
+                format!("{} air & sky", plugin_weather::MODULE.to_string()),
             ],
             devices: Vec::new(),
+            device_idx: 0,
             cities: Vec::new(),
+            city_idx: 0,
+            histories: Vec::new(),
         };
 
         myself.info(consts::NEW.to_string()).await;
@@ -73,20 +94,30 @@ impl PluginUnit {
 
     async fn update_devices(&self) -> String {
         let mut output = format!(
-            "{:<12} {:<7} {:<10} {:16} {:<7} {:13} {:<16}",
-            "Name", "Onboard", "Version", "Tailscale IP", "Temp", "App Uptime", "Last Update"
+            "{:<12} {:<7} {:<10} {:16} {:<7} {:<5} {:<6} {:13} {:<16}",
+            "Name",
+            "Onboard",
+            "Version",
+            "Tailscale IP",
+            "Temp",
+            "CPU",
+            "Memory",
+            "App Uptime",
+            "Last Update"
         );
 
         for device in &self.devices {
             output += &format!(
-                "\n{:<12} {:<7} {:<10} {:16} {:<7} {:13} {:<16}",
+                "\n{:<12} {:<7} {:<10} {:16} {:<7} {:<5} {:<6} {:13} {:<16}",
                 device.name,
-                plugin_devices::onboard_str(device.onboard),
+                plugin_devices::status_str(device),
                 plugin_devices::version_str(&device.version),
                 plugin_devices::tailscale_ip_str(&device.tailscale_ip),
                 common::temperature_str(device.temperature),
+                plugin_devices::cpu_load_str(&device.state),
+                plugin_devices::memory_str(&device.state),
                 plugin_devices::app_uptime_str(device.app_uptime),
-                utils::time::ts_str_local(device.ts),
+                plugin_devices::last_update_str(device.ts),
             );
         }
 
@@ -101,8 +132,11 @@ impl PluginUnit {
         for city in &self.cities {
             let (update, temperature, weather) = match &city.weather {
                 Some(weather) => (
-                    utils::time::ts_str(utils::time::datetime_str_to_ts(&weather.time) as u64),
-                    format!("{:.1}°C", weather.temperature),
+                    utils::time::ts_str(
+                        (utils::time::datetime_str_to_ts(&weather.time)
+                            - weather.utc_offset_seconds as i64) as u64,
+                    ),
+                    weather::temperature_str(weather.temperature),
                     weather::weather_code_str(weather.weathercode).to_owned(),
                 ),
                 None => (
@@ -115,17 +149,17 @@ impl PluginUnit {
             let city_name = common::pad_str(&city.name, 12);
 
             output += &format!("\n{city_name} {update:<11} {temperature:7} {weather:20}",);
+
+            // the data above is stale then
+            if let Some(error) = &city.error {
+                output += &format!(" ! {}", common::shorten(error, 40, 0));
+            }
         }
 
         output
     }
 
     async fn update_weather_daily(&mut self) -> String {
-        fn format_date(input: &str) -> String {
-            let date = NaiveDate::parse_from_str(input, "%Y-%m-%d").expect("無法解析日期");
-            format!("{} {}", date.format("%m/%d"), date.weekday())
-        }
-
         if self.cities.is_empty() {
             return NO_DATA.to_string();
         }
@@ -141,7 +175,10 @@ impl PluginUnit {
             if idx == 0 {
                 continue;
             }
-            output.push_str(&format!("{:<27} ", format_date(&daily.time)));
+            output.push_str(&format!(
+                "{} ",
+                common::pad_str(&weather::date_str(&daily.time), 27)
+            ));
         }
 
         for city in &self.cities {
@@ -154,9 +191,9 @@ impl PluginUnit {
                         continue;
                     }
                     let (temperature, precipitation_probability_max, weather_emoji, weather) = (
-                        format!(
-                            "{:.0}/{:.0}",
-                            daily.temperature_2m_max, daily.temperature_2m_min
+                        weather::temperature_range_str(
+                            daily.temperature_2m_max,
+                            daily.temperature_2m_min,
                         ),
                         format!("{}%", daily.precipitation_probability_max),
                         weather::weather_code_emoji(daily.weather_code).to_owned(),
@@ -168,7 +205,7 @@ impl PluginUnit {
                     output.push_str(&format!(
                         "{weather_emoji} {precipitation_probability_max:4} {temperature} "
                     ));
-                    let weather = common::pad_str(&weather, 13);
+                    let weather = common::fit_str(&weather, 13);
                     output.push_str(&weather);
                 }
             }
@@ -177,11 +214,196 @@ impl PluginUnit {
         output
     }
 
+    async fn update_weather_hourly(&mut self) -> String {
+        let Some(city) = self.cities.get(self.city_idx) else {
+            return NO_DATA.to_string();
+        };
+        let Some(weather) = &city.weather else {
+            return format!("{} (Up/Down)\n{NO_DATA}", city.name);
+        };
+
+        let mut output = city.name.clone();
+        if let Some(daily) = weather.daily.first() {
+            let sun_str = |sun: &Option<String>| {
+                sun.as_deref().map_or(consts::NA.to_string(), |sun| {
+                    utils::time::datetime_str_format(sun, "%H:%M")
+                })
+            };
+            output += &format!(
+                "  Sunrise {}  Sunset {}  Wind max {}  UV max {:.1}",
+                sun_str(&daily.sunrise),
+                sun_str(&daily.sunset),
+                weather::wind_speed_str(daily.wind_speed_10m_max),
+                daily.uv_index_max
+            );
+        }
+        output += "  (Up/Down)";
+
+        output += &format!(
+            "\n{:<16} {:7} {:5} {:6} {:9} {:8} {:4}",
+            "Time", "Temp", "Rain", "mm", "Wind", "Humidity", "UV"
+        );
+        for hourly in &weather.hourly {
+            let time = format!(
+                "{} {}",
+                weather::date_str(&utils::time::datetime_str_format(&hourly.time, "%Y-%m-%d")),
+                utils::time::datetime_str_format(&hourly.time, "%H:%M")
+            );
+            output += &format!(
+                "\n{} {:7} {:5} {:<6.1} {:9} {:8} {:<4.1}",
+                common::pad_str(&time, 16),
+                weather::temperature_str(hourly.temperature_2m),
+                format!("{}%", hourly.precipitation_probability),
+                hourly.precipitation,
+                weather::wind_speed_str(hourly.wind_speed_10m),
+                format!("{}%", hourly.relative_humidity_2m),
+                hourly.uv_index,
+            );
+        }
+
+        output
+    }
+
+    fn load_histories(&mut self) {
+        let to = utils::time::ts();
+        let from = to.saturating_sub(HISTORY_DAYS * 24 * 60 * 60);
+
+        self.histories = self
+            .cities
+            .iter()
+            .map(|city| {
+                let records = weather_history::history_path(&city.name)
+                    .and_then(|path| store::load::<WeatherRecord>(&path))
+                    .unwrap_or_default();
+                (city.name.clone(), WeatherHistory::new(&records, from, to))
+            })
+            .collect();
+    }
+
+    async fn update_weather_history(&mut self) -> String {
+        if self.histories.is_empty() {
+            return NO_DATA.to_string();
+        }
+
+        let mut output = format!(
+            "{:<12} {:<7} {:7} {:7} {:7} {:<HISTORY_TREND_POINTS$} {}",
+            "City", "Count", "Min", "Avg", "Max", "Trend", "Forecast error"
+        );
+        for (city_name, history) in &self.histories {
+            let (min, avg, max) = match history.temperature {
+                Some((min, avg, max)) => (
+                    weather::temperature_str(min),
+                    weather::temperature_str(avg),
+                    weather::temperature_str(max),
+                ),
+                None => (
+                    consts::NA.to_owned(),
+                    consts::NA.to_owned(),
+                    consts::NA.to_owned(),
+                ),
+            };
+            let forecast_error = match history.forecast_error() {
+                Some((max, min)) => format!(
+                    "{}/{}",
+                    weather::temperature_delta_str(max),
+                    weather::temperature_delta_str(min)
+                ),
+                None => consts::NA.to_owned(),
+            };
+
+            output += &format!(
+                "\n{} {:<7} {min:7} {avg:7} {max:7} {:<HISTORY_TREND_POINTS$} {forecast_error}",
+                common::pad_str(city_name, 12),
+                history.observations,
+                common::sparkline(&history.trend(HISTORY_TREND_POINTS)),
+            );
+        }
+
+        output
+    }
+
+    // sunrise, sunset and moon computed locally for today
+    async fn update_weather_air_sky(&mut self) -> String {
+        if self.cities.is_empty() {
+            return NO_DATA.to_string();
+        }
+
+        let today = chrono::Local::now().date_naive();
+        let moon_phase = astro::moon_phase(today);
+
+        let mut output = format!(
+            "{:<12} {:<4} {:<16} {:<6} {:<6} {:<7} {:<7} {:<8}  {} {}",
+            "City",
+            "AQI",
+            "",
+            "PM2.5",
+            "PM10",
+            "Sunrise",
+            "Sunset",
+            "Daylight",
+            astro::moon_phase_emoji(moon_phase),
+            astro::moon_phase_str(moon_phase)
+        );
+        for city in &self.cities {
+            let (us_aqi, category, pm2_5, pm10) = match &city.air_quality {
+                Some(air_quality) => (
+                    air_quality.us_aqi.to_string(),
+                    weather::aqi_str(air_quality.us_aqi).to_owned(),
+                    format!("{:.1}", air_quality.pm2_5),
+                    format!("{:.1}", air_quality.pm10),
+                ),
+                None => (
+                    consts::NA.to_owned(),
+                    String::new(),
+                    consts::NA.to_owned(),
+                    consts::NA.to_owned(),
+                ),
+            };
+            // today and the times of the city, not of this node
+            let utc_offset = chrono::FixedOffset::east_opt(city.utc_offset_seconds())
+                .unwrap_or(chrono::FixedOffset::east_opt(0).unwrap());
+            let city_today = chrono::Utc::now().with_timezone(&utc_offset).date_naive();
+            let (sunrise, sunset, daylight) =
+                match astro::sun_times(city_today, city.latitude, city.longitude) {
+                    Some((sunrise, sunset)) => {
+                        let daylight = (sunset - sunrise).num_minutes();
+                        (
+                            sunrise
+                                .with_timezone(&utc_offset)
+                                .format("%H:%M")
+                                .to_string(),
+                            sunset
+                                .with_timezone(&utc_offset)
+                                .format("%H:%M")
+                                .to_string(),
+                            format!("{}h{:02}m", daylight / 60, daylight % 60),
+                        )
+                    }
+                    None => (
+                        consts::NA.to_owned(),
+                        consts::NA.to_owned(),
+                        consts::NA.to_owned(),
+                    ),
+                };
+
+            output += &format!(
+                "\n{} {us_aqi:<4} {} {pm2_5:<6} {pm10:<6} {sunrise:<7} {sunset:<7} {daylight:<8}",
+                common::pad_str(&city.name, 12),
+                common::fit_str(&category, 16),
+            );
+        }
+
+        output
+    }
+
     async fn update(&mut self) {
         let output = match self.page_idx {
             0 => self.update_devices().await,
             1 => self.update_weather_current().await,
             2 => self.update_weather_daily().await,
+            HOURLY_PAGE => self.update_weather_hourly().await,
+            4 => self.update_weather_history().await,
+            5 => self.update_weather_air_sky().await,
             _ => NO_DATA.to_string(),
         };
 
@@ -216,6 +438,11 @@ impl PluginUnit {
                     tailscale_ip: None,
                     temperature: None,
                     app_uptime: None,
+                    state: None,
+                    stale: false,
+                    mqtt_onboard: None,
+                    lan_ip: None,
+                    web_port: None,
                 };
                 self.devices.push(device_add.clone());
             }
@@ -304,6 +531,52 @@ impl PluginUnit {
         }
     }
 
+    async fn handle_action_update_devices_state(&mut self, cmd_parts: &[String]) {
+        if let (Some(name), Some(state)) = (cmd_parts.get(5), cmd_parts.get(6)) {
+            let ts = utils::time::ts();
+
+            match serde_json::from_str::<DeviceState>(state) {
+                Ok(state) => {
+                    if let Some(device) =
+                        self.devices.iter_mut().find(|device| device.name == *name)
+                    {
+                        device.ts = ts;
+                        device.version = Some(state.version.clone());
+                        device.tailscale_ip = state.tailscale_ip.clone();
+                        device.temperature = state.temperature;
+                        device.app_uptime = Some(state.app_uptime);
+                        device.state = Some(state);
+                    }
+                }
+                Err(e) => {
+                    self.warn(format!("Invalid state from `{name}`: {e}")).await;
+                }
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<name> <state>",
+                Action::Update.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
+    async fn handle_action_update_devices_stale(&mut self, cmd_parts: &[String]) {
+        if let (Some(name), Some(stale)) = (cmd_parts.get(5), cmd_parts.get(6)) {
+            if let Some(device) = self.devices.iter_mut().find(|device| device.name == *name) {
+                device.stale = stale == "1";
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<name> <stale>",
+                Action::Update.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
     // p infos update devices <device_key> <...>
     async fn handle_action_update_devices(&mut self, cmd_parts: &[String]) {
         if let Some(device_key) = cmd_parts.get(4) {
@@ -326,6 +599,8 @@ impl PluginUnit {
                     self.handle_action_update_devices_app_uptime(cmd_parts)
                         .await
                 }
+                Ok(DeviceKey::State) => self.handle_action_update_devices_state(cmd_parts).await,
+                Ok(DeviceKey::Stale) => self.handle_action_update_devices_stale(cmd_parts).await,
                 _ => {
                     self.warn(common::MsgTemplate::InvalidParameters.format(
                         &format!("<device_key> (`{device_key}`)"),
@@ -357,17 +632,24 @@ impl PluginUnit {
                 let time = time.to_string();
                 let temperature = temperature.parse::<f32>().unwrap();
                 let weathercode = weathercode.parse::<u8>().unwrap();
+                let utc_offset_seconds = cmd_parts
+                    .get(9)
+                    .and_then(|offset| offset.parse::<i32>().ok())
+                    .unwrap_or_default();
 
                 if let Some(weather) = city.weather.as_mut() {
                     weather.time = time;
                     weather.temperature = temperature;
                     weather.weathercode = weathercode;
+                    weather.utc_offset_seconds = utc_offset_seconds;
                 } else {
                     city.weather = Some(Weather {
                         time,
                         temperature,
                         weathercode,
+                        utc_offset_seconds,
                         daily: vec![],
+                        hourly: vec![],
                     });
                 }
             } else {
@@ -375,7 +657,7 @@ impl PluginUnit {
             }
         } else {
             self.warn(common::MsgTemplate::MissingParameters.format(
-                "<city_name> <time> <temperature> <weathercode>",
+                "<city_name> <time> <temperature> <weathercode> [utc_offset_seconds]",
                 Action::Update.as_ref(),
                 &cmd_parts.join(" "),
             ))
@@ -412,17 +694,27 @@ impl PluginUnit {
                         .parse::<u8>()
                         .unwrap(),
                     weather_code: weather_code.parse::<u8>().unwrap(),
+                    sunrise: cmd_parts
+                        .get(12)
+                        .filter(|sunrise| *sunrise != consts::NA)
+                        .cloned(),
+                    sunset: cmd_parts
+                        .get(13)
+                        .filter(|sunset| *sunset != consts::NA)
+                        .cloned(),
+                    wind_speed_10m_max: cmd_parts
+                        .get(14)
+                        .and_then(|wind| wind.parse::<f32>().ok())
+                        .unwrap_or_default(),
+                    uv_index_max: cmd_parts
+                        .get(15)
+                        .and_then(|uv| uv.parse::<f32>().ok())
+                        .unwrap_or_default(),
                 };
 
                 if let Some(weather) = city.weather.as_mut() {
                     if weather.daily.len() <= idx {
-                        weather.daily.resize_with(idx + 1, || WeatherDaily {
-                            time: String::new(),
-                            temperature_2m_max: 0.0,
-                            temperature_2m_min: 0.0,
-                            precipitation_probability_max: 0,
-                            weather_code: 0,
-                        });
+                        weather.daily.resize_with(idx + 1, WeatherDaily::default);
                     }
 
                     weather.daily[idx] = daily;
@@ -440,14 +732,98 @@ impl PluginUnit {
         }
     }
 
+    // p infos update weather hourly <city_name> '<json>'
+    async fn handle_action_update_weather_hourly(&mut self, cmd_parts: &[String]) {
+        if let (Some(city_name), Some(hourly)) = (cmd_parts.get(5), cmd_parts.get(6)) {
+            if let Some(city) = self.cities.iter_mut().find(|c| c.name == *city_name) {
+                match serde_json::from_str::<Vec<WeatherHourly>>(hourly) {
+                    Ok(hourly) => {
+                        if let Some(weather) = city.weather.as_mut() {
+                            weather.hourly = hourly;
+                        }
+                    }
+                    Err(e) => {
+                        self.warn(format!("Invalid hourly forecast for `{city_name}`: {e}"))
+                            .await;
+                    }
+                }
+            } else {
+                self.warn(format!("City `{city_name}` not found.")).await;
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<city_name> <hourly>",
+                Action::Update.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
+    // p infos update weather error <city_name> ["<error>"]
+    async fn handle_action_update_weather_error(&mut self, cmd_parts: &[String]) {
+        if let Some(city_name) = cmd_parts.get(5) {
+            if let Some(city) = self.cities.iter_mut().find(|c| c.name == *city_name) {
+                city.error = cmd_parts.get(6).cloned();
+            } else {
+                self.warn(format!("City `{city_name}` not found.")).await;
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<city_name>",
+                Action::Update.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
+    // p infos update weather air_quality <city_name> <time> <us_aqi> <pm2_5> <pm10>
+    async fn handle_action_update_weather_air_quality(&mut self, cmd_parts: &[String]) {
+        if let (Some(city_name), Some(time), Some(us_aqi), Some(pm2_5), Some(pm10)) = (
+            cmd_parts.get(5),
+            cmd_parts.get(6),
+            cmd_parts.get(7),
+            cmd_parts.get(8),
+            cmd_parts.get(9),
+        ) {
+            if let Some(city) = self.cities.iter_mut().find(|c| c.name == *city_name) {
+                city.air_quality = Some(AirQuality {
+                    time: time.to_string(),
+                    us_aqi: us_aqi.parse::<u16>().unwrap_or_default(),
+                    pm2_5: pm2_5.parse::<f32>().unwrap_or_default(),
+                    pm10: pm10.parse::<f32>().unwrap_or_default(),
+                });
+            } else {
+                self.warn(format!("City `{city_name}` not found.")).await;
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<city_name> <time> <us_aqi> <pm2_5> <pm10>",
+                Action::Update.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
     // p infos update weather <weather_key> <...>
     async fn handle_action_update_weather(&mut self, cmd_parts: &[String]) {
         if let Some(weather_key) = cmd_parts.get(4) {
             match weather_key.parse::<WeatherKey>() {
                 Ok(WeatherKey::Summary) => {
-                    self.handle_action_update_weather_summary(cmd_parts).await
+                    self.handle_action_update_weather_summary(cmd_parts).await;
+                    if self.page_idx == HISTORY_PAGE {
+                        self.load_histories();
+                    }
                 }
                 Ok(WeatherKey::Daily) => self.handle_action_update_weather_daily(cmd_parts).await,
+                Ok(WeatherKey::Hourly) => self.handle_action_update_weather_hourly(cmd_parts).await,
+                Ok(WeatherKey::Error) => self.handle_action_update_weather_error(cmd_parts).await,
+                Ok(WeatherKey::AirQuality) => {
+                    self.handle_action_update_weather_air_quality(cmd_parts)
+                        .await
+                }
                 _ => {
                     self.warn(common::MsgTemplate::InvalidParameters.format(
                         &format!("<weather_key> (`{weather_key}`)"),
@@ -504,7 +880,10 @@ impl PluginUnit {
                             name: city_name.to_string(),
                             latitude,
                             longitude,
+                            provider: weather::DEFAULT_PROVIDER.to_string(),
                             weather: None,
+                            error: None,
+                            air_quality: None,
                         });
                         self.info(format!(
                             "Added city: `{city_name}` ({latitude}, {longitude})"
@@ -534,6 +913,71 @@ impl PluginUnit {
         }
     }
 
+    // p infos remove weather <city_name>
+    // p infos rename weather <city_name> <new_name>
+    // p infos move weather <city_name> <idx>
+    async fn handle_action_city(&mut self, action: Action, cmd_parts: &[String]) {
+        if cmd_parts.get(3).map(|info_key| info_key.parse::<InfoKey>())
+            != Some(Ok(InfoKey::Weather))
+        {
+            self.warn(common::MsgTemplate::InvalidParameters.format(
+                "<info_key>",
+                action.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        }
+
+        let Some(city_name) = cmd_parts.get(4) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<city_name>",
+                action.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+        let Some(idx) = self.cities.iter().position(|city| city.name == *city_name) else {
+            self.warn(format!("City `{city_name}` not found.")).await;
+            return;
+        };
+
+        match (action, cmd_parts.get(5)) {
+            (Action::Remove, _) => {
+                self.cities.remove(idx);
+                self.city_idx = self.city_idx.min(self.cities.len().saturating_sub(1));
+            }
+            (Action::Rename, Some(new_name)) => self.cities[idx].name = new_name.to_string(),
+            (Action::Move, Some(to)) => match to.parse::<usize>() {
+                Ok(to) if to < self.cities.len() => {
+                    let city = self.cities.remove(idx);
+                    self.cities.insert(to, city);
+                }
+                _ => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        &format!("<idx> (`{to}`)"),
+                        Action::Move.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            },
+            (action, _) => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    "<city_name> <value>",
+                    action.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+
+        if self.page_idx == HISTORY_PAGE {
+            self.load_histories();
+        }
+    }
+
     // p infos add <info_key> <...>
     async fn handle_action_add(&mut self, cmd_parts: &[String]) {
         if let Some(info_key) = cmd_parts.get(3) {
@@ -558,24 +1002,53 @@ impl PluginUnit {
         }
     }
 
+    fn switch_page(&mut self, page_idx: usize) {
+        self.page_idx = page_idx;
+        self.scroll = 0;
+        if self.page_idx == HISTORY_PAGE {
+            self.load_histories();
+        }
+    }
+
+    // ` 1:devices `, ... on the top border
+    fn page_tabs(&self) -> Vec<String> {
+        self.sub_title
+            .iter()
+            .enumerate()
+            .map(|(idx, sub_title)| format!(" {}:{sub_title} ", idx + 1))
+            .collect()
+    }
+
     // p infos key <key>
     async fn handle_action_key(&mut self, cmd_parts: &[String]) {
         if let Some(key) = cmd_parts.get(3) {
             match key.parse::<Key>() {
-                Ok(Key::Left) => {
-                    if self.page_idx > 0 {
-                        self.page_idx -= 1;
-                    } else {
-                        self.page_idx = PAGES - 1;
-                    }
+                Ok(Key::Up) if self.page_idx == 0 => {
+                    self.device_idx = self.device_idx.saturating_sub(1);
                 }
-                Ok(Key::Right) => {
-                    if self.page_idx + 1 < PAGES {
-                        self.page_idx += 1;
-                    } else {
-                        self.page_idx = 0;
+                Ok(Key::Down) if self.page_idx == 0 && self.device_idx + 1 < self.devices.len() => {
+                    self.device_idx += 1;
+                }
+                Ok(Key::Up) if self.page_idx == 3 => {
+                    self.city_idx = self.city_idx.saturating_sub(1);
+                }
+                Ok(Key::Down) if self.page_idx == 3 && self.city_idx + 1 < self.cities.len() => {
+                    self.city_idx += 1;
+                }
+                Ok(Key::Enter) if self.page_idx == 0 => {
+                    if let Some(device) = self.devices.get(self.device_idx) {
+                        self.cmd(format!(
+                            "{} {} {} {}",
+                            consts::P,
+                            plugin_devices::MODULE,
+                            Action::Open,
+                            device.name
+                        ))
+                        .await;
                     }
                 }
+                Ok(Key::Left) => self.switch_page((self.page_idx + PAGES - 1) % PAGES),
+                Ok(Key::Right) => self.switch_page((self.page_idx + 1) % PAGES),
 
                 Ok(k @ Key::AltUp)
                 | Ok(k @ Key::AltDown)
@@ -620,7 +1093,13 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     async fn handle_action(&mut self, action: Action, cmd_parts: &[String], _msg: &Msg) {
+        plugin_devices::sync_inventory(&mut self.devices);
+
         match action {
             Action::Help => self.handle_action_help().await,
             Action::Show => self.handle_action_show().await,
@@ -632,6 +1111,11 @@ impl plugins_main::Plugin for PluginUnit {
             Action::Key => self.handle_action_key(cmd_parts).await,
             Action::Update => self.handle_action_update(cmd_parts).await,
             Action::Add => self.handle_action_add(cmd_parts).await,
+            // e.g. the locale changed, the update below redraws
+            Action::Redraw => (),
+            Action::Remove | Action::Rename | Action::Move => {
+                self.handle_action_city(action, cmd_parts).await
+            }
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
@@ -642,45 +1126,80 @@ impl plugins_main::Plugin for PluginUnit {
         self.update().await;
     }
 
+    async fn handle_mouse(&mut self, kind: MouseKind, column: u16, row: u16) {
+        match kind {
+            MouseKind::ScrollUp => {
+                self.scroll = self.scroll.saturating_sub(panel::SCROLL_LINES);
+            }
+            // limited to the content as drawn
+            MouseKind::ScrollDown => self.scroll += panel::SCROLL_LINES,
+            MouseKind::Down if row == self.panel_info.area.y => {
+                // after the corner and `infos -`
+                let mut x = self.panel_info.area.x + 1 + MODULE.len() as u16 + 2;
+                let mut clicked = None;
+                for (idx, tab) in self.page_tabs().iter().enumerate() {
+                    let width = tab.len() as u16;
+                    if (x..x + width).contains(&column) {
+                        clicked = Some(idx);
+                        break;
+                    }
+                    x += width;
+                }
+
+                if let Some(page_idx) = clicked {
+                    self.switch_page(page_idx);
+                    self.update().await;
+                }
+            }
+            _ => (),
+        }
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         // Clear the panel area
-        let (panel_x, panel_y, panel_width, panel_height) = panel::caculate_position(
-            frame,
-            self.panel_info.x,
-            self.panel_info.y,
-            self.panel_info.w,
-            self.panel_info.h,
-        );
+        let (panel_x, panel_y, panel_width, panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
 
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
 
-        let sub_title = format!(
-            " - {}/{PAGES} - {}",
-            self.page_idx + 1,
-            self.sub_title[self.page_idx]
-        );
+        // pages as tabs, clicked to switch
+        let mut title = vec![Span::raw(format!("{MODULE} -"))];
+        for (idx, tab) in self.page_tabs().into_iter().enumerate() {
+            title.push(if idx == self.page_idx {
+                Span::styled(tab, theme.selected)
+            } else {
+                Span::raw(tab)
+            });
+        }
 
         // Draw the panel block
         let panel_block = Block::default()
             .borders(Borders::ALL)
-            .title(format!("{MODULE} - {sub_title}"))
+            .title(Line::from(title))
             .padding(ratatui::widgets::Padding::new(0, 0, 0, 0))
             .border_type(if active {
                 BorderType::Double
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
         let output = &self.output.lines().collect::<Vec<&str>>();
 
-        let scroll_offset = 0;
+        let visible = panel_block.inner(panel_area).height as usize;
+        self.scroll = self.scroll.min(output.len().saturating_sub(visible));
+        let scroll_offset = self.scroll as u16;
 
-        let lines: Vec<Line> = output
+        let mut lines: Vec<Line> = output
             .iter()
             .flat_map(|entry| {
                 entry
@@ -690,8 +1209,23 @@ impl plugins_main::Plugin for PluginUnit {
             })
             .collect();
 
+        let header = if self.page_idx == HOURLY_PAGE { 1 } else { 0 };
+        if self.output != NO_DATA
+            && let Some(line) = lines.get_mut(header)
+        {
+            *line = line.clone().style(theme.table_header);
+        }
+
+        // selected device, the first line is the header
+        if self.page_idx == 0
+            && active
+            && let Some(line) = lines.get_mut(self.device_idx + 1)
+        {
+            *line = line.clone().style(theme.selected);
+        }
+
         let text = Paragraph::new(Text::from(lines))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }))
+            .style(theme.text(active))
             .scroll((scroll_offset, 0));
 
         frame.render_widget(text, panel_block.inner(panel_area));
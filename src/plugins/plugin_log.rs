@@ -3,28 +3,70 @@ use async_trait::async_trait;
 use colored::*;
 use ratatui::{
     Frame,
-    style::{Color, Style},
-    text::{Line, Span, Text},
+    text::Text,
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
+use strum_macros::{AsRefStr, Display, EnumString};
 use tokio::sync::mpsc::Sender;
 
 use crate::arguments::Mode;
 use crate::consts;
 use crate::globals;
-use crate::messages::{Action, Key, Msg};
-use crate::plugins::plugins_main::{self, Plugin};
-use crate::utils::{api, common, panel, time};
+use crate::messages::{Action, Key, MouseKind, Msg};
+use crate::plugins::{
+    plugin_mqtt,
+    plugins_main::{self, Plugin},
+};
+use crate::utils::{
+    api, common,
+    log_view::{LevelFilter, LogView, ViewLine},
+    panel, time,
+};
 
 pub const MODULE: &str = "log";
 const LOG_CAPACITY: usize = 1000;
+const OFF: &str = "off";
+const ON: &str = "on";
+const ALL: &str = "all";
+const FILTER_PARAMS: &str = "<level|plugin> <value>";
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
+enum FilterKey {
+    #[strum(serialize = "level")]
+    Level,
+    #[strum(serialize = "plugin")]
+    Plugin,
+}
+
+#[derive(Debug)]
+struct Entry {
+    plugin: String,
+    level: Option<log::Level>,
+    line: String,
+}
+
+// the lines shown by the plugin and level filters
+fn shown<'a>(logs: &'a [Entry], plugin: &Option<String>, view: &LogView) -> Vec<ViewLine<'a>> {
+    logs.iter()
+        .filter(|entry| plugin.as_ref().is_none_or(|plugin| entry.plugin == *plugin))
+        .filter(|entry| view.shows(entry.level))
+        .map(|entry| ViewLine {
+            level: entry.level,
+            text: &entry.line,
+        })
+        .collect()
+}
 
 #[derive(Debug)]
 pub struct PluginUnit {
     msg_tx: Sender<Msg>,
     mode: Mode,
     dest: Option<String>,
-    logs: Vec<String>,
+    mqtt_level: Option<log::Level>,
+    collect: bool,
+    logs: Vec<Entry>,
+    plugin_filter: Option<String>,
+    view: LogView,
     panel_info: panel::PanelInfo,
 }
 
@@ -34,7 +76,11 @@ impl PluginUnit {
             msg_tx,
             mode,
             dest: None,
+            mqtt_level: None,
+            collect: false,
             logs: Vec::new(),
+            plugin_filter: None,
+            view: LogView::new(),
             panel_info: panel::PanelInfo::new(panel::PanelType::Normal),
         };
 
@@ -47,6 +93,21 @@ impl PluginUnit {
         self.info(Action::Show.to_string()).await;
         self.info(format!("  Mode: {}", self.mode)).await;
         self.info(format!("  Dest: {:?}", self.dest)).await;
+        self.info(format!(
+            "  MQTT publish: {}",
+            self.mqtt_level
+                .map(|level| level.to_string())
+                .unwrap_or(OFF.to_string())
+        ))
+        .await;
+        self.info(format!("  MQTT collect: {}", self.collect)).await;
+        self.info(format!("  Level filter: {}", self.view.level))
+            .await;
+        self.info(format!(
+            "  Plugin filter: {}",
+            self.plugin_filter.as_deref().unwrap_or(ALL)
+        ))
+        .await;
         self.info(format!("  Panel info: {:?}", self.panel_info))
             .await;
     }
@@ -56,65 +117,136 @@ impl PluginUnit {
         self.info(format!("  {} <dest>", Action::Dest)).await;
         self.info("    dest: the destination IP to send log messages to".to_string())
             .await;
+        self.info(format!("  {} <info|warn|error|{OFF}>", Action::Publish))
+            .await;
+        self.info(format!(
+            "    publish log records at or above the level to {}/<name>/{MODULE}",
+            plugin_mqtt::TOPIC_PREFIX
+        ))
+        .await;
+        self.info(format!("  {} <{ON}|{OFF}>", Action::Collect))
+            .await;
+        self.info(format!(
+            "    show log records from {}/+/{MODULE} of other nodes",
+            plugin_mqtt::TOPIC_PREFIX
+        ))
+        .await;
+        self.info(format!("  {} [{FILTER_PARAMS}]", Action::Filter))
+            .await;
+        self.info(format!(
+            "    {} <{}|{}|{}>, {} <plugin|{ALL}>, no parameters to clear",
+            FilterKey::Level,
+            LevelFilter::All,
+            LevelFilter::Warn,
+            LevelFilter::Error,
+            FilterKey::Plugin
+        ))
+        .await;
+        self.info(format!("  {} <{ON}|{OFF}>", Action::Follow))
+            .await;
+        self.info("    keep showing the latest records, also by Ctrl+F".to_string())
+            .await;
     }
 
-    async fn handle_action_log(&mut self, ts: u64, plugin: &str, cmd_parts: &[String]) {
-        if let (Some(level), Some(msg)) = (cmd_parts.get(3), cmd_parts.get(4)) {
-            // if dest exists, send log to dest
-            if let Some(dest) = &self.dest {
-                api::post_log(
-                    dest,
-                    &api::LogRequest {
-                        data: api::LogData {
-                            name: globals::get_sys_name(),
-                            ts,
-                            level: level.to_string(),
-                            plugin: plugin.to_string(),
-                            msg: msg.to_string(),
-                        },
-                    },
-                )
-                .await;
-            }
-
-            match self.mode {
-                Mode::Gui => {
-                    let msgs: Vec<&str> = msg.split('\n').collect();
+    fn push_log(&mut self, ts: u64, plugin: &str, level: &str, msg: &str) {
+        match self.mode {
+            Mode::Gui => {
+                let msgs: Vec<&str> = msg.split('\n').collect();
 
-                    for msg in msgs {
-                        self.logs.push(format!(
+                for msg in msgs {
+                    self.logs.push(Entry {
+                        plugin: plugin.to_string(),
+                        level: level.parse::<log::Level>().ok(),
+                        line: format!(
                             "{} {plugin:>10}: [{}] {msg}",
                             time::ts_str(ts),
                             common::level_str(level)
-                        ));
-                    }
-                    if self.logs.len() > LOG_CAPACITY {
-                        self.logs.remove(0);
-                    }
-                    self.cmd(format!(
-                        "{} {} {} {}",
-                        consts::P,
-                        plugins_main::MODULE,
-                        Action::Redraw,
-                        MODULE
-                    ))
-                    .await;
+                        ),
+                    });
                 }
-                Mode::Cli => {
-                    let msg = format!(
-                        "{} {plugin:>10}: [{}] {msg}",
-                        time::ts_str(ts),
-                        common::level_str(level)
-                    );
-                    let msg = match level.to_lowercase().as_str() {
-                        "info" => msg.normal(),
-                        "warn" => msg.yellow(),
-                        "error" => msg.red(),
-                        _ => msg.red().on_yellow(),
-                    };
-                    println!("{msg}");
+                if self.logs.len() > LOG_CAPACITY {
+                    let trimmed = self.logs.len() - LOG_CAPACITY;
+                    let shown = shown(&self.logs[..trimmed], &self.plugin_filter, &self.view);
+                    self.view.trimmed(shown.len());
+                    self.logs.drain(..trimmed);
                 }
             }
+            Mode::Cli => {
+                let msg = format!(
+                    "{} {plugin:>10}: [{}] {msg}",
+                    time::ts_str(ts),
+                    common::level_str(level)
+                );
+                let msg = match level.to_lowercase().as_str() {
+                    "info" => msg.normal(),
+                    "warn" => msg.yellow(),
+                    "error" => msg.red(),
+                    _ => msg.red().on_yellow(),
+                };
+                println!("{msg}");
+            }
+        }
+    }
+
+    async fn redraw(&self) {
+        if self.mode == Mode::Gui {
+            self.cmd(format!(
+                "{} {} {} {}",
+                consts::P,
+                plugins_main::MODULE,
+                Action::Redraw,
+                MODULE
+            ))
+            .await;
+        }
+    }
+
+    // Note: records from the mqtt plugin are not published, it will ping-pong the broker
+    async fn publish_log(&self, log_data: &api::LogData) {
+        let Some(mqtt_level) = self.mqtt_level else {
+            return;
+        };
+
+        if log_data.plugin == plugin_mqtt::MODULE {
+            return;
+        }
+
+        match log_data.level.parse::<log::Level>() {
+            Ok(level) if level <= mqtt_level => {}
+            _ => return,
+        }
+
+        if let Ok(payload) = serde_json::to_string(log_data) {
+            self.cmd(format!(
+                "{} {} {} false {MODULE} {}",
+                consts::P,
+                plugin_mqtt::MODULE,
+                Action::Publish,
+                shell_words::quote(&payload)
+            ))
+            .await;
+        }
+    }
+
+    async fn handle_action_log(&mut self, ts: u64, plugin: &str, cmd_parts: &[String]) {
+        if let (Some(level), Some(msg)) = (cmd_parts.get(3), cmd_parts.get(4)) {
+            let log_data = api::LogData {
+                name: globals::get_sys_name(),
+                ts,
+                level: level.to_string(),
+                plugin: plugin.to_string(),
+                msg: msg.to_string(),
+            };
+
+            self.publish_log(&log_data).await;
+
+            // if dest exists, send log to dest
+            if let Some(dest) = &self.dest {
+                api::post_log(dest, &api::LogRequest { data: log_data }).await;
+            }
+
+            self.push_log(ts, plugin, level, msg);
+            self.redraw().await;
         } else {
             self.warn(format!("Incomplete log command: {cmd_parts:?}"))
                 .await;
@@ -129,8 +261,142 @@ impl PluginUnit {
         }
     }
 
+    // p log publish <info|warn|error|off>
+    async fn handle_action_publish(&mut self, cmd_parts: &[String]) {
+        if let Some(level) = cmd_parts.get(3) {
+            if level == OFF {
+                self.mqtt_level = None;
+            } else if let Ok(level) = level.parse::<log::Level>() {
+                self.mqtt_level = Some(level);
+            } else {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    &format!("<level> (`{level}`)"),
+                    Action::Publish.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                &format!("<info|warn|error|{OFF}>"),
+                Action::Publish.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
+    // p log collect <on|off>
+    async fn handle_action_collect(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|s| s.as_str()) {
+            Some(ON) => self.collect = true,
+            Some(OFF) => self.collect = false,
+            _ => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    &format!("<{ON}|{OFF}>"),
+                    Action::Collect.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p log remote <log_data>
+    async fn handle_action_remote(&mut self, cmd_parts: &[String]) {
+        if !self.collect {
+            return;
+        }
+
+        if let Some(log_data) = cmd_parts.get(3) {
+            match serde_json::from_str::<api::LogData>(log_data) {
+                Ok(log_data) => {
+                    // our own records are already in the log
+                    if log_data.name == globals::get_sys_name() {
+                        return;
+                    }
+
+                    let plugin = format!("{}/{}", log_data.name, log_data.plugin);
+                    self.push_log(log_data.ts, &plugin, &log_data.level, &log_data.msg);
+                    self.redraw().await;
+                }
+                Err(e) => {
+                    self.warn(format!("Invalid remote log record: {e}")).await;
+                }
+            }
+        }
+    }
+
+    // p log filter [<level|plugin> <value>]
+    async fn handle_action_filter(&mut self, cmd_parts: &[String]) {
+        match (cmd_parts.get(3), cmd_parts.get(4)) {
+            (None, _) => {
+                self.view.level = LevelFilter::All;
+                self.plugin_filter = None;
+            }
+            (Some(filter_key), Some(value)) => match filter_key.parse::<FilterKey>() {
+                Ok(FilterKey::Level) => match value.parse::<LevelFilter>() {
+                    Ok(level) => self.view.level = level,
+                    Err(_) => {
+                        self.warn(common::MsgTemplate::InvalidParameters.format(
+                            &format!("<level> (`{value}`)"),
+                            Action::Filter.as_ref(),
+                            &cmd_parts.join(" "),
+                        ))
+                        .await;
+                        return;
+                    }
+                },
+                Ok(FilterKey::Plugin) if value == ALL => self.plugin_filter = None,
+                Ok(FilterKey::Plugin) => self.plugin_filter = Some(value.to_string()),
+                Err(_) => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        &format!("<filter_key> (`{filter_key}`)"),
+                        Action::Filter.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                    return;
+                }
+            },
+            (Some(_), None) => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    FILTER_PARAMS,
+                    Action::Filter.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        // the shown lines are not the same any more
+        self.view.clear();
+        self.redraw().await;
+    }
+
+    // p log follow <on|off>
+    async fn handle_action_follow(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|s| s.as_str()) {
+            Some(ON) => self.view.set_follow(true),
+            Some(OFF) => self.view.set_follow(false),
+            _ => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    &format!("<{ON}|{OFF}>"),
+                    Action::Follow.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        self.redraw().await;
+    }
+
     async fn handle_action_key_alt_c(&mut self) {
         self.logs.clear();
+        self.view.clear();
         self.cmd(format!(
             "{} {} {} {}",
             consts::P,
@@ -145,6 +411,12 @@ impl PluginUnit {
         if let Some(key) = cmd_parts.get(3) {
             match key.parse::<Key>() {
                 Ok(_k @ Key::AltC) => self.handle_action_key_alt_c().await,
+                Ok(Key::Enter) => {
+                    let lines = shown(&self.logs, &self.plugin_filter, &self.view);
+                    self.view.next_match(&lines);
+                    self.redraw().await;
+                }
+                Ok(k) if self.view.handle_key(&k) => self.redraw().await,
                 Ok(k @ Key::AltUp)
                 | Ok(k @ Key::AltDown)
                 | Ok(k @ Key::AltLeft)
@@ -188,6 +460,10 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     async fn handle_action(&mut self, action: Action, cmd_parts: &[String], msg: &Msg) {
         match action {
             Action::Help => self.handle_action_help().await,
@@ -199,6 +475,11 @@ impl plugins_main::Plugin for PluginUnit {
                 }
             }
             Action::Dest => self.handle_action_dest(cmd_parts).await,
+            Action::Publish => self.handle_action_publish(cmd_parts).await,
+            Action::Collect => self.handle_action_collect(cmd_parts).await,
+            Action::Remote => self.handle_action_remote(cmd_parts).await,
+            Action::Filter => self.handle_action_filter(cmd_parts).await,
+            Action::Follow => self.handle_action_follow(cmd_parts).await,
             Action::Key => self.handle_action_key(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
@@ -207,71 +488,57 @@ impl plugins_main::Plugin for PluginUnit {
         }
     }
 
+    async fn handle_mouse(&mut self, kind: MouseKind, _column: u16, _row: u16) {
+        match kind {
+            MouseKind::ScrollUp => self.view.scroll_up(panel::SCROLL_LINES),
+            MouseKind::ScrollDown => self.view.scroll_down(panel::SCROLL_LINES),
+            _ => (),
+        }
+    }
+
+    async fn handle_search(&mut self, pattern: &str) {
+        let lines = shown(&self.logs, &self.plugin_filter, &self.view);
+        self.view.search(pattern, &lines);
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         // Clear the panel area
-        let (panel_x, panel_y, panel_width, panel_height) = panel::caculate_position(
-            frame,
-            self.panel_info.x,
-            self.panel_info.y,
-            self.panel_info.w,
-            self.panel_info.h,
-        );
+        let (panel_x, panel_y, panel_width, panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
 
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
 
         // Draw the panel block
+        let mut title = self.view.title(MODULE);
+        if let Some(plugin) = &self.plugin_filter {
+            title += &format!(" - {plugin}");
+        }
         let panel_block = Block::default()
             .borders(Borders::ALL)
-            .title(MODULE)
+            .title(title)
             .padding(ratatui::widgets::Padding::new(0, 0, 0, 0))
             .border_type(if active {
                 BorderType::Double
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
-        let area_height = panel_area.height;
-
-        let scroll_offset = if self.logs.len() as u16 > (area_height - 3) {
-            self.logs.len() as u16 - (area_height - 3)
-        } else {
-            0
-        };
-
-        let lines: Vec<Line> = self
-            .logs
-            .iter()
-            .flat_map(|entry| {
-                entry
-                    .split('\n') // 處理內部的換行
-                    .map(|subline| {
-                        if subline.contains("[W]") {
-                            Line::from(Span::styled(
-                                subline.to_string(),
-                                Style::default().fg(Color::Yellow),
-                            ))
-                        } else if subline.contains("[E]") {
-                            Line::from(Span::styled(
-                                subline.to_string(),
-                                Style::default().fg(Color::Red),
-                            ))
-                        } else {
-                            Line::from(Span::raw(subline.to_string()))
-                        }
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
+        let inner_area = panel_block.inner(panel_area);
+        let lines = shown(&self.logs, &self.plugin_filter, &self.view);
+        let lines = self.view.render(&lines, inner_area.height as usize, &theme);
 
-        let text = Paragraph::new(Text::from(lines))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }))
-            .scroll((scroll_offset, 0));
+        let text = Paragraph::new(Text::from(lines)).style(theme.text(active));
 
-        frame.render_widget(text, panel_block.inner(panel_area));
+        frame.render_widget(text, inner_area);
     }
 }
@@ -1,32 +1,264 @@
 use anyhow::Result;
 use async_trait::async_trait;
 use log::Level::{Error, Info, Warn};
+use once_cell::sync::Lazy;
 use ratatui::{
     Frame,
-    style::{Color, Style},
+    layout::{Constraint, Layout},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
 };
+use regex::Regex;
 use rumqttc::{AsyncClient, Event, Incoming, LastWill, MqttOptions, Publish, QoS};
+use strum_macros::{AsRefStr, Display, EnumString};
 use tokio::sync::{broadcast, mpsc::Sender};
 
 use crate::arguments::Mode;
 use crate::consts;
 use crate::globals;
-use crate::messages::{self as msgs, Action, DeviceKey, Key, Msg};
+use crate::messages::{self as msgs, Action, DeviceKey, Key, MouseKind, Msg};
 use crate::plugins::{
-    plugin_devices,
+    plugin_devices, plugin_log,
     plugins_main::{self, Plugin},
 };
-use crate::utils::{self, common, panel};
+use crate::utils::{
+    self, common,
+    log_view::{LevelFilter, LogView, ViewLine},
+    panel,
+};
 
 pub const MODULE: &str = "mqtt";
 const BROKER: &str = "broker.emqx.io";
 const BROKER_PORT: u16 = 1883;
 const MQTT_KEEP_ALIVE: u64 = 300;
 const RESTART_DELAY: u64 = 60;
-const TOPIC_PREFIX: &str = "tln";
+pub const TOPIC_PREFIX: &str = "tln";
 const MAX_OUTPUT_LEN: usize = 300;
+const FILTER_PARAMS: &str = "<topic|regex|level> <pattern>";
+const OFF: &str = "off";
+const ON: &str = "on";
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
+enum Direction {
+    #[strum(serialize = "in")]
+    In,
+    #[strum(serialize = "out")]
+    Out,
+}
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
+enum FilterKey {
+    #[strum(serialize = "topic")]
+    Topic,
+    #[strum(serialize = "regex")]
+    Regex,
+    // of the log view
+    #[strum(serialize = "level")]
+    Level,
+}
+
+// the lines shown by the level filter
+fn shown<'a>(logs: &'a [(Option<log::Level>, String)], view: &LogView) -> Vec<ViewLine<'a>> {
+    logs.iter()
+        .filter(|(level, _)| view.shows(*level))
+        .flat_map(|(level, entry)| {
+            entry.split('\n').map(|text| ViewLine {
+                level: *level,
+                text,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct Record {
+    id: u64,
+    ts: u64,
+    direction: Direction,
+    topic: String,
+    payload: String,
+    qos: u8,
+    retain: bool,
+}
+
+impl Record {
+    fn line(&self) -> String {
+        let (icon, name, key) = match split_topic(&self.topic) {
+            Some((name, key)) => (
+                match self.direction {
+                    Direction::In => "📩",
+                    Direction::Out => "📤",
+                },
+                name,
+                key,
+            ),
+            None => ("📩", String::new(), self.topic.clone()),
+        };
+
+        format!("{icon} pub:: {key} {name} {}", self.payload)
+    }
+
+    fn level(&self) -> log::Level {
+        match split_topic(&self.topic) {
+            Some((_, key))
+                if self.direction == Direction::Out || key.parse::<DeviceKey>().is_ok() =>
+            {
+                Info
+            }
+            _ => Error,
+        }
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} {} q{} {} {} {}",
+            utils::time::ts_str(self.ts),
+            match self.direction {
+                Direction::In => "⬇",
+                Direction::Out => "⬆",
+            },
+            self.qos,
+            if self.retain { "r" } else { "-" },
+            self.topic,
+            self.payload.replace('\n', " ")
+        )
+    }
+
+    fn detail(&self) -> String {
+        let payload = match serde_json::from_str::<serde_json::Value>(&self.payload) {
+            Ok(value) if value.is_object() || value.is_array() => {
+                serde_json::to_string_pretty(&value).unwrap_or_else(|_| self.payload.clone())
+            }
+            _ => self.payload.clone(),
+        };
+
+        format!(
+            "topic: {}\ndirection: {}, qos: {}, retain: {}, time: {}\n\n{payload}",
+            self.topic,
+            self.direction,
+            self.qos,
+            self.retain,
+            utils::time::ts_str_full(self.ts)
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Inspector {
+    enabled: bool,
+    paused: bool,
+    next_id: u64,
+    records: Vec<Record>,
+    pending: Vec<Record>,
+    selected: Option<u64>,
+    topic_filter: Option<String>,
+    regex_filter: Option<Regex>,
+}
+
+impl Inspector {
+    fn new() -> Self {
+        Self {
+            enabled: false,
+            paused: false,
+            next_id: 0,
+            records: Vec::new(),
+            pending: Vec::new(),
+            selected: None,
+            topic_filter: None,
+            regex_filter: None,
+        }
+    }
+
+    fn push(&mut self, mut record: Record) {
+        record.id = self.next_id;
+        self.next_id += 1;
+
+        let records = if self.paused {
+            &mut self.pending
+        } else {
+            &mut self.records
+        };
+        records.push(record);
+        let len = records.len();
+        if len > MAX_OUTPUT_LEN {
+            records.drain(..len - MAX_OUTPUT_LEN);
+        }
+    }
+
+    fn resume(&mut self) {
+        self.paused = false;
+        let mut pending = std::mem::take(&mut self.pending);
+        self.records.append(&mut pending);
+        let len = self.records.len();
+        if len > MAX_OUTPUT_LEN {
+            self.records.drain(..len - MAX_OUTPUT_LEN);
+        }
+    }
+
+    fn is_visible(&self, record: &Record) -> bool {
+        if let Some(topic_filter) = &self.topic_filter
+            && !topic_matches(topic_filter, &record.topic)
+        {
+            return false;
+        }
+
+        if let Some(regex_filter) = &self.regex_filter
+            && !regex_filter.is_match(&record.topic)
+            && !regex_filter.is_match(&record.payload)
+        {
+            return false;
+        }
+
+        true
+    }
+
+    fn visible(&self) -> Vec<&Record> {
+        self.records.iter().filter(|r| self.is_visible(r)).collect()
+    }
+
+    fn selected_pos(&self, visible: &[&Record]) -> Option<usize> {
+        self.selected
+            .and_then(|id| visible.iter().position(|r| r.id == id))
+    }
+
+    fn select_up(&mut self) {
+        let visible = self.visible();
+        let pos = match self.selected_pos(&visible) {
+            Some(pos) => pos.saturating_sub(1),
+            None => visible.len().saturating_sub(1),
+        };
+        self.selected = visible.get(pos).map(|r| r.id);
+    }
+
+    fn select_down(&mut self) {
+        let visible = self.visible();
+        self.selected = match self.selected_pos(&visible) {
+            Some(pos) if pos + 1 < visible.len() => Some(visible[pos + 1].id),
+            // past the last record: follow the tail again
+            _ => None,
+        };
+    }
+
+    fn select_first(&mut self) {
+        self.selected = self.visible().first().map(|r| r.id);
+    }
+
+    fn filter_str(&self) -> String {
+        let mut filters = vec![];
+        if let Some(topic_filter) = &self.topic_filter {
+            filters.push(format!("{} `{topic_filter}`", FilterKey::Topic));
+        }
+        if let Some(regex_filter) = &self.regex_filter {
+            filters.push(format!("{} `{}`", FilterKey::Regex, regex_filter.as_str()));
+        }
+
+        if filters.is_empty() {
+            "none".to_string()
+        } else {
+            filters.join(", ")
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct PluginUnit {
@@ -34,7 +266,9 @@ pub struct PluginUnit {
     shutdown_tx: broadcast::Sender<()>,
     mode: Mode,
     client: Option<AsyncClient>,
-    logs: Vec<String>,
+    logs: Vec<(Option<log::Level>, String)>,
+    view: LogView,
+    inspector: Inspector,
     panel_info: panel::PanelInfo,
 }
 
@@ -50,6 +284,8 @@ impl PluginUnit {
             mode,
             client: None,
             logs: vec![],
+            view: LogView::new(),
+            inspector: Inspector::new(),
             panel_info: panel::PanelInfo::new(panel::PanelType::Normal),
         };
 
@@ -167,30 +403,38 @@ impl PluginUnit {
     }
 
     async fn publish(&mut self, topic: &str, retain: bool, payload: &str) {
-        if let Some(client) = &self.client {
-            let re = regex::Regex::new(&format!(r"^{TOPIC_PREFIX}/([^/]+)/([^/]+)$"))
-                .expect("Failed to regex");
-            if let Some(captures) = re.captures(topic) {
-                let name = &captures[1];
-                let key = &captures[2];
-
-                if let Err(e) = client
-                    .publish(topic, QoS::AtLeastOnce, retain, payload)
-                    .await
-                {
-                    self.warn(format!(
-                        "Failed to publish topic (`{topic}`) payload (`{payload}`). Err: {e:?}"
-                    ))
-                    .await;
-                } else {
-                    output_push(
-                        &self.msg_tx,
-                        &self.mode,
-                        Info,
-                        format!("📤 pub:: {key} {name} {payload}"),
-                    )
-                    .await;
-                }
+        if let Some(client) = self.client.clone()
+            && let Some((name, key)) = split_topic(topic)
+        {
+            if let Err(e) = client
+                .publish(topic, QoS::AtLeastOnce, retain, payload)
+                .await
+            {
+                self.warn(format!(
+                    "Failed to publish topic (`{topic}`) payload (`{payload}`). Err: {e:?}"
+                ))
+                .await;
+            } else if key == plugin_log::MODULE {
+                // log records are already in the log panel
+            } else if self.mode == Mode::Gui {
+                self.push_record(Record {
+                    id: 0,
+                    ts: utils::time::ts(),
+                    direction: Direction::Out,
+                    topic: topic.to_string(),
+                    payload: payload.to_string(),
+                    qos: QoS::AtLeastOnce as u8,
+                    retain,
+                })
+                .await;
+            } else {
+                output_push(
+                    &self.msg_tx,
+                    &self.mode,
+                    Info,
+                    format!("📤 pub:: {key} {name} {payload}"),
+                )
+                .await;
             }
         }
     }
@@ -224,14 +468,231 @@ impl PluginUnit {
             self.client.is_some()
         ))
         .await;
+        self.info(format!("  Inspector: {}", self.inspector.enabled))
+            .await;
+        self.info(format!("  Paused: {}", self.inspector.paused))
+            .await;
+        self.info(format!("  Filter: {}", self.inspector.filter_str()))
+            .await;
+        self.info(format!(
+            "  Records: {} ({} pending)",
+            self.inspector.records.len(),
+            self.inspector.pending.len()
+        ))
+        .await;
     }
 
     async fn handle_action_help(&self) {
         self.info(Action::Help.to_string()).await;
+        self.info(format!("  {}", Action::Inspect)).await;
+        self.info(
+            "    toggle the inspector view, Up/Down/Home/End to select a message".to_string(),
+        )
+        .await;
+        self.info(format!("  {}", Action::Pause)).await;
+        self.info("    toggle pausing the inspector view".to_string())
+            .await;
+        self.info(format!("  {} {FILTER_PARAMS}", Action::Filter))
+            .await;
+        self.info("    topic: MQTT topic filter, `+` and `#` wildcards supported".to_string())
+            .await;
+        self.info("    regex: matched against topic or payload".to_string())
+            .await;
+        self.info(format!(
+            "    level: {}, {} or {} of the log view",
+            LevelFilter::All,
+            LevelFilter::Warn,
+            LevelFilter::Error
+        ))
+        .await;
+        self.info(format!("  {}", Action::Filter)).await;
+        self.info("    clear all filters".to_string()).await;
+        self.info(format!("  {} <{ON}|{OFF}>", Action::Follow))
+            .await;
+        self.info("    keep showing the latest lines, also by Ctrl+F".to_string())
+            .await;
+    }
+
+    fn push_log(&mut self, level: Option<log::Level>, line: String) {
+        self.logs.push((level, line));
+        let logs_len = self.logs.len();
+        if logs_len > MAX_OUTPUT_LEN {
+            let trimmed = logs_len - MAX_OUTPUT_LEN;
+            let shown = shown(&self.logs[..trimmed], &self.view);
+            self.view.trimmed(shown.len());
+            self.logs.drain(..trimmed);
+        }
+    }
+
+    async fn redraw(&self) {
+        self.cmd(format!(
+            "{} {} {}",
+            consts::P,
+            plugins_main::MODULE,
+            Action::Redraw,
+        ))
+        .await;
+    }
+
+    async fn push_record(&mut self, record: Record) {
+        let msg = format!(
+            "{} [{}] {}",
+            utils::time::ts_str(record.ts),
+            common::level_to_str(&record.level()),
+            record.line()
+        );
+        self.push_log(Some(record.level()), msg);
+
+        self.inspector.push(record);
+
+        self.redraw().await;
+    }
+
+    // p mqtt record <direction> <qos> <retain> <topic> <payload>
+    async fn handle_action_record(&mut self, cmd_parts: &[String]) {
+        if let (Some(direction), Some(qos), Some(retain), Some(topic), Some(payload)) = (
+            cmd_parts.get(3),
+            cmd_parts.get(4),
+            cmd_parts.get(5),
+            cmd_parts.get(6),
+            cmd_parts.get(7),
+        ) {
+            match (direction.parse::<Direction>(), qos.parse::<u8>()) {
+                (Ok(direction), Ok(qos)) => {
+                    self.push_record(Record {
+                        id: 0,
+                        ts: utils::time::ts(),
+                        direction,
+                        topic: topic.to_string(),
+                        payload: payload.to_string(),
+                        qos,
+                        retain: retain == "true",
+                    })
+                    .await;
+                }
+                _ => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        "<direction> <qos>",
+                        Action::Record.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            }
+        } else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<direction> <qos> <retain> <topic> <payload>",
+                Action::Record.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+        }
+    }
+
+    async fn handle_action_inspect(&mut self) {
+        self.inspector.enabled = !self.inspector.enabled;
+        self.inspector.selected = None;
+        self.info(format!("Inspector: {}", self.inspector.enabled))
+            .await;
+        self.redraw().await;
+    }
+
+    async fn handle_action_pause(&mut self) {
+        if self.inspector.paused {
+            self.inspector.resume();
+        } else {
+            self.inspector.paused = true;
+        }
+        self.info(format!("Paused: {}", self.inspector.paused))
+            .await;
+        self.redraw().await;
+    }
+
+    // p mqtt filter [<topic|regex> <pattern>]
+    async fn handle_action_filter(&mut self, cmd_parts: &[String]) {
+        match (cmd_parts.get(3), cmd_parts.get(4)) {
+            (None, _) => {
+                self.inspector.topic_filter = None;
+                self.inspector.regex_filter = None;
+                self.view.level = LevelFilter::All;
+                self.view.clear();
+            }
+            (Some(filter_key), Some(pattern)) => match filter_key.parse::<FilterKey>() {
+                Ok(FilterKey::Topic) => self.inspector.topic_filter = Some(pattern.to_string()),
+                Ok(FilterKey::Regex) => match Regex::new(pattern) {
+                    Ok(re) => self.inspector.regex_filter = Some(re),
+                    Err(e) => {
+                        self.warn(format!("Invalid regex `{pattern}`: {e}")).await;
+                        return;
+                    }
+                },
+                Ok(FilterKey::Level) => match pattern.parse::<LevelFilter>() {
+                    Ok(level) => {
+                        self.view.level = level;
+                        self.view.clear();
+                    }
+                    Err(_) => {
+                        self.warn(common::MsgTemplate::InvalidParameters.format(
+                            &format!("<level> (`{pattern}`)"),
+                            Action::Filter.as_ref(),
+                            &cmd_parts.join(" "),
+                        ))
+                        .await;
+                        return;
+                    }
+                },
+                Err(_) => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        &format!("<filter_key> (`{filter_key}`)"),
+                        Action::Filter.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                    return;
+                }
+            },
+            (Some(_), None) => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    FILTER_PARAMS,
+                    Action::Filter.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        self.inspector.selected = None;
+        self.info(format!("Filter: {}", self.inspector.filter_str()))
+            .await;
+        self.redraw().await;
+    }
+
+    // p mqtt follow <on|off>
+    async fn handle_action_follow(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|s| s.as_str()) {
+            Some(ON) => self.view.set_follow(true),
+            Some(OFF) => self.view.set_follow(false),
+            _ => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    &format!("<{ON}|{OFF}>"),
+                    Action::Follow.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        self.redraw().await;
     }
 
     async fn handle_action_key_alt_c(&mut self) {
         self.logs.clear();
+        self.view.clear();
+        self.inspector.records.clear();
+        self.inspector.pending.clear();
+        self.inspector.selected = None;
         self.cmd(format!(
             "{} {} {} {}",
             consts::P,
@@ -246,6 +707,28 @@ impl PluginUnit {
         if let Some(key) = cmd_parts.get(3) {
             match key.parse::<Key>() {
                 Ok(_k @ Key::AltC) => self.handle_action_key_alt_c().await,
+                Ok(Key::Up) if self.inspector.enabled => {
+                    self.inspector.select_up();
+                    self.redraw().await;
+                }
+                Ok(Key::Down) if self.inspector.enabled => {
+                    self.inspector.select_down();
+                    self.redraw().await;
+                }
+                Ok(Key::Home) if self.inspector.enabled => {
+                    self.inspector.select_first();
+                    self.redraw().await;
+                }
+                Ok(Key::End) if self.inspector.enabled => {
+                    self.inspector.selected = None;
+                    self.redraw().await;
+                }
+                Ok(Key::Enter) if !self.inspector.enabled => {
+                    let lines = shown(&self.logs, &self.view);
+                    self.view.next_match(&lines);
+                    self.redraw().await;
+                }
+                Ok(k) if !self.inspector.enabled && self.view.handle_key(&k) => self.redraw().await,
                 Ok(k @ Key::AltUp)
                 | Ok(k @ Key::AltDown)
                 | Ok(k @ Key::AltLeft)
@@ -274,13 +757,10 @@ impl PluginUnit {
         }
     }
 
+    // p mqtt output_push <level> <output>
     async fn handle_action_output_push(&mut self, cmd_parts: &[String]) {
-        if let Some(output) = cmd_parts.get(3) {
-            self.logs.push(output.to_string());
-            let logs_len = self.logs.len();
-            if logs_len > MAX_OUTPUT_LEN {
-                self.logs.drain(..logs_len - MAX_OUTPUT_LEN);
-            }
+        if let (Some(level), Some(output)) = (cmd_parts.get(3), cmd_parts.get(4)) {
+            self.push_log(level.parse::<log::Level>().ok(), output.to_string());
 
             self.cmd(format!(
                 "{} {} {}",
@@ -291,8 +771,8 @@ impl PluginUnit {
             .await;
         } else {
             self.warn(common::MsgTemplate::MissingParameters.format(
-                "<output>",
-                Action::OutputUpdate.as_ref(),
+                "<level> <output>",
+                Action::OutputPush.as_ref(),
                 &cmd_parts.join(" "),
             ))
             .await;
@@ -314,6 +794,10 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     async fn handle_action(&mut self, action: Action, cmd_parts: &[String], _msg: &Msg) {
         match action {
             Action::Help => self.handle_action_help().await,
@@ -328,6 +812,11 @@ impl plugins_main::Plugin for PluginUnit {
             Action::Publish => self.handle_action_publish(cmd_parts).await,
             Action::Key => self.handle_action_key(cmd_parts).await,
             Action::OutputPush => self.handle_action_output_push(cmd_parts).await,
+            Action::Record => self.handle_action_record(cmd_parts).await,
+            Action::Inspect => self.handle_action_inspect().await,
+            Action::Pause => self.handle_action_pause().await,
+            Action::Filter => self.handle_action_filter(cmd_parts).await,
+            Action::Follow => self.handle_action_follow(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
@@ -335,72 +824,136 @@ impl plugins_main::Plugin for PluginUnit {
         }
     }
 
+    // the wheel moves the selection in the inspector
+    async fn handle_mouse(&mut self, kind: MouseKind, _column: u16, _row: u16) {
+        match kind {
+            MouseKind::ScrollUp if self.inspector.enabled => self.inspector.select_up(),
+            MouseKind::ScrollDown if self.inspector.enabled => self.inspector.select_down(),
+            MouseKind::ScrollUp => self.view.scroll_up(panel::SCROLL_LINES),
+            MouseKind::ScrollDown => self.view.scroll_down(panel::SCROLL_LINES),
+            _ => (),
+        }
+    }
+
+    async fn handle_search(&mut self, pattern: &str) {
+        let lines = shown(&self.logs, &self.view);
+        self.view.search(pattern, &lines);
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         // Clear the panel area
-        let (panel_x, panel_y, panel_width, panel_height) = panel::caculate_position(
-            frame,
-            self.panel_info.x,
-            self.panel_info.y,
-            self.panel_info.w,
-            self.panel_info.h,
-        );
+        let (panel_x, panel_y, panel_width, panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
 
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
+
+        if self.inspector.enabled {
+            self.draw_inspector(frame, panel_area, active);
+            return;
+        }
 
         // Draw the panel block
         let panel_block = Block::default()
             .borders(Borders::ALL)
-            .title(MODULE)
+            .title(self.view.title(MODULE))
             .padding(ratatui::widgets::Padding::new(0, 0, 0, 0))
             .border_type(if active {
                 BorderType::Double
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
-        let area_height = panel_area.height;
+        let inner_area = panel_block.inner(panel_area);
+        let lines = shown(&self.logs, &self.view);
+        let lines = self.view.render(&lines, inner_area.height as usize, &theme);
 
-        let scroll_offset = if self.logs.len() as u16 > (area_height - 3) {
-            self.logs.len() as u16 - (area_height - 3)
-        } else {
-            0
-        };
+        let text = Paragraph::new(Text::from(lines)).style(theme.text(active));
+
+        frame.render_widget(text, inner_area);
+    }
+}
+
+impl PluginUnit {
+    fn draw_inspector(&self, frame: &mut Frame, panel_area: ratatui::layout::Rect, active: bool) {
+        let theme = globals::get_theme();
+        let style = theme.text(active);
+
+        let mut title = format!("{MODULE} - inspector");
+        if self.inspector.paused {
+            title += &format!(" [paused +{}]", self.inspector.pending.len());
+        }
+        if self.inspector.selected.is_none() {
+            title += " [follow]";
+        }
+        if self.inspector.topic_filter.is_some() || self.inspector.regex_filter.is_some() {
+            title += &format!(" [filter: {}]", self.inspector.filter_str());
+        }
+
+        let panel_block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .padding(ratatui::widgets::Padding::new(0, 0, 0, 0))
+            .border_type(if active {
+                BorderType::Double
+            } else {
+                BorderType::Plain
+            })
+            .style(style)
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
+        frame.render_widget(panel_block.clone(), panel_area);
+
+        let visible = self.inspector.visible();
+        let selected_pos = self.inspector.selected_pos(&visible);
 
-        let lines: Vec<Line> = self
-            .logs
+        let [list_area, detail_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Percentage(40)])
+                .areas(panel_block.inner(panel_area));
+
+        // list: keep the selected record (or the tail) in view
+        let list_height = list_area.height as usize;
+        let anchor = selected_pos.unwrap_or(visible.len().saturating_sub(1));
+        let scroll_offset = (anchor + 1).saturating_sub(list_height);
+
+        let lines: Vec<Line> = visible
             .iter()
-            .flat_map(|entry| {
-                entry
-                    .split('\n') // 處理內部的換行
-                    .map(|subline| {
-                        if subline.contains("[W]") {
-                            Line::from(Span::styled(
-                                subline.to_string(),
-                                Style::default().fg(Color::Yellow),
-                            ))
-                        } else if subline.contains("[E]") {
-                            Line::from(Span::styled(
-                                subline.to_string(),
-                                Style::default().fg(Color::Red),
-                            ))
-                        } else {
-                            Line::from(Span::raw(subline.to_string()))
-                        }
-                    })
-                    .collect::<Vec<_>>()
+            .enumerate()
+            .map(|(idx, record)| {
+                let line_style = if Some(idx) == selected_pos {
+                    theme.selected
+                } else {
+                    theme.level(Some(record.level()))
+                };
+                Line::from(Span::styled(record.summary(), line_style))
             })
             .collect();
 
-        let text = Paragraph::new(Text::from(lines))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }))
-            .scroll((scroll_offset, 0));
+        let list = Paragraph::new(Text::from(lines))
+            .style(style)
+            .scroll((scroll_offset as u16, 0));
+        frame.render_widget(list, list_area);
 
-        frame.render_widget(text, panel_block.inner(panel_area));
+        // detail of the selected record
+        let detail_block = Block::default().borders(Borders::TOP).style(style);
+        let detail = match selected_pos {
+            Some(pos) => visible[pos].detail(),
+            None => "Up/Down to select a message".to_string(),
+        };
+        let detail = Paragraph::new(detail)
+            .style(style)
+            .wrap(Wrap { trim: false })
+            .block(detail_block);
+        frame.render_widget(detail, detail_area);
     }
 }
 
@@ -425,55 +978,121 @@ async fn process_event(
 
 async fn process_event_publish(msg_tx: &Sender<Msg>, mode: &Mode, publish: &Publish) {
     let topic = &publish.topic;
-    let re =
-        regex::Regex::new(&format!(r"^{TOPIC_PREFIX}/([^/]+)/([^/]+)$")).expect("Failed to regex");
 
-    if let Some(captures) = re.captures(topic) {
-        let name = &captures[1];
-        let key = &captures[2];
+    if let Some((name, key)) = split_topic(topic) {
         let payload = String::from_utf8_lossy(&publish.payload);
 
+        // tln/+/log: hand over to the log plugin, it decides whether to collect
+        if key == plugin_log::MODULE {
+            msgs::cmd(
+                msg_tx,
+                MODULE,
+                &format!(
+                    "{} {} {} {}",
+                    consts::P,
+                    plugin_log::MODULE,
+                    Action::Remote,
+                    shell_words::quote(&payload),
+                ),
+            )
+            .await;
+            return;
+        }
+
         match key.parse::<DeviceKey>() {
             Ok(
                 DeviceKey::Onboard
                 | DeviceKey::Version
                 | DeviceKey::TailscaleIp
                 | DeviceKey::Temperature
-                | DeviceKey::AppUptime,
+                | DeviceKey::AppUptime
+                | DeviceKey::State
+                | DeviceKey::Upgrade,
             ) => {
-                output_push(
-                    msg_tx,
-                    mode,
-                    Info,
-                    format!("📩 pub:: {key} {name} {payload}"),
-                )
-                .await;
+                record_push(msg_tx, mode, Info, publish).await;
 
                 msgs::cmd(
                     msg_tx,
                     MODULE,
                     &format!(
-                        "{} {} {} {key} {name} {payload}",
+                        "{} {} {} {key} {name} {}",
                         consts::P,
                         plugin_devices::MODULE,
                         Action::Update,
+                        shell_words::quote(&payload),
                     ),
                 )
                 .await;
             }
             _ => {
-                output_push(
-                    msg_tx,
-                    mode,
-                    Error,
-                    format!("📩 pub:: {key} {name} {payload}"),
-                )
-                .await;
+                record_push(msg_tx, mode, Error, publish).await;
             }
         }
     }
 }
 
+async fn record_push(msg_tx: &Sender<Msg>, mode: &Mode, level: log::Level, publish: &Publish) {
+    let payload = String::from_utf8_lossy(&publish.payload);
+
+    match mode {
+        Mode::Gui => {
+            msgs::cmd(
+                msg_tx,
+                MODULE,
+                &format!(
+                    "{} {MODULE} {} {} {} {} {} {}",
+                    consts::P,
+                    Action::Record,
+                    Direction::In,
+                    publish.qos as u8,
+                    publish.retain,
+                    shell_words::quote(&publish.topic),
+                    shell_words::quote(&payload),
+                ),
+            )
+            .await;
+        }
+        Mode::Cli => {
+            let (name, key) = split_topic(&publish.topic).unwrap_or_default();
+            output_push(
+                msg_tx,
+                mode,
+                level,
+                format!("📩 pub:: {key} {name} {payload}"),
+            )
+            .await;
+        }
+    }
+}
+
+static TOPIC_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(r"^{TOPIC_PREFIX}/([^/]+)/([^/]+)$")).expect("Failed to regex")
+});
+
+// tln/<name>/<key> -> (name, key)
+fn split_topic(topic: &str) -> Option<(String, String)> {
+    TOPIC_RE
+        .captures(topic)
+        .map(|captures| (captures[1].to_string(), captures[2].to_string()))
+}
+
+// MQTT topic filter matching with `+` (one level) and `#` (remaining levels)
+fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    let mut filter_levels = filter.split('/').peekable();
+    while let Some(filter_level) = filter_levels.next() {
+        match (filter_level, topic_levels.next()) {
+            // only valid as the last level
+            ("#", _) => return filter_levels.peek().is_none(),
+            ("+", Some(_)) => continue,
+            (f, Some(t)) if f == t => continue,
+            _ => return false,
+        }
+    }
+
+    topic_levels.next().is_none()
+}
+
 async fn output_push(msg_tx: &Sender<Msg>, mode: &Mode, level: log::Level, msg: String) {
     let ts = utils::time::ts();
     match mode {
@@ -486,7 +1105,11 @@ async fn output_push(msg_tx: &Sender<Msg>, mode: &Mode, level: log::Level, msg:
             msgs::cmd(
                 msg_tx,
                 MODULE,
-                &format!("{} {MODULE} {} '{msg}'", consts::P, Action::OutputPush,),
+                &format!(
+                    "{} {MODULE} {} {level} '{msg}'",
+                    consts::P,
+                    Action::OutputPush,
+                ),
             )
             .await;
         }
@@ -498,3 +1121,45 @@ async fn output_push(msg_tx: &Sender<Msg>, mode: &Mode, level: log::Level, msg:
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn topic_filters() {
+        assert!(topic_matches(
+            "tln/dev1/temperature",
+            "tln/dev1/temperature"
+        ));
+        assert!(!topic_matches(
+            "tln/dev1/temperature",
+            "tln/dev2/temperature"
+        ));
+
+        assert!(topic_matches("tln/+/temperature", "tln/dev1/temperature"));
+        assert!(!topic_matches("tln/+/temperature", "tln/temperature"));
+        assert!(!topic_matches("tln/+", "tln/dev1/temperature"));
+
+        assert!(topic_matches("#", "tln/dev1/temperature"));
+        assert!(topic_matches("tln/#", "tln/dev1/temperature"));
+        // the parent level too
+        assert!(topic_matches("tln/#", "tln"));
+        assert!(!topic_matches("tln/#", "other/dev1"));
+        // not a valid filter
+        assert!(!topic_matches("tln/#/temperature", "tln/dev1/temperature"));
+
+        assert!(!topic_matches("tln/dev1", "tln/dev1/temperature"));
+    }
+
+    #[test]
+    fn topics() {
+        assert_eq!(
+            split_topic("tln/dev1/temperature"),
+            Some(("dev1".to_string(), "temperature".to_string()))
+        );
+        assert_eq!(split_topic("tln/dev1"), None);
+        assert_eq!(split_topic("tln/dev1/state/extra"), None);
+        assert_eq!(split_topic("other/dev1/temperature"), None);
+    }
+}
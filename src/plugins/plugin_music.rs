@@ -145,6 +145,7 @@ impl PluginUnit {
                         &msg_tx_clone_clone,
                         MODULE,
                         server_ip.as_str(),
+                        consts::WEB_PORT,
                         entry.path().to_str().unwrap(),
                         &filename,
                     )
@@ -264,6 +265,7 @@ impl PluginUnit {
                             &msg_tx_clone_clone,
                             MODULE,
                             server_ip.as_str(),
+                            consts::WEB_PORT,
                             entry.path().to_str().unwrap(),
                             &filename,
                         )
@@ -1,51 +1,100 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use sysinfo::{Disks, Networks, System};
 use tokio::sync::mpsc::Sender;
 use tokio::time::Duration;
 
+use crate::arguments::Mode;
 use crate::consts;
+use crate::globals;
 use crate::messages::{self as msgs, Action, DeviceKey, Msg};
 use crate::plugins::{
     plugin_mqtt,
     plugins_main::{self, Plugin},
 };
-use crate::utils::{common, time};
+use crate::utils::{
+    common,
+    device_state::{self, DeviceState},
+    release::{self, UpgradeReport},
+    time,
+};
 
 pub const MODULE: &str = "system";
-const UPDATE_INTERVAL: u64 = 300;
+pub const UPDATE_INTERVAL: u64 = 300;
+// give mqtt a moment to publish the report before restarting
+const RESTART_DELAY: u64 = 3;
 
 #[derive(Debug)]
 struct SystemInfo {
     ts_start_uptime: u64,
     tailscale_ip: Option<String>,
     temperature: Option<f32>,
+    system: System,
 }
 
 impl SystemInfo {
     fn new() -> Self {
+        // cpu usage is measured between two refreshes, so take the first sample now
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        system.refresh_memory();
+
         Self {
             ts_start_uptime: time::uptime(),
             tailscale_ip: common::get_tailscale_ip(),
             temperature: get_temperature(),
+            system,
         }
     }
 
     fn update(&mut self) {
         self.tailscale_ip = common::get_tailscale_ip();
         self.temperature = get_temperature();
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+    }
+
+    fn app_uptime(&self) -> u64 {
+        time::uptime() - self.ts_start_uptime
+    }
+
+    fn state(&self) -> DeviceState {
+        let (disk_used, disk_total) = get_disk_usage();
+
+        let load_average = System::load_average();
+
+        DeviceState {
+            schema: device_state::SCHEMA,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            tailscale_ip: self.tailscale_ip.clone(),
+            temperature: self.temperature,
+            app_uptime: self.app_uptime(),
+            cpu_load: self.system.global_cpu_usage(),
+            memory_used: self.system.used_memory(),
+            memory_total: self.system.total_memory(),
+            disk_used,
+            disk_total,
+            load_average: [load_average.one, load_average.five, load_average.fifteen],
+            os: System::long_os_version().unwrap_or_else(|| consts::NA.to_string()),
+            arch: System::cpu_arch(),
+            platform: release::platform(),
+            ips: get_ips(),
+        }
     }
 }
 
 #[derive(Debug)]
 pub struct PluginUnit {
     msg_tx: Sender<Msg>,
+    mode: Mode,
     system_info: SystemInfo,
 }
 
 impl PluginUnit {
-    pub async fn new(msg_tx: Sender<Msg>) -> Result<Self> {
+    pub async fn new(msg_tx: Sender<Msg>, mode: Mode) -> Result<Self> {
         let myself = Self {
             msg_tx: msg_tx.clone(),
+            mode,
             system_info: SystemInfo::new(),
         };
 
@@ -79,13 +128,24 @@ impl PluginUnit {
             common::get_tailscale_ip_str(&self.system_info.tailscale_ip)
         ))
         .await;
-        let uptime_str = time::uptime_str(time::uptime() - self.system_info.ts_start_uptime);
+        let uptime_str = time::uptime_str(self.system_info.app_uptime());
         self.info(format!("  App uptime: {uptime_str}")).await;
         self.info(format!(
             "  Temperature: {}",
             common::temperature_str(self.system_info.temperature)
         ))
         .await;
+
+        let state = self.system_info.state();
+        self.info(format!("  CPU load: {}", state.cpu_load_str()))
+            .await;
+        self.info(format!("  Memory: {}", state.memory_str())).await;
+        self.info(format!("  Disk: {}", state.disk_str())).await;
+        self.info(format!("  Load average: {}", state.load_average_str()))
+            .await;
+        self.info(format!("  OS: {} ({})", state.os, state.arch))
+            .await;
+        self.info(format!("  IPs: {}", state.ips.join(", "))).await;
     }
 
     async fn handle_action_update(&mut self) {
@@ -135,7 +195,7 @@ impl PluginUnit {
         .await;
 
         // app uptime
-        let uptime = time::uptime() - self.system_info.ts_start_uptime;
+        let uptime = self.system_info.app_uptime();
         self.cmd(format!(
             "{} {} {} false {} '{}'",
             consts::P,
@@ -145,11 +205,154 @@ impl PluginUnit {
             uptime
         ))
         .await;
+
+        // state
+        match serde_json::to_string(&self.system_info.state()) {
+            Ok(state) => {
+                self.cmd(format!(
+                    "{} {} {} false {} {}",
+                    consts::P,
+                    plugin_mqtt::MODULE,
+                    Action::Publish,
+                    DeviceKey::State,
+                    shell_words::quote(&state)
+                ))
+                .await;
+            }
+            Err(e) => self.warn(format!("Failed to serialize state: {e}")).await,
+        }
+    }
+
+    // p system release
+    async fn handle_action_release(&self) {
+        self.info(Action::Release.to_string()).await;
+
+        match release::release() {
+            Ok(manifest) => {
+                self.info(format!(
+                    "  Released v{} for {}: {}",
+                    manifest.version,
+                    release::platform(),
+                    manifest.sha256
+                ))
+                .await;
+            }
+            Err(e) => self.warn(format!("Failed to release: {e}")).await,
+        }
+    }
+
+    // p system upgrade
+    async fn handle_action_upgrade(&self) {
+        self.info(Action::Upgrade.to_string()).await;
+
+        let Some(server_ip) = globals::get_server_ip() else {
+            self.warn(consts::SERVER_IP_NOT_SET.to_string()).await;
+            return;
+        };
+
+        let msg_tx = self.msg_tx.clone();
+        let tui = self.mode == Mode::Gui;
+        tokio::spawn(async move {
+            let ret = release::upgrade(&msg_tx, MODULE, &server_ip).await;
+
+            let report = match &ret {
+                Ok(Some(installed)) => UpgradeReport {
+                    ok: true,
+                    version: installed.manifest.version.clone(),
+                    msg: "installed, restarting".to_string(),
+                },
+                Ok(None) => UpgradeReport {
+                    ok: true,
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    msg: "already up to date".to_string(),
+                },
+                Err(e) => UpgradeReport {
+                    ok: false,
+                    version: env!("CARGO_PKG_VERSION").to_string(),
+                    msg: e.to_string(),
+                },
+            };
+
+            if report.ok {
+                msgs::info(
+                    &msg_tx,
+                    MODULE,
+                    &format!("Upgrade: v{} {}", report.version, report.msg),
+                )
+                .await;
+            } else {
+                msgs::warn(&msg_tx, MODULE, &format!("Upgrade failed: {}", report.msg)).await;
+            }
+
+            publish_report(&msg_tx, &report).await;
+
+            if let Ok(Some(installed)) = &ret {
+                tokio::time::sleep(Duration::from_secs(RESTART_DELAY)).await;
+                if let Err(e) = release::restart(&installed.exe, tui) {
+                    msgs::warn(&msg_tx, MODULE, &format!("Failed to restart: {e}")).await;
+
+                    // installed but still running the old version
+                    let report = UpgradeReport {
+                        ok: false,
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        msg: format!(
+                            "installed v{}, failed to restart: {e}",
+                            installed.manifest.version
+                        ),
+                    };
+                    publish_report(&msg_tx, &report).await;
+
+                    if tui {
+                        msgs::cmd(
+                            &msg_tx,
+                            MODULE,
+                            &format!(
+                                "{} {} {} clear",
+                                consts::P,
+                                plugins_main::MODULE,
+                                Action::Redraw
+                            ),
+                        )
+                        .await;
+                    }
+                }
+            }
+        });
     }
 
     async fn handle_action_help(&self) {
         self.info(Action::Help.to_string()).await;
         self.info(format!("  {}", Action::Update)).await;
+        self.info(format!(
+            "  {}    copy this binary to {} for `{}`",
+            Action::Release,
+            consts::NAS_RELEASE_FOLDER,
+            Action::Upgrade
+        ))
+        .await;
+        self.info(format!(
+            "  {}    download the release from the server, verify and restart",
+            Action::Upgrade
+        ))
+        .await;
+    }
+}
+
+async fn publish_report(msg_tx: &Sender<Msg>, report: &UpgradeReport) {
+    if let Ok(report) = serde_json::to_string(report) {
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {} {} false {} {}",
+                consts::P,
+                plugin_mqtt::MODULE,
+                Action::Publish,
+                DeviceKey::Upgrade,
+                shell_words::quote(&report)
+            ),
+        )
+        .await;
     }
 }
 
@@ -168,6 +371,8 @@ impl plugins_main::Plugin for PluginUnit {
             Action::Help => self.handle_action_help().await,
             Action::Show => self.handle_action_show().await,
             Action::Update => self.handle_action_update().await,
+            Action::Release => self.handle_action_release().await,
+            Action::Upgrade => self.handle_action_upgrade().await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
@@ -192,6 +397,39 @@ fn get_temperature() -> Option<f32> {
     None
 }
 
+fn get_disk_usage() -> (u64, u64) {
+    let disks = Disks::new_with_refreshed_list();
+
+    // prefer the root filesystem, otherwise the first disk reported
+    let disk = disks
+        .iter()
+        .find(|disk| disk.mount_point() == std::path::Path::new("/"))
+        .or_else(|| disks.iter().next());
+
+    match disk {
+        Some(disk) => (
+            disk.total_space().saturating_sub(disk.available_space()),
+            disk.total_space(),
+        ),
+        None => (0, 0),
+    }
+}
+
+fn get_ips() -> Vec<String> {
+    let networks = Networks::new_with_refreshed_list();
+
+    let mut ips: Vec<String> = networks
+        .values()
+        .flat_map(|network| network.ip_networks().iter())
+        .filter(|ipnetwork| !ipnetwork.addr.is_loopback())
+        .map(|ipnetwork| ipnetwork.addr.to_string())
+        .collect();
+    ips.sort();
+    ips.dedup();
+
+    ips
+}
+
 fn get_temperature_mqtt(temperature: Option<f32>) -> String {
     match temperature {
         Some(t) => format!("{:.1}", t),
@@ -7,13 +7,13 @@ use rand::Rng;
 use ratatui::{
     Frame,
     crossterm::{cursor::SetCursorStyle, execute},
-    style::{Color, Style},
     text::Text,
     widgets::{Block, BorderType, Borders, Clear, Paragraph},
 };
 use tokio::sync::{Mutex, mpsc::Sender};
 
 use crate::consts;
+use crate::globals;
 use crate::messages::{self as msgs, Action, Key, Msg};
 use crate::plugins::plugins_main::{self, Plugin};
 use crate::utils::{self, common, panel};
@@ -40,6 +40,8 @@ impl PluginUnit {
                 y: 0,
                 w: 0,
                 h: 0,
+                rect: None,
+                area: ratatui::layout::Rect::default(),
             },
             open: Arc::new(Mutex::new(false)),
             escape_secs: 0,
@@ -183,16 +185,17 @@ impl plugins_main::Plugin for PluginUnit {
         &self.panel_info
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        &mut self.panel_info
+    }
+
     fn draw(&mut self, frame: &mut Frame, active: bool) {
+        let theme = globals::get_theme();
+
         // Clear the panel area
         #[allow(unused_assignments)]
-        let (panel_x, panel_y, mut panel_width, mut panel_height) = panel::caculate_position(
-            frame,
-            self.panel_info.x,
-            self.panel_info.y,
-            self.panel_info.w,
-            self.panel_info.h,
-        );
+        let (panel_x, panel_y, mut panel_width, mut panel_height) =
+            panel::caculate_position(frame, &self.panel_info);
 
         panel_width = 65;
         panel_height = 8;
@@ -200,6 +203,7 @@ impl plugins_main::Plugin for PluginUnit {
         let panel_area =
             panel::panel_rect(panel_x, panel_y, panel_width, panel_height, frame.area());
         frame.render_widget(Clear, panel_area);
+        self.panel_info.area = panel_area;
 
         // Draw the panel block
         let panel_block = Block::default()
@@ -211,14 +215,16 @@ impl plugins_main::Plugin for PluginUnit {
             } else {
                 BorderType::Plain
             })
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }));
+            .style(theme.text(active))
+            .border_style(theme.border(active))
+            .title_style(theme.title(active));
         frame.render_widget(panel_block.clone(), panel_area);
 
         // Draw the panel content
         let scroll_offset = 0;
 
         let text = Paragraph::new(Text::from(big_clock(self.output.as_str())))
-            .style(Style::default().fg(if active { Color::Cyan } else { Color::White }))
+            .style(theme.text(active))
             .scroll((scroll_offset, 0));
 
         frame.render_widget(text, panel_block.inner(panel_area));
@@ -1,19 +1,48 @@
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{
+    Arc,
+    atomic::{AtomicBool, Ordering},
+};
+
 use anyhow::Result;
 use async_trait::async_trait;
-use tokio::sync::mpsc::Sender;
+use strum::IntoEnumIterator;
+use tokio::sync::{Semaphore, mpsc::Sender};
+use tokio::task::JoinSet;
 
 use crate::arguments::Mode;
 use crate::consts;
+use crate::globals;
 use crate::messages::{self as msgs, Action, Msg, WeatherKey};
 use crate::plugins::plugins_main::{self, Plugin};
 use crate::utils::{
-    common,
-    weather::{self, City, Weather, WeatherDaily},
+    self, common, geocoding, store,
+    weather::{
+        self, AirQuality, City, Locale, Units, Weather, WeatherCondition, WeatherDaily,
+        WeatherHourly,
+    },
+    weather_history::{self, WeatherHistory, WeatherRecord},
 };
 
 pub const MODULE: &str = "weather";
 const WEATHER_POLLING: u64 = 15 * 60; // 15 mins
-const ADD_PARAMS: &str = "<name> <latitude> <longitude>";
+const MAX_CONCURRENT_FETCHES: usize = 4;
+const DEFAULT_HISTORY_DAYS: u64 = 7;
+const HISTORY_TREND_POINTS: usize = 48;
+const HISTORY_RETENTION_DAYS: u64 = 90;
+const PRUNE_INTERVAL: u64 = 24 * 60 * 60;
+const ADD_PARAMS: &str = "<name> [<latitude> <longitude> [provider]]";
+const ALERT_PARAMS: &str = "add <city> \"<condition>\" \"<cmd>\" | remove <idx>";
+
+#[derive(Debug)]
+struct Alert {
+    city: String,
+    condition: WeatherCondition,
+    cmd: String,
+    // local date of the last firing, once per day
+    fired: Option<String>,
+}
 
 #[derive(Debug)]
 pub struct PluginUnit {
@@ -21,6 +50,15 @@ pub struct PluginUnit {
     mode: Mode,
     gui_panel: Option<String>,
     cities: Vec<City>,
+    alerts: Vec<Alert>,
+    updating: Arc<AtomicBool>,
+    geocoder: String,
+    // a weather provider with air quality, for all cities
+    air_quality: String,
+    // last recorded observation time and forecast date per city
+    last_observation: HashMap<String, String>,
+    last_forecast: HashMap<String, String>,
+    last_prune: u64,
 }
 
 impl PluginUnit {
@@ -30,6 +68,13 @@ impl PluginUnit {
             mode,
             gui_panel: None,
             cities: Vec::new(),
+            alerts: Vec::new(),
+            updating: Arc::new(AtomicBool::new(false)),
+            geocoder: geocoding::DEFAULT_GEOCODER.to_string(),
+            air_quality: weather::DEFAULT_PROVIDER.to_string(),
+            last_observation: HashMap::new(),
+            last_forecast: HashMap::new(),
+            last_prune: 0,
         };
 
         myself.info(consts::NEW.to_string()).await;
@@ -68,12 +113,47 @@ impl PluginUnit {
         self.info(format!("  Mode: {}", self.mode)).await;
         self.info(format!("  Gui panel: {:?}", self.gui_panel))
             .await;
-        self.info(format!("  {:<12} {:<7}", "Name", "Temp")).await;
+        self.info(format!("  Geocoder: {}", self.geocoder)).await;
+        self.info(format!("  Air quality: {}", self.air_quality))
+            .await;
+        self.info(format!(
+            "  Locale: {}, units: {}",
+            globals::get_locale(),
+            globals::get_units()
+        ))
+        .await;
+        self.info(format!(
+            "  {:<12} {:<7} {:<5} {:<12} {}",
+            "Name", "Temp", "AQI", "Provider", "Error"
+        ))
+        .await;
         for city in &self.cities {
             self.info(format!(
-                "  {} {:<7}",
+                "  {} {:<7} {:<5} {:<12} {}",
                 common::pad_str(&city.name, 12),
-                common::temperature_str(city.weather.as_ref().map(|w| w.temperature))
+                city.weather
+                    .as_ref()
+                    .map_or(consts::NA.to_string(), |weather| {
+                        weather::temperature_str(weather.temperature)
+                    }),
+                city.air_quality
+                    .as_ref()
+                    .map_or(consts::NA.to_string(), |air_quality| {
+                        air_quality.us_aqi.to_string()
+                    }),
+                city.provider,
+                city.error.as_deref().unwrap_or("-")
+            ))
+            .await;
+        }
+        self.info("  Alerts:".to_string()).await;
+        if self.alerts.is_empty() {
+            self.info("    <none>".to_string()).await;
+        }
+        for (idx, alert) in self.alerts.iter().enumerate() {
+            self.info(format!(
+                "    {idx}: {} {} `{}`",
+                alert.city, alert.condition, alert.cmd
             ))
             .await;
         }
@@ -83,6 +163,46 @@ impl PluginUnit {
         self.info(Action::Help.to_string()).await;
         self.info(format!("  {}", Action::Update)).await;
         self.info(format!("  {} {ADD_PARAMS}", Action::Add)).await;
+        self.info("    without coordinates, the place is looked up by the geocoder".to_string())
+            .await;
+        self.info(format!("  {} <name>", Action::Remove)).await;
+        self.info(format!("  {} <name> <new_name>", Action::Rename))
+            .await;
+        self.info(format!("  {} <name> <idx>", Action::Move)).await;
+        self.info(format!(
+            "  {} <{}|fixture:<path>>",
+            Action::Geocoder,
+            geocoding::GEOCODERS.join("|")
+        ))
+        .await;
+        self.info(format!("  {} <provider>", Action::AirQuality))
+            .await;
+        self.info(format!("  {} <en|zh-TW|nl|ja>", Action::Locale))
+            .await;
+        self.info(format!("  {} <metric|imperial>", Action::Units))
+            .await;
+        self.info(format!("  {} <city> [days]", Action::History))
+            .await;
+        self.info(format!(
+            "  Providers: {} (default), fixture:<path>",
+            weather::PROVIDERS.join(", ")
+        ))
+        .await;
+        self.info(format!("  {} {ALERT_PARAMS}", Action::Alert))
+            .await;
+        self.info(format!(
+            "    <condition>: <field> <op> <value>, fields: {}",
+            weather::ConditionField::iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))
+        .await;
+        self.info(
+            "    `{city}`, `{date}` and `{value}` in cmd are replaced, fires once a day"
+                .to_string(),
+        )
+        .await;
     }
 
     async fn handle_action_gui(&mut self, cmd_parts: &[String]) {
@@ -100,98 +220,233 @@ impl PluginUnit {
 
     // p weather update
     async fn handle_action_update_cities(&mut self) {
+        // e.g. the script and init both ask for an update at startup
+        if self.updating.swap(true, Ordering::SeqCst) {
+            self.info("Update in progress, skipped".to_string()).await;
+            return;
+        }
+
+        self.prune_history().await;
+
         let cities = self.cities.clone();
         let msg_tx_clone = self.msg_tx.clone();
         let mode = self.mode.clone();
         let gui_panel_clone = self.gui_panel.clone();
+        let updating_clone = Arc::clone(&self.updating);
+        let air_quality = self.air_quality.clone();
 
         tokio::spawn(async move {
-            for city in cities {
-                let weather = weather::get_weather(city.latitude, city.longitude).await;
+            let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_FETCHES));
+            let mut join_set = JoinSet::new();
 
-                if let Ok(weather) = weather {
-                    let (time, temperature, weathercode) =
-                        (weather.time, weather.temperature, weather.weathercode);
-                    let city_name = &city.name;
+            for city in cities {
+                let Some(provider) = weather::provider(&city.provider) else {
+                    continue;
+                };
+                let semaphore = Arc::clone(&semaphore);
+                let msg_tx = msg_tx_clone.clone();
+                let mode = mode.clone();
+                let gui_panel = gui_panel_clone.clone();
+                let air_quality_provider = weather::provider(&air_quality);
 
-                    msgs::cmd(
-                        &msg_tx_clone,
-                        MODULE,
-                        &format!(
-                            "{} {MODULE} {} {} {city_name} {time} {temperature} {weathercode}",
-                            consts::P,
-                            Action::Update,
-                            WeatherKey::Summary,
-                        ),
-                    )
-                    .await;
+                join_set.spawn(async move {
+                    let Ok(_permit) = semaphore.acquire().await else {
+                        return;
+                    };
+                    let fetched =
+                        weather::get_weather(provider.as_ref(), city.latitude, city.longitude)
+                            .await;
 
-                    // update infos
-                    if mode == Mode::Gui
-                        && let Some(gui_panel_clone) = &gui_panel_clone
-                    {
-                        msgs::cmd(
-                            &msg_tx_clone,
+                    if let Some(e) = &fetched.error {
+                        msgs::warn(
+                            &msg_tx,
                             MODULE,
                             &format!(
-                                "{} {gui_panel_clone} {} {MODULE} {} {city_name} {time} {temperature} {weathercode}",
-                                consts::P,
-                                Action::Update,
-                                WeatherKey::Summary,
+                                "[{}] `{}`: {e}{}",
+                                provider.name(),
+                                city.name,
+                                if fetched.weather.is_some() {
+                                    ", using cached data"
+                                } else {
+                                    ""
+                                }
                             ),
                         )
                         .await;
                     }
 
-                    for (idx, daily) in weather.daily.iter().enumerate() {
-                        let (
-                            time,
-                            temperature_2m_max,
-                            temperature_2m_min,
-                            precipitation_probability_max,
-                            weather_code,
-                        ) = (
-                            &daily.time,
-                            daily.temperature_2m_max,
-                            daily.temperature_2m_min,
-                            daily.precipitation_probability_max,
-                            daily.weather_code,
-                        );
-
-                        msgs::cmd(
-                            &msg_tx_clone,
-                            MODULE,
-                            &format!(
-                                "{} {MODULE} {} {} {city_name} {idx} {time} {temperature_2m_max} {temperature_2m_min} {precipitation_probability_max} {weather_code}",
-                                consts::P,
-                                Action::Update,
-                                WeatherKey::Daily,
-                            ),
-                        )
-                        .await;
+                    // stale data is still better than nothing when offline
+                    if let Some(weather) = fetched.weather {
+                        publish_weather(&msg_tx, &mode, &gui_panel, &city.name, weather).await;
+                    }
+                    publish_error(
+                        &msg_tx,
+                        &mode,
+                        &gui_panel,
+                        &city.name,
+                        fetched.error.as_deref(),
+                    )
+                    .await;
 
-                        // update infos
-                        if mode == Mode::Gui
-                            && let Some(gui_panel_clone) = &gui_panel_clone
-                        {
-                            msgs::cmd(
-                                &msg_tx_clone,
+                    let Some(air_quality_provider) = air_quality_provider else {
+                        return;
+                    };
+                    match air_quality_provider
+                        .get_air_quality(city.latitude, city.longitude)
+                        .await
+                    {
+                        Ok(air_quality) => {
+                            publish_air_quality(
+                                &msg_tx,
+                                &mode,
+                                &gui_panel,
+                                &city.name,
+                                air_quality,
+                            )
+                            .await;
+                        }
+                        Err(e) => {
+                            msgs::warn(
+                                &msg_tx,
                                 MODULE,
-                                &format!(
-                                    "{} {gui_panel_clone} {} {MODULE} {} {city_name} {idx} {time} {temperature_2m_max} {temperature_2m_min} {precipitation_probability_max} {weather_code}",
-                                    consts::P,
-                                    Action::Update,
-                                    WeatherKey::Daily,
-                                ),
+                                &format!("[{}] `{}`: {e}", air_quality_provider.name(), city.name),
                             )
                             .await;
                         }
                     }
-                }
+                });
             }
+
+            while join_set.join_next().await.is_some() {}
+            updating_clone.store(false, Ordering::SeqCst);
+
+            // queued after all the updates above
+            msgs::cmd(
+                &msg_tx_clone,
+                MODULE,
+                &format!("{} {MODULE} {}", consts::P, Action::Check),
+            )
+            .await;
         });
     }
 
+    // p weather check
+    async fn handle_action_check(&mut self) {
+        let today = chrono::Local::now().date_naive().to_string();
+
+        let mut fired = vec![];
+        for alert in self.alerts.iter_mut() {
+            if alert.fired.as_deref() == Some(today.as_str()) {
+                continue;
+            }
+
+            let Some(weather) = self
+                .cities
+                .iter()
+                .find(|city| city.name == alert.city)
+                .and_then(|city| city.weather.as_ref())
+            else {
+                continue;
+            };
+
+            // the first day in the forecast that matches
+            if let Some((date, value)) = weather.daily.iter().find_map(|daily| {
+                alert
+                    .condition
+                    .matches(daily)
+                    .map(|value| (daily.time.clone(), value))
+            }) {
+                alert.fired = Some(today.clone());
+                fired.push((
+                    format!(
+                        "Weather alert: `{}` {} on {date} ({value})",
+                        alert.city, alert.condition
+                    ),
+                    alert
+                        .cmd
+                        .replace("{city}", &alert.city)
+                        .replace("{date}", &date)
+                        .replace("{value}", &value.to_string()),
+                ));
+            }
+        }
+
+        for (msg, cmd) in fired {
+            self.warn(msg).await;
+            self.cmd(cmd).await;
+        }
+    }
+
+    // p weather alert add <city> "<condition>" "<cmd>"
+    // p weather alert remove <idx>
+    async fn handle_action_alert(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|action| action.parse::<Action>()) {
+            Some(Ok(Action::Add)) => {
+                if let (Some(city_name), Some(condition), Some(cmd)) =
+                    (cmd_parts.get(4), cmd_parts.get(5), cmd_parts.get(6))
+                {
+                    match condition.parse::<WeatherCondition>() {
+                        Ok(condition) => {
+                            if !self.cities.iter().any(|city| city.name == *city_name) {
+                                self.warn(format!("City `{city_name}` not found.")).await;
+                                return;
+                            }
+                            self.info(format!("Alert added: {city_name} {condition} `{cmd}`"))
+                                .await;
+                            self.alerts.push(Alert {
+                                city: city_name.to_string(),
+                                condition,
+                                cmd: cmd.to_string(),
+                                fired: None,
+                            });
+                        }
+                        Err(e) => {
+                            self.warn(common::MsgTemplate::InvalidParameters.format(
+                                &format!("<condition> (`{condition}`: {e})"),
+                                Action::Alert.as_ref(),
+                                &cmd_parts.join(" "),
+                            ))
+                            .await;
+                        }
+                    }
+                } else {
+                    self.warn(common::MsgTemplate::MissingParameters.format(
+                        "<city> \"<condition>\" \"<cmd>\"",
+                        Action::Alert.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            }
+            Some(Ok(Action::Remove)) => match cmd_parts.get(4).map(|idx| idx.parse::<usize>()) {
+                Some(Ok(idx)) if idx < self.alerts.len() => {
+                    let alert = self.alerts.remove(idx);
+                    self.info(format!(
+                        "Alert removed: {} {} `{}`",
+                        alert.city, alert.condition, alert.cmd
+                    ))
+                    .await;
+                }
+                _ => {
+                    self.warn(common::MsgTemplate::InvalidParameters.format(
+                        "<idx>",
+                        Action::Alert.as_ref(),
+                        &cmd_parts.join(" "),
+                    ))
+                    .await;
+                }
+            },
+            _ => {
+                self.warn(common::MsgTemplate::MissingParameters.format(
+                    ALERT_PARAMS,
+                    Action::Alert.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
     // p weather update summary ...
     async fn handle_action_update_summary(&mut self, cmd_parts: &[String]) {
         #[allow(clippy::collapsible_if)]
@@ -205,20 +460,45 @@ impl PluginUnit {
                 let time = time.to_string();
                 let temperature = temperature.parse::<f32>().unwrap();
                 let weathercode = weathercode.parse::<u8>().unwrap();
+                let utc_offset_seconds = cmd_parts
+                    .get(8)
+                    .and_then(|offset| offset.parse::<i32>().ok())
+                    .unwrap_or_default();
 
                 if let Some(weather) = city.weather.as_mut() {
                     weather.time = time;
                     weather.temperature = temperature;
                     weather.weathercode = weathercode;
+                    weather.utc_offset_seconds = utc_offset_seconds;
                 } else {
                     city.weather = Some(Weather {
                         time,
                         temperature,
                         weathercode,
+                        utc_offset_seconds,
                         daily: vec![],
+                        hourly: vec![],
                     });
                 }
             }
+
+            if let (Ok(temperature), Ok(weathercode)) =
+                (temperature.parse::<f32>(), weathercode.parse::<u8>())
+                && self.last_observation.get(city_name) != Some(time)
+            {
+                self.last_observation
+                    .insert(city_name.to_string(), time.to_string());
+                self.record(
+                    city_name,
+                    WeatherRecord::Observation {
+                        ts: utils::time::ts(),
+                        time: time.to_string(),
+                        temperature,
+                        weathercode,
+                    },
+                )
+                .await;
+            }
         }
     }
 
@@ -252,25 +532,210 @@ impl PluginUnit {
                         .parse::<u8>()
                         .unwrap(),
                     weather_code: weather_code.parse::<u8>().unwrap(),
+                    sunrise: cmd_parts
+                        .get(11)
+                        .filter(|sunrise| *sunrise != consts::NA)
+                        .cloned(),
+                    sunset: cmd_parts
+                        .get(12)
+                        .filter(|sunset| *sunset != consts::NA)
+                        .cloned(),
+                    wind_speed_10m_max: cmd_parts
+                        .get(13)
+                        .and_then(|wind| wind.parse::<f32>().ok())
+                        .unwrap_or_default(),
+                    uv_index_max: cmd_parts
+                        .get(14)
+                        .and_then(|uv| uv.parse::<f32>().ok())
+                        .unwrap_or_default(),
                 };
 
                 if let Some(weather) = city.weather.as_mut() {
                     if weather.daily.len() <= idx {
-                        weather.daily.resize_with(idx + 1, || WeatherDaily {
-                            time: String::new(),
-                            temperature_2m_max: 0.0,
-                            temperature_2m_min: 0.0,
-                            precipitation_probability_max: 0,
-                            weather_code: 0,
-                        });
+                        weather.daily.resize_with(idx + 1, WeatherDaily::default);
                     }
 
-                    weather.daily[idx] = daily;
+                    weather.daily[idx] = daily.clone();
+                }
+
+                // tomorrow, compared with the observations once the day is over
+                if idx == 1 && self.last_forecast.get(city_name) != Some(&daily.time) {
+                    self.last_forecast
+                        .insert(city_name.to_string(), daily.time.clone());
+                    self.record(
+                        city_name,
+                        WeatherRecord::Forecast {
+                            ts: utils::time::ts(),
+                            date: daily.time,
+                            temperature_2m_max: daily.temperature_2m_max,
+                            temperature_2m_min: daily.temperature_2m_min,
+                        },
+                    )
+                    .await;
+                }
+            }
+        }
+    }
+
+    async fn record(&self, city_name: &str, record: WeatherRecord) {
+        if let Err(e) =
+            weather_history::history_path(city_name).and_then(|path| store::append(&path, &record))
+        {
+            self.warn(format!("Failed to record history of `{city_name}`: {e}"))
+                .await;
+        }
+    }
+
+    // records older than the retention, once a day
+    async fn prune_history(&mut self) {
+        let now = utils::time::ts();
+        if self.last_prune + PRUNE_INTERVAL > now {
+            return;
+        }
+        self.last_prune = now;
+
+        let from = now.saturating_sub(HISTORY_RETENTION_DAYS * 24 * 60 * 60);
+        for city in &self.cities {
+            if let Err(e) = weather_history::history_path(&city.name).and_then(|path| {
+                store::retain::<WeatherRecord>(&path, |record| record.ts() >= from)
+            }) {
+                self.warn(format!("Failed to prune history of `{}`: {e}", city.name))
+                    .await;
+            }
+        }
+    }
+
+    // p weather history <city> [days]
+    async fn handle_action_history(&self, cmd_parts: &[String]) {
+        let Some(city_name) = cmd_parts.get(3) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<city> [days]",
+                Action::History.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        let days = match cmd_parts.get(4).map(|days| days.parse::<u64>()) {
+            None => DEFAULT_HISTORY_DAYS,
+            Some(Ok(days)) if days > 0 => days,
+            Some(_) => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "[days]",
+                    Action::History.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        };
+
+        let records = match weather_history::history_path(city_name)
+            .and_then(|path| store::load::<WeatherRecord>(&path))
+        {
+            Ok(records) => records,
+            Err(e) => {
+                self.warn(format!("Failed to load history of `{city_name}`: {e}"))
+                    .await;
+                return;
+            }
+        };
+
+        let to = utils::time::ts();
+        let from = to.saturating_sub(days * 24 * 60 * 60);
+        let history = WeatherHistory::new(&records, from, to);
+
+        self.info(format!(
+            "{} {city_name} (last {days} days)",
+            Action::History
+        ))
+        .await;
+        self.info(format!("  Observations: {}", history.observations))
+            .await;
+        self.info(format!(
+            "  Temperature:  {}",
+            match history.temperature {
+                Some((min, avg, max)) => format!(
+                    "min {} / avg {} / max {}",
+                    weather::temperature_str(min),
+                    weather::temperature_str(avg),
+                    weather::temperature_str(max),
+                ),
+                None => consts::NA.to_string(),
+            }
+        ))
+        .await;
+        self.info(format!(
+            "  Trend:        {}",
+            common::sparkline(&history.trend(HISTORY_TREND_POINTS))
+        ))
+        .await;
+        self.info(format!("  Forecast vs actual: {}", history.days.len()))
+            .await;
+        for day in &history.days {
+            self.info(format!(
+                "    {}  forecast {}  actual {}",
+                day.date,
+                weather::temperature_range_str(day.forecast.0, day.forecast.1),
+                weather::temperature_range_str(day.actual.0, day.actual.1),
+            ))
+            .await;
+        }
+        if let Some((max, min)) = history.forecast_error() {
+            self.info(format!(
+                "  Mean error:   max {} / min {}",
+                weather::temperature_delta_str(max),
+                weather::temperature_delta_str(min),
+            ))
+            .await;
+        }
+    }
+
+    // p weather update hourly <city_name> '<json>'
+    async fn handle_action_update_hourly(&mut self, cmd_parts: &[String]) {
+        if let (Some(city_name), Some(hourly)) = (cmd_parts.get(4), cmd_parts.get(5))
+            && let Some(city) = self.cities.iter_mut().find(|city| city.name == *city_name)
+            && let Some(weather) = city.weather.as_mut()
+        {
+            match serde_json::from_str::<Vec<WeatherHourly>>(hourly) {
+                Ok(hourly) => weather.hourly = hourly,
+                Err(e) => {
+                    self.warn(format!("Invalid hourly forecast for `{city_name}`: {e}"))
+                        .await;
                 }
             }
         }
     }
 
+    // p weather update error <city_name> ["<error>"]
+    async fn handle_action_update_error(&mut self, cmd_parts: &[String]) {
+        if let Some(city_name) = cmd_parts.get(4)
+            && let Some(city) = self.cities.iter_mut().find(|city| city.name == *city_name)
+        {
+            city.error = cmd_parts.get(5).cloned();
+        }
+    }
+
+    // p weather update air_quality <city_name> <time> <us_aqi> <pm2_5> <pm10>
+    async fn handle_action_update_air_quality(&mut self, cmd_parts: &[String]) {
+        if let (Some(city_name), Some(time), Some(us_aqi), Some(pm2_5), Some(pm10)) = (
+            cmd_parts.get(4),
+            cmd_parts.get(5),
+            cmd_parts.get(6),
+            cmd_parts.get(7),
+            cmd_parts.get(8),
+        ) && let Some(city) = self.cities.iter_mut().find(|city| city.name == *city_name)
+        {
+            city.air_quality = Some(AirQuality {
+                time: time.to_string(),
+                us_aqi: us_aqi.parse::<u16>().unwrap_or_default(),
+                pm2_5: pm2_5.parse::<f32>().unwrap_or_default(),
+                pm10: pm10.parse::<f32>().unwrap_or_default(),
+            });
+        }
+    }
+
     // p weather update
     // p weather update <weather_key> ...
     async fn handle_action_update(&mut self, cmd_parts: &[String]) {
@@ -278,6 +743,11 @@ impl PluginUnit {
             match weather_key.parse::<WeatherKey>() {
                 Ok(WeatherKey::Summary) => self.handle_action_update_summary(cmd_parts).await,
                 Ok(WeatherKey::Daily) => self.handle_action_update_daily(cmd_parts).await,
+                Ok(WeatherKey::Hourly) => self.handle_action_update_hourly(cmd_parts).await,
+                Ok(WeatherKey::Error) => self.handle_action_update_error(cmd_parts).await,
+                Ok(WeatherKey::AirQuality) => {
+                    self.handle_action_update_air_quality(cmd_parts).await
+                }
                 Err(_) => {
                     self.warn(common::MsgTemplate::InvalidParameters.format(
                         &format!("<weather_key> (`{weather_key}`)"),
@@ -292,35 +762,332 @@ impl PluginUnit {
         }
     }
 
+    // keep the city list of infos in sync, the params are quoted
+    async fn update_infos(&self, action: Action, params: &[&str]) {
+        if self.mode == Mode::Gui
+            && let Some(gui_panel) = &self.gui_panel
+        {
+            self.cmd(format!(
+                "{} {gui_panel} {action} {MODULE} {}",
+                consts::P,
+                shell_words::join(params)
+            ))
+            .await;
+        }
+    }
+
+    // p weather add <name>, resolved then added with coordinates
+    async fn handle_action_add_geocode(&self, city_name: &str) {
+        let Some(geocoder) = geocoding::geocoder(&self.geocoder) else {
+            self.warn(format!("Unknown geocoder `{}`", self.geocoder))
+                .await;
+            return;
+        };
+
+        let msg_tx_clone = self.msg_tx.clone();
+        let city_name = city_name.to_string();
+        tokio::spawn(async move {
+            match geocoder.geocode(&city_name).await {
+                Ok(place) => {
+                    msgs::info(
+                        &msg_tx_clone,
+                        MODULE,
+                        &format!(
+                            "[{}] `{city_name}`: {}, {} ({}, {})",
+                            geocoder.name(),
+                            place.name,
+                            place.country.as_deref().unwrap_or(consts::NA),
+                            place.latitude,
+                            place.longitude
+                        ),
+                    )
+                    .await;
+                    msgs::cmd(
+                        &msg_tx_clone,
+                        MODULE,
+                        &format!(
+                            "{} {MODULE} {} {} {} {}",
+                            consts::P,
+                            Action::Add,
+                            shell_words::quote(&city_name),
+                            place.latitude,
+                            place.longitude
+                        ),
+                    )
+                    .await;
+                }
+                Err(e) => {
+                    msgs::warn(
+                        &msg_tx_clone,
+                        MODULE,
+                        &format!("[{}] `{city_name}`: {e}", geocoder.name()),
+                    )
+                    .await;
+                }
+            }
+        });
+    }
+
+    // p weather remove <name>
+    async fn handle_action_remove(&mut self, cmd_parts: &[String]) {
+        let Some(city_name) = cmd_parts.get(3) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<name>",
+                Action::Remove.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        let Some(idx) = self.cities.iter().position(|city| city.name == *city_name) else {
+            self.warn(format!("City `{city_name}` not found.")).await;
+            return;
+        };
+
+        self.cities.remove(idx);
+        self.info(format!("Removed city: `{city_name}`")).await;
+
+        let alerts = self.alerts.len();
+        self.alerts.retain(|alert| alert.city != *city_name);
+        if alerts != self.alerts.len() {
+            self.info(format!(
+                "Removed {} alert(s) of `{city_name}`",
+                alerts - self.alerts.len()
+            ))
+            .await;
+        }
+
+        self.update_infos(Action::Remove, &[city_name]).await;
+    }
+
+    // p weather rename <name> <new_name>
+    async fn handle_action_rename(&mut self, cmd_parts: &[String]) {
+        let (Some(city_name), Some(new_name)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<name> <new_name>",
+                Action::Rename.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        if let Err(e) = common::check_file_name(new_name) {
+            self.warn(format!("Invalid city name: {e}")).await;
+            return;
+        }
+        if self.cities.iter().any(|city| city.name == *new_name) {
+            self.warn(format!("City `{new_name}` already exists."))
+                .await;
+            return;
+        }
+
+        let Some(city) = self.cities.iter_mut().find(|city| city.name == *city_name) else {
+            self.warn(format!("City `{city_name}` not found.")).await;
+            return;
+        };
+
+        city.name = new_name.to_string();
+
+        // the history follows the city
+        if let (Ok(history_path), Ok(new_history_path)) = (
+            weather_history::history_path(city_name),
+            weather_history::history_path(new_name),
+        ) && history_path.exists()
+            && let Err(e) = fs::rename(&history_path, new_history_path)
+        {
+            self.warn(format!("Failed to rename history of `{city_name}`: {e}"))
+                .await;
+        }
+        if let Some(time) = self.last_observation.remove(city_name) {
+            self.last_observation.insert(new_name.to_string(), time);
+        }
+        if let Some(date) = self.last_forecast.remove(city_name) {
+            self.last_forecast.insert(new_name.to_string(), date);
+        }
+        for alert in self
+            .alerts
+            .iter_mut()
+            .filter(|alert| alert.city == *city_name)
+        {
+            alert.city = new_name.to_string();
+        }
+        self.info(format!("Renamed city: `{city_name}` -> `{new_name}`"))
+            .await;
+
+        self.update_infos(Action::Rename, &[city_name, new_name])
+            .await;
+    }
+
+    // p weather move <name> <idx>
+    async fn handle_action_move(&mut self, cmd_parts: &[String]) {
+        let (Some(city_name), Some(idx)) = (cmd_parts.get(3), cmd_parts.get(4)) else {
+            self.warn(common::MsgTemplate::MissingParameters.format(
+                "<name> <idx>",
+                Action::Move.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        let Some(from) = self.cities.iter().position(|city| city.name == *city_name) else {
+            self.warn(format!("City `{city_name}` not found.")).await;
+            return;
+        };
+
+        match idx.parse::<usize>() {
+            Ok(to) if to < self.cities.len() => {
+                let city = self.cities.remove(from);
+                self.cities.insert(to, city);
+                self.info(format!("Moved city: `{city_name}` to {to}"))
+                    .await;
+
+                self.update_infos(Action::Move, &[city_name, &to.to_string()])
+                    .await;
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    &format!("<idx> (`{idx}`)"),
+                    Action::Move.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p weather locale <locale>
+    async fn handle_action_locale(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|locale| locale.parse::<Locale>()) {
+            Some(Ok(locale)) => {
+                globals::set_locale(locale);
+                self.info(format!("Locale: {locale}")).await;
+                self.redraw_infos().await;
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "<en|zh-TW|nl|ja>",
+                    Action::Locale.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p weather units <units>
+    async fn handle_action_units(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3).map(|units| units.parse::<Units>()) {
+            Some(Ok(units)) => {
+                globals::set_units(units);
+                self.info(format!("Units: {units}")).await;
+                self.redraw_infos().await;
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "<metric|imperial>",
+                    Action::Units.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    async fn redraw_infos(&self) {
+        if self.mode == Mode::Gui
+            && let Some(gui_panel) = &self.gui_panel
+        {
+            self.cmd(format!("{} {gui_panel} {}", consts::P, Action::Redraw))
+                .await;
+        }
+    }
+
+    // p weather geocoder <geocoder>
+    async fn handle_action_geocoder(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3) {
+            Some(spec) if geocoding::geocoder(spec).is_some() => {
+                self.geocoder = spec.to_string();
+                self.info(format!("Geocoder: {spec}")).await;
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    &format!("<{}|fixture:<path>>", geocoding::GEOCODERS.join("|")),
+                    Action::Geocoder.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
+    // p weather air_quality <provider>
+    async fn handle_action_air_quality(&mut self, cmd_parts: &[String]) {
+        match cmd_parts.get(3) {
+            Some(spec) if weather::provider(spec).is_some() => {
+                self.air_quality = spec.to_string();
+                self.info(format!("Air quality: {spec}")).await;
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    "<provider>",
+                    Action::AirQuality.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+            }
+        }
+    }
+
     async fn handle_action_add(&mut self, cmd_parts: &[String]) {
+        // the name is also the file name of the history
+        if let Some(city_name) = cmd_parts.get(3)
+            && let Err(e) = common::check_file_name(city_name)
+        {
+            self.warn(format!("Invalid city name: {e}")).await;
+            return;
+        }
+
+        if cmd_parts.len() == 4 {
+            self.handle_action_add_geocode(&cmd_parts[3]).await;
+            return;
+        }
+
         if let (Some(city_name), Some(latitude), Some(longitude)) =
             (cmd_parts.get(3), cmd_parts.get(4), cmd_parts.get(5))
         {
-            match (latitude.parse::<f32>(), longitude.parse::<f32>()) {
-                (Ok(latitude), Ok(longitude)) => {
+            let provider = cmd_parts
+                .get(6)
+                .map_or(weather::DEFAULT_PROVIDER, |provider| provider.as_str());
+
+            match (
+                latitude.parse::<f32>(),
+                longitude.parse::<f32>(),
+                weather::provider(provider),
+            ) {
+                (Ok(latitude), Ok(longitude), Some(_)) => {
                     if !self.cities.iter().any(|city| city.name == *city_name) {
                         self.cities.push(City {
                             name: city_name.to_string(),
                             latitude,
                             longitude,
+                            provider: provider.to_string(),
                             weather: None,
+                            error: None,
+                            air_quality: None,
                         });
                         self.info(format!(
-                            "Added city: `{city_name}` ({latitude}, {longitude})"
+                            "Added city: `{city_name}` ({latitude}, {longitude}) from {provider}"
                         ))
                         .await;
 
-                        // update infos
-                        if self.mode == Mode::Gui
-                            && let Some(gui_panel) = &self.gui_panel
-                        {
-                            self.cmd(format!(
-                                "{} {gui_panel} {} {MODULE} {city_name} {latitude} {longitude}",
-                                consts::P,
-                                Action::Add,
-                            ))
-                            .await;
-                        }
+                        self.update_infos(
+                            Action::Add,
+                            &[city_name, &latitude.to_string(), &longitude.to_string()],
+                        )
+                        .await;
                     } else {
                         self.warn(format!("City `{city_name}` already exists."))
                             .await;
@@ -363,6 +1130,16 @@ impl plugins_main::Plugin for PluginUnit {
             Action::Gui => self.handle_action_gui(cmd_parts).await,
             Action::Update => self.handle_action_update(cmd_parts).await,
             Action::Add => self.handle_action_add(cmd_parts).await,
+            Action::Check => self.handle_action_check().await,
+            Action::Alert => self.handle_action_alert(cmd_parts).await,
+            Action::Remove => self.handle_action_remove(cmd_parts).await,
+            Action::Rename => self.handle_action_rename(cmd_parts).await,
+            Action::Move => self.handle_action_move(cmd_parts).await,
+            Action::Geocoder => self.handle_action_geocoder(cmd_parts).await,
+            Action::AirQuality => self.handle_action_air_quality(cmd_parts).await,
+            Action::Locale => self.handle_action_locale(cmd_parts).await,
+            Action::Units => self.handle_action_units(cmd_parts).await,
+            Action::History => self.handle_action_history(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
@@ -370,3 +1147,217 @@ impl plugins_main::Plugin for PluginUnit {
         }
     }
 }
+
+// p weather update <weather_key> ..., to weather itself and infos
+async fn publish_weather(
+    msg_tx: &Sender<Msg>,
+    mode: &Mode,
+    gui_panel: &Option<String>,
+    city_name: &str,
+    weather: Weather,
+) {
+    let (time, temperature, weathercode, utc_offset_seconds) = (
+        weather.time,
+        weather.temperature,
+        weather.weathercode,
+        weather.utc_offset_seconds,
+    );
+
+    msgs::cmd(
+        msg_tx,
+        MODULE,
+        &format!(
+            "{} {MODULE} {} {} {city_name} {time} {temperature} {weathercode} {utc_offset_seconds}",
+            consts::P,
+            Action::Update,
+            WeatherKey::Summary,
+        ),
+    )
+    .await;
+
+    // update infos
+    if *mode == Mode::Gui
+        && let Some(gui_panel) = &gui_panel
+    {
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {gui_panel} {} {MODULE} {} {city_name} {time} {temperature} {weathercode} {utc_offset_seconds}",
+                consts::P,
+                Action::Update,
+                WeatherKey::Summary,
+            ),
+        )
+        .await;
+    }
+
+    for (idx, daily) in weather.daily.iter().enumerate() {
+        let (
+            time,
+            temperature_2m_max,
+            temperature_2m_min,
+            precipitation_probability_max,
+            weather_code,
+        ) = (
+            &daily.time,
+            daily.temperature_2m_max,
+            daily.temperature_2m_min,
+            daily.precipitation_probability_max,
+            daily.weather_code,
+        );
+        let (sunrise, sunset, wind_speed_10m_max, uv_index_max) = (
+            daily.sunrise.as_deref().unwrap_or(consts::NA),
+            daily.sunset.as_deref().unwrap_or(consts::NA),
+            daily.wind_speed_10m_max,
+            daily.uv_index_max,
+        );
+
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {MODULE} {} {} {city_name} {idx} {time} {temperature_2m_max} {temperature_2m_min} {precipitation_probability_max} {weather_code} {sunrise} {sunset} {wind_speed_10m_max} {uv_index_max}",
+                consts::P,
+                Action::Update,
+                WeatherKey::Daily,
+            ),
+        )
+        .await;
+
+        // update infos
+        if *mode == Mode::Gui
+            && let Some(gui_panel) = &gui_panel
+        {
+            msgs::cmd(
+                msg_tx,
+                MODULE,
+                &format!(
+                    "{} {gui_panel} {} {MODULE} {} {city_name} {idx} {time} {temperature_2m_max} {temperature_2m_min} {precipitation_probability_max} {weather_code} {sunrise} {sunset} {wind_speed_10m_max} {uv_index_max}",
+                    consts::P,
+                    Action::Update,
+                    WeatherKey::Daily,
+                ),
+            )
+            .await;
+        }
+    }
+
+    if let Ok(hourly) = serde_json::to_string(&weather.hourly) {
+        let hourly = shell_words::quote(&hourly);
+
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {MODULE} {} {} {city_name} {hourly}",
+                consts::P,
+                Action::Update,
+                WeatherKey::Hourly,
+            ),
+        )
+        .await;
+
+        // update infos
+        if *mode == Mode::Gui
+            && let Some(gui_panel) = &gui_panel
+        {
+            msgs::cmd(
+                msg_tx,
+                MODULE,
+                &format!(
+                    "{} {gui_panel} {} {MODULE} {} {city_name} {hourly}",
+                    consts::P,
+                    Action::Update,
+                    WeatherKey::Hourly,
+                ),
+            )
+            .await;
+        }
+    }
+}
+
+// p weather update air_quality <city_name> ..., to weather itself and infos
+async fn publish_air_quality(
+    msg_tx: &Sender<Msg>,
+    mode: &Mode,
+    gui_panel: &Option<String>,
+    city_name: &str,
+    air_quality: AirQuality,
+) {
+    let (time, us_aqi, pm2_5, pm10) = (
+        air_quality.time,
+        air_quality.us_aqi,
+        air_quality.pm2_5,
+        air_quality.pm10,
+    );
+
+    msgs::cmd(
+        msg_tx,
+        MODULE,
+        &format!(
+            "{} {MODULE} {} {} {city_name} {time} {us_aqi} {pm2_5} {pm10}",
+            consts::P,
+            Action::Update,
+            WeatherKey::AirQuality,
+        ),
+    )
+    .await;
+
+    // update infos
+    if *mode == Mode::Gui
+        && let Some(gui_panel) = gui_panel
+    {
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {gui_panel} {} {MODULE} {} {city_name} {time} {us_aqi} {pm2_5} {pm10}",
+                consts::P,
+                Action::Update,
+                WeatherKey::AirQuality,
+            ),
+        )
+        .await;
+    }
+}
+
+// p weather update error <city_name> ["<error>"], no error clears it
+async fn publish_error(
+    msg_tx: &Sender<Msg>,
+    mode: &Mode,
+    gui_panel: &Option<String>,
+    city_name: &str,
+    error: Option<&str>,
+) {
+    let error = error.map(shell_words::quote).unwrap_or_default();
+
+    msgs::cmd(
+        msg_tx,
+        MODULE,
+        &format!(
+            "{} {MODULE} {} {} {city_name} {error}",
+            consts::P,
+            Action::Update,
+            WeatherKey::Error,
+        ),
+    )
+    .await;
+
+    // update infos
+    if *mode == Mode::Gui
+        && let Some(gui_panel) = gui_panel
+    {
+        msgs::cmd(
+            msg_tx,
+            MODULE,
+            &format!(
+                "{} {gui_panel} {} {MODULE} {} {city_name} {error}",
+                consts::P,
+                Action::Update,
+                WeatherKey::Error,
+            ),
+        )
+        .await;
+    }
+}
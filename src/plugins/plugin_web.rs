@@ -8,6 +8,7 @@ use serde_json::json;
 use tokio::sync::mpsc::Sender;
 
 use crate::consts;
+use crate::globals;
 use crate::messages::{self as msgs, Action, Msg};
 use crate::plugins::plugins_main::{self, Plugin};
 use crate::utils::{api, common, nas};
@@ -25,6 +26,20 @@ fn is_valid_filename(path: &str) -> bool {
     }) && !path.is_absolute()
 }
 
+// only paths under the allow-listed roots (`web_roots` in cfg.toml) are served
+fn is_allowed_path(path: &str) -> bool {
+    if !is_valid_filename(path) {
+        return false;
+    }
+
+    let root = Path::new(path).components().find_map(|c| match c {
+        std::path::Component::Normal(root) => root.to_str(),
+        _ => None,
+    });
+
+    root.is_some_and(|root| globals::get_web_roots().iter().any(|r| r == root))
+}
+
 async fn msgs_info(msg_tx: &Sender<Msg>, msg: &str) {
     msgs::info(msg_tx, MODULE, msg).await;
 }
@@ -62,7 +77,7 @@ async fn upload(
 
     msgs_info(&msg_tx, &format!("API: POST /upload: `{filename}`")).await;
 
-    if !is_valid_filename(filename) {
+    if !is_allowed_path(filename) {
         return HttpResponse::BadRequest().body("Invalid filename");
     }
 
@@ -88,7 +103,7 @@ async fn download(
 
     msgs_info(&msg_tx, &format!("API: POST /download: `{filename}`")).await;
 
-    if !is_valid_filename(filename) {
+    if !is_allowed_path(filename) {
         return HttpResponse::BadRequest().body("Invalid filename");
     }
 
@@ -128,7 +143,7 @@ async fn remove(
 
     msgs_info(&msg_tx, &format!("API: POST /remove: `{filename}`")).await;
 
-    if !is_valid_filename(filename) {
+    if !is_allowed_path(filename) {
         return HttpResponse::BadRequest().body("Invalid filename");
     }
 
@@ -164,6 +179,10 @@ async fn get_folder_meta(
     )
     .await;
 
+    if !is_allowed_path(foldername) {
+        return HttpResponse::BadRequest().body("Invalid foldername");
+    }
+
     let folder_meta = nas::get_folder_meta(foldername);
 
     msgs_info(
@@ -226,6 +245,8 @@ impl PluginUnit {
 
     async fn handle_action_show(&self) {
         self.info(Action::Show.to_string()).await;
+        self.info(format!("  Roots: {}", globals::get_web_roots().join(", ")))
+            .await;
     }
 
     async fn handle_action_help(&self) {
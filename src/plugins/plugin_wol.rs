@@ -3,6 +3,7 @@ use async_trait::async_trait;
 use tokio::sync::mpsc::Sender;
 
 use crate::consts;
+use crate::globals;
 use crate::messages::{Action, Msg};
 use crate::plugins::plugins_main::{self, Plugin};
 use crate::utils::common;
@@ -10,7 +11,7 @@ use crate::utils::common;
 pub const MODULE: &str = "wol";
 const ADD_PARAMS: &str = "<name> <mac_address>";
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Wol {
     name: String,
     mac: [u8; 6],
@@ -19,6 +20,7 @@ struct Wol {
 #[derive(Debug)]
 pub struct PluginUnit {
     msg_tx: Sender<Msg>,
+    // added by `p wol add`, the inventory of cfg.toml is read when needed
     wol: Vec<Wol>,
 }
 
@@ -30,17 +32,45 @@ impl PluginUnit {
         };
 
         myself.info(consts::NEW.to_string()).await;
+        myself.init().await;
 
         Ok(myself)
     }
 
+    async fn init(&self) {
+        self.info(consts::INIT.to_string()).await;
+    }
+
+    // devices with MAC in the inventory of cfg.toml, then the added ones
+    fn devices(&self) -> Vec<Wol> {
+        // invalid ones are dropped when cfg.toml is loaded
+        let mut devices: Vec<Wol> = globals::get_devices()
+            .into_iter()
+            .filter_map(|device| {
+                let mac = common::parse_mac(device.mac.as_deref()?).ok()?;
+                Some(Wol {
+                    name: device.name,
+                    mac,
+                })
+            })
+            .collect();
+        for device in &self.wol {
+            if !devices.iter().any(|d| d.name == device.name) {
+                devices.push(device.clone());
+            }
+        }
+
+        devices
+    }
+
     async fn handle_action_show(&self) {
         self.info(Action::Show.to_string()).await;
 
-        if self.wol.is_empty() {
+        let devices = self.devices();
+        if devices.is_empty() {
             self.info("  No devices configured.".to_string()).await;
         } else {
-            for device in &self.wol {
+            for device in &devices {
                 self.info(format!(
                     "  {}: {:02X}:{:02X}:{:02X}:{:02X}:{:02X}:{:02X}",
                     device.name,
@@ -76,7 +106,7 @@ impl PluginUnit {
                 .await;
             match common::parse_mac(mac_str) {
                 Ok(mac) => {
-                    if self.wol.iter().any(|d| d.name == *name) {
+                    if self.devices().iter().any(|d| d.name == *name) {
                         self.warn(format!("Device `{name}` already exists.")).await;
                     } else {
                         self.wol.push(Wol {
@@ -105,8 +135,9 @@ impl PluginUnit {
         self.info(Action::Wake.to_string()).await;
 
         if let Some(name) = cmd_parts.get(3) {
+            let name = &globals::get_device_name(name);
             self.info(format!("  Waking device `{name}`")).await;
-            match self.wol.iter().find(|d| d.name == *name) {
+            match self.devices().iter().find(|d| d.name == *name) {
                 Some(device) => match wol::send_wol(wol::MacAddr(device.mac), None, None) {
                     Ok(_) => {
                         self.info(format!("Device `{name}` woken up successfully."))
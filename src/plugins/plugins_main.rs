@@ -2,21 +2,34 @@ use anyhow::Result;
 use async_trait::async_trait;
 use ratatui::{
     DefaultTerminal, Frame,
-    crossterm::{cursor::SetCursorStyle, execute},
+    crossterm::{
+        cursor::SetCursorStyle,
+        event::{DisableMouseCapture, EnableMouseCapture},
+        execute,
+    },
+    layout::Position,
+    prelude::Rect,
+    text::{Line, Span},
+    widgets::Paragraph,
 };
 use tokio::sync::{broadcast, mpsc};
 
 use crate::arguments::Mode;
 use crate::consts;
-use crate::messages::{self as msgs, Action, Data, Key, Msg};
+use crate::globals;
+use crate::messages::{self as msgs, Action, Data, Key, MouseKind, Msg};
 use crate::plugins::{
-    plugin_cfg, plugin_cli, plugin_command, plugin_devices, plugin_editor, plugin_gui,
-    plugin_infos, plugin_log, plugin_mqtt, plugin_music, plugin_ping, plugin_script, plugin_system,
-    plugin_time, plugin_weather, plugin_web, plugin_wol,
+    plugin_cfg, plugin_cli, plugin_command, plugin_devices, plugin_discovery, plugin_editor,
+    plugin_gui, plugin_infos, plugin_log, plugin_mqtt, plugin_music, plugin_ping, plugin_script,
+    plugin_system, plugin_time, plugin_weather, plugin_web, plugin_wol,
 };
-use crate::utils::{common, panel};
+use crate::utils::{common, layout::LayoutNode, panel, theme};
 
 pub const MODULE: &str = "plugins";
+const LAYOUT_NONE: &str = "none";
+const LAYOUT_PARAMS: &str = "add <name> \"<spec>\" | remove <name> | <name|none>";
+const WORKSPACE_NONE: &str = "none";
+const WORKSPACE_PARAMS: &str = "add <name> <panel>... | remove <name> | <name|none>";
 
 #[async_trait]
 pub trait Plugin {
@@ -31,6 +44,13 @@ pub trait Plugin {
         )
     }
 
+    fn panel_info_mut(&mut self) -> &mut panel::PanelInfo {
+        panic!(
+            "`panel_info_mut` is not implemented for plugin: `{}`",
+            self.name()
+        )
+    }
+
     async fn info(&self, msg: String) {
         msgs::info(self.msg_tx(), self.name(), &msg).await;
     }
@@ -73,6 +93,8 @@ pub trait Plugin {
                 y: y.parse::<u16>().unwrap(),
                 w: w.parse::<u16>().unwrap(),
                 h: h.parse::<u16>().unwrap(),
+                rect: None,
+                area: Rect::default(),
             })
         } else {
             msgs::warn(
@@ -105,6 +127,22 @@ pub trait Plugin {
         w: u16,
         h: u16,
     ) -> (u16, u16, u16, u16) {
+        // placed by the active layout, x, y, w and h are not used
+        if self.panel_info().rect.is_some() {
+            msgs::warn(
+                self.msg_tx(),
+                self.name(),
+                &format!(
+                    "`{}` is placed by the layout, `{} {MODULE} {} {LAYOUT_NONE}` to move it",
+                    self.name(),
+                    consts::P,
+                    Action::Layout
+                ),
+            )
+            .await;
+            return (x, y, w, h);
+        }
+
         let mut new_x = x;
         let mut new_y = y;
         let mut new_w = w;
@@ -113,38 +151,27 @@ pub trait Plugin {
             Key::AltUp => {
                 new_y = new_y.saturating_sub(1);
             }
-            Key::AltDown => {
-                // if self.panel_info.y + self.panel_info.h < globals::get_terminal_height() {
+            // in percentage of the screen, kept on it
+            Key::AltDown if new_y + new_h < 100 => {
                 new_y += 1;
-                // }
             }
             Key::AltLeft => {
                 new_x = new_x.saturating_sub(1);
             }
-            Key::AltRight => {
-                // if self.panel_info.x + self.panel_info.w < globals::get_terminal_width() {
+            Key::AltRight if new_x + new_w < 100 => {
                 new_x += 1;
-                // }
             }
-            Key::AltW => {
-                if new_h > 3 {
-                    new_h -= 1;
-                }
+            Key::AltW if new_h > 3 => {
+                new_h -= 1;
             }
-            Key::AltS => {
-                // if self.panel_info.y + self.panel_info.h < globals::get_terminal_height() {
+            Key::AltS if new_y + new_h < 100 => {
                 new_h += 1;
-                // }
             }
-            Key::AltA => {
-                if new_w > 10 {
-                    new_w -= 1;
-                }
+            Key::AltA if new_w > 10 => {
+                new_w -= 1;
             }
-            Key::AltD => {
-                // if self.panel_info.x + self.panel_info.w < globals::get_terminal_width() {
+            Key::AltD if new_x + new_w < 100 => {
                 new_w += 1;
-                // }
             }
             _ => {}
         }
@@ -162,6 +189,33 @@ pub trait Plugin {
     fn draw(&mut self, _frame: &mut Frame, _active: bool) {
         panic!("`draw` is not implemented for plugin: `{}`", self.name())
     }
+
+    // the panel is under the mouse, ignored by default
+    async fn handle_mouse(&mut self, _kind: MouseKind, _column: u16, _row: u16) {}
+
+    // `/<pattern>` in the prompt, empty to stop searching, ignored by default
+    async fn handle_search(&mut self, _pattern: &str) {}
+}
+
+// a left button drag on the border of a panel
+#[derive(Debug)]
+struct Drag {
+    panel: String,
+    // bottom or right border, else moved by the top border
+    resize: bool,
+    column: u16,
+    row: u16,
+    // percentage steps sent so far
+    sent: (i32, i32),
+}
+
+#[derive(Debug)]
+struct Workspace {
+    name: String,
+    // normal panels shown, `command` and popups are always there
+    panels: Vec<String>,
+    // index in `Plugins::panels`, restored as switched back
+    active_panel: Option<usize>,
 }
 
 pub struct Plugins {
@@ -174,6 +228,13 @@ pub struct Plugins {
     panels: Vec<String>,
     active_panel: usize,
     active_popup: Option<usize>,
+    layouts: Vec<(String, LayoutNode)>,
+    // free `x y w h` of each panel if none
+    layout: Option<String>,
+    workspaces: Vec<Workspace>,
+    // all panels if none
+    workspace: Option<usize>,
+    drag: Option<Drag>,
 }
 
 impl Plugins {
@@ -193,6 +254,11 @@ impl Plugins {
             panels: Vec::new(),
             active_panel: 0,
             active_popup: None,
+            layouts: Vec::new(),
+            layout: None,
+            workspaces: Vec::new(),
+            workspace: None,
+            drag: None,
         };
 
         myself.info(consts::NEW.to_string()).await;
@@ -209,6 +275,7 @@ impl Plugins {
         }
 
         self.terminal = Some(ratatui::init());
+        let _ = execute!(std::io::stdout(), EnableMouseCapture);
 
         let mut shutdown_rx = self.shutdown_tx.subscribe();
         tokio::spawn(async move {
@@ -216,6 +283,7 @@ impl Plugins {
 
             let mut stdout = std::io::stdout();
             execute!(stdout, SetCursorStyle::DefaultUserShape).unwrap();
+            let _ = execute!(stdout, DisableMouseCapture);
 
             ratatui::restore();
         });
@@ -234,10 +302,9 @@ impl Plugins {
             }
             plugin_cfg::MODULE => Box::new(plugin_cfg::PluginUnit::new(self.msg_tx.clone()).await?)
                 as Box<dyn Plugin + Send + Sync>,
-            plugin_system::MODULE => {
-                Box::new(plugin_system::PluginUnit::new(self.msg_tx.clone()).await?)
-                    as Box<dyn Plugin + Send + Sync>
-            }
+            plugin_system::MODULE => Box::new(
+                plugin_system::PluginUnit::new(self.msg_tx.clone(), self.mode.clone()).await?,
+            ) as Box<dyn Plugin + Send + Sync>,
             plugin_cli::MODULE => Box::new(plugin_cli::PluginUnit::new(self.msg_tx.clone()).await?)
                 as Box<dyn Plugin + Send + Sync>,
             plugin_web::MODULE => Box::new(plugin_web::PluginUnit::new(self.msg_tx.clone()).await?)
@@ -260,6 +327,10 @@ impl Plugins {
             plugin_devices::MODULE => Box::new(
                 plugin_devices::PluginUnit::new(self.msg_tx.clone(), self.mode.clone()).await?,
             ) as Box<dyn Plugin + Send + Sync>,
+            plugin_discovery::MODULE => {
+                Box::new(plugin_discovery::PluginUnit::new(self.msg_tx.clone()).await?)
+                    as Box<dyn Plugin + Send + Sync>
+            }
             plugin_infos::MODULE => {
                 Box::new(plugin_infos::PluginUnit::new(self.msg_tx.clone()).await?)
                     as Box<dyn Plugin + Send + Sync>
@@ -297,6 +368,13 @@ impl Plugins {
         };
 
         self.plugins.push(plugin);
+        globals::set_plugins(
+            self.plugins
+                .iter()
+                .map(|plugin| plugin.name().to_string())
+                .collect(),
+        );
+
         Ok(())
     }
 
@@ -326,6 +404,32 @@ impl Plugins {
             self.info(format!("    - {}", plugin.name())).await;
         }
 
+        self.info(format!(
+            "  - Layout: {}",
+            self.layout.as_deref().unwrap_or(LAYOUT_NONE)
+        ))
+        .await;
+        for (name, node) in &self.layouts {
+            self.info(format!("    - {name}: {node}")).await;
+        }
+
+        self.info(format!(
+            "  - Workspace: {}",
+            self.workspace
+                .and_then(|idx| self.workspaces.get(idx))
+                .map_or(WORKSPACE_NONE, |workspace| workspace.name.as_str())
+        ))
+        .await;
+        for (idx, workspace) in self.workspaces.iter().enumerate() {
+            self.info(format!(
+                "    - Alt+{} {}: {}",
+                idx + 1,
+                workspace.name,
+                workspace.panels.join(" ")
+            ))
+            .await;
+        }
+
         if self.panels.is_empty() {
             self.info("  - Panels:".to_string()).await;
             self.info("    - <none>".to_string()).await;
@@ -340,15 +444,270 @@ impl Plugins {
     async fn handle_action_help(&self) {
         self.info(Action::Help.to_string()).await;
         self.info(format!("  {} <plugin>", Action::Insert)).await;
+        self.info(format!("  {} {LAYOUT_PARAMS}", Action::Layout))
+            .await;
+        self.info(format!("  {} {WORKSPACE_PARAMS}", Action::Workspace))
+            .await;
+        self.info("    Alt+1..9 to switch to the nth workspace".to_string())
+            .await;
+        self.info(
+            "    e.g. \"rows(60%: cols(70%: log, 30%: mqtt), 40%: infos)\", constraints 60% 20 >10 <10"
+                .to_string(),
+        )
+        .await;
+        self.info(format!("  {} [<pattern>]", Action::Search)).await;
+        self.info("    search in the active panel, also by `/` in the prompt".to_string())
+            .await;
+        self.info(format!(
+            "  {} <{}|{}|{}|theme_file>",
+            Action::Theme,
+            theme::ThemeName::Dark,
+            theme::ThemeName::Light,
+            theme::ThemeName::HighContrast
+        ))
+        .await;
+    }
+
+    // p plugins layout add <name> "<spec>"
+    // p plugins layout remove <name>
+    // p plugins layout <name|none>
+    async fn handle_action_layout(&mut self, cmd_parts: &[String]) {
+        match (
+            cmd_parts.get(3).map(|s| s.as_str()),
+            cmd_parts.get(4),
+            cmd_parts.get(5),
+        ) {
+            (Some("add"), Some(name), Some(spec)) if name != LAYOUT_NONE => {
+                match spec.parse::<LayoutNode>() {
+                    Ok(node) => {
+                        self.info(format!("Layout `{name}`: {node}")).await;
+                        match self.layouts.iter_mut().find(|(n, _)| n == name) {
+                            Some((_, layout)) => *layout = node,
+                            None => self.layouts.push((name.to_string(), node)),
+                        }
+                    }
+                    Err(e) => {
+                        self.warn(format!("Failed to parse layout `{name}`: {e}"))
+                            .await;
+                        return;
+                    }
+                }
+            }
+            (Some("remove"), Some(name), None) => {
+                let len = self.layouts.len();
+                self.layouts.retain(|(n, _)| n != name);
+                if self.layouts.len() == len {
+                    self.warn(format!("Layout `{name}` not found.")).await;
+                    return;
+                }
+                if self.layout.as_ref() == Some(name) {
+                    self.layout = None;
+                }
+            }
+            (Some(LAYOUT_NONE), None, None) => self.layout = None,
+            (Some(name), None, None) => {
+                let Some((_, node)) = self.layouts.iter().find(|(n, _)| n == name) else {
+                    self.warn(format!("Layout `{name}` not found.")).await;
+                    return;
+                };
+
+                let missing = node
+                    .panels()
+                    .into_iter()
+                    .filter(|panel| !self.panels.contains(panel))
+                    .collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    self.info(format!(
+                        "Panels not inserted yet, placed once inserted: {}",
+                        missing.join(", ")
+                    ))
+                    .await;
+                }
+                self.layout = Some(name.to_string());
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    LAYOUT_PARAMS,
+                    Action::Layout.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        self.redraw();
+    }
+
+    // p plugins workspace add <name> <panel>...
+    // p plugins workspace remove <name>
+    // p plugins workspace <name|none>
+    async fn handle_action_workspace(&mut self, cmd_parts: &[String]) {
+        match (cmd_parts.get(3).map(|s| s.as_str()), cmd_parts.get(4)) {
+            (Some("add"), Some(name)) if name != WORKSPACE_NONE && cmd_parts.len() > 5 => {
+                let panels = cmd_parts[5..].to_vec();
+                self.info(format!("Workspace `{name}`: {}", panels.join(" ")))
+                    .await;
+
+                let missing = panels
+                    .iter()
+                    .filter(|panel| !self.panels.contains(panel))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if !missing.is_empty() {
+                    self.warn(format!(
+                        "Panels not inserted yet, shown once inserted: {}",
+                        missing.join(", ")
+                    ))
+                    .await;
+                }
+                match self.workspaces.iter_mut().find(|w| w.name == *name) {
+                    Some(workspace) => workspace.panels = panels,
+                    None => self.workspaces.push(Workspace {
+                        name: name.to_string(),
+                        panels,
+                        active_panel: None,
+                    }),
+                }
+            }
+            (Some("remove"), Some(name)) if cmd_parts.len() == 5 => {
+                let Some(idx) = self.workspaces.iter().position(|w| w.name == *name) else {
+                    self.warn(format!("Workspace `{name}` not found.")).await;
+                    return;
+                };
+                self.workspaces.remove(idx);
+                self.workspace = match self.workspace {
+                    Some(active) if active == idx => None,
+                    Some(active) if active > idx => Some(active - 1),
+                    active => active,
+                };
+            }
+            (Some(WORKSPACE_NONE), None) => self.switch_workspace(None),
+            (Some(name), None) => {
+                let Some(idx) = self.workspaces.iter().position(|w| w.name == name) else {
+                    self.warn(format!("Workspace `{name}` not found.")).await;
+                    return;
+                };
+                self.switch_workspace(Some(idx));
+            }
+            _ => {
+                self.warn(common::MsgTemplate::InvalidParameters.format(
+                    WORKSPACE_PARAMS,
+                    Action::Workspace.as_ref(),
+                    &cmd_parts.join(" "),
+                ))
+                .await;
+                return;
+            }
+        }
+
+        self.redraw();
+    }
+
+    fn switch_workspace(&mut self, workspace: Option<usize>) {
+        if let Some(workspace) = self.workspace.and_then(|idx| self.workspaces.get_mut(idx)) {
+            workspace.active_panel = Some(self.active_panel);
+        }
+        self.workspace = workspace;
+
+        let restored = workspace
+            .and_then(|idx| self.workspaces.get(idx))
+            .and_then(|workspace| workspace.active_panel);
+        match restored {
+            Some(active_panel) if self.is_focusable(active_panel) => {
+                self.active_panel = active_panel
+            }
+            _ => {
+                if let Some(active_panel) =
+                    (0..self.panels.len()).find(|idx| self.is_focusable(*idx))
+                {
+                    self.active_panel = active_panel;
+                }
+            }
+        }
+    }
+
+    fn is_visible(&self, panel: &str) -> bool {
+        match self.workspace.and_then(|idx| self.workspaces.get(idx)) {
+            Some(workspace) => {
+                panel == plugin_command::MODULE || workspace.panels.iter().any(|p| p == panel)
+            }
+            None => true,
+        }
+    }
+
+    // a visible normal panel
+    fn is_focusable(&self, idx: usize) -> bool {
+        self.panels.get(idx).is_some_and(|panel| {
+            self.is_visible(panel)
+                && self.get_plugin(panel).is_some_and(|plugin| {
+                    plugin.panel_info().panel_type == panel::PanelType::Normal
+                })
+        })
+    }
+
+    // on the top border of `command`, right aligned
+    fn draw_workspaces(&self, frame: &mut Frame) {
+        if self.workspaces.is_empty() {
+            return;
+        }
+
+        let theme = globals::get_theme();
+        let spans = self
+            .workspaces
+            .iter()
+            .enumerate()
+            .map(|(idx, workspace)| {
+                let span = Span::raw(format!(" {}:{} ", idx + 1, workspace.name));
+                if self.workspace == Some(idx) {
+                    span.style(theme.selected)
+                } else {
+                    span
+                }
+            })
+            .collect::<Vec<_>>();
+        let line = Line::from(spans);
+
+        let area = frame.area();
+        let width = (line.width() as u16).min(area.width.saturating_sub(2));
+        let tab_bar = Rect {
+            x: area.width.saturating_sub(width + 1),
+            y: panel::layout_area(frame).height,
+            width,
+            height: 1,
+        };
+        frame.render_widget(Paragraph::new(line), tab_bar.intersection(area));
+    }
+
+    // areas of the active layout, the other panels keep their own position
+    fn apply_layout(&mut self, frame: &Frame) {
+        let areas = self
+            .layout
+            .as_ref()
+            .and_then(|layout| self.layouts.iter().find(|(name, _)| name == layout))
+            .map(|(_, node)| node.areas(panel::layout_area(frame)))
+            .unwrap_or_default();
+
+        for panel in self.panels.clone() {
+            let rect = areas
+                .iter()
+                .find(|(name, _)| *name == panel)
+                .map(|(_, rect)| *rect);
+            if let Some(plugin) = self.get_plugin_mut(&panel) {
+                plugin.panel_info_mut().rect = rect;
+            }
+        }
     }
 
     fn draw(&mut self, frame: &mut Frame) {
+        self.apply_layout(frame);
+
         let panels = self.panels.clone();
         let active_panel = self.active_panel;
         let active_popup = self.active_popup;
 
         for (idx, panel) in panels.iter().enumerate() {
-            if idx == active_panel {
+            if idx == active_panel || !self.is_visible(panel) {
                 continue;
             }
             #[allow(clippy::collapsible_if)]
@@ -360,7 +719,7 @@ impl Plugins {
         }
 
         for (idx, panel) in panels.iter().enumerate() {
-            if idx != active_panel {
+            if idx != active_panel || !self.is_visible(panel) {
                 continue;
             }
             #[allow(clippy::collapsible_if)]
@@ -371,6 +730,8 @@ impl Plugins {
             }
         }
 
+        self.draw_workspaces(frame);
+
         // for popup panels
         if let Some(active_popup) = active_popup {
             for (idx, panel) in panels.iter().enumerate() {
@@ -388,12 +749,22 @@ impl Plugins {
     }
 
     fn redraw(&mut self) {
-        let mut terminal = self.terminal.take().unwrap();
+        // no terminal in cli mode
+        let Some(mut terminal) = self.terminal.take() else {
+            return;
+        };
         let _ = terminal.draw(|frame| self.draw(frame));
         self.terminal = Some(terminal);
     }
 
-    async fn handle_action_redraw(&mut self) {
+    // p plugins redraw [clear], clear e.g. after the terminal was given up
+    async fn handle_action_redraw(&mut self, cmd_parts: &[String]) {
+        if cmd_parts.get(3).is_some_and(|arg| arg == "clear")
+            && let Some(terminal) = &mut self.terminal
+        {
+            let _ = terminal.clear();
+        }
+
         self.redraw();
     }
 
@@ -421,15 +792,11 @@ impl Plugins {
     }
 
     async fn handle_action_key_tab(&mut self) {
+        // popups and panels of other workspaces are skipped
         if self.active_popup.is_none() {
-            self.active_panel = (self.active_panel + 1) % self.panels.len();
-            loop {
-                let panel = &self.panels[self.active_panel];
-                if let Some(plugin) = self.get_plugin(panel) {
-                    if plugin.panel_info().panel_type == panel::PanelType::Popup {
-                        self.active_panel = (self.active_panel + 1) % self.panels.len();
-                        continue;
-                    }
+            for _ in 0..self.panels.len() {
+                self.active_panel = (self.active_panel + 1) % self.panels.len();
+                if self.is_focusable(self.active_panel) {
                     break;
                 }
             }
@@ -463,6 +830,9 @@ impl Plugins {
                 | Ok(k @ Key::Right)
                 | Ok(k @ Key::Home)
                 | Ok(k @ Key::End)
+                | Ok(k @ Key::PageUp)
+                | Ok(k @ Key::PageDown)
+                | Ok(k @ Key::Enter)
                 | Ok(k @ Key::AltC)
                 | Ok(k @ Key::AltUp)
                 | Ok(k @ Key::AltDown)
@@ -472,13 +842,187 @@ impl Plugins {
                 | Ok(k @ Key::AltS)
                 | Ok(k @ Key::AltA)
                 | Ok(k @ Key::AltD)
-                | Ok(k @ Key::ControlX) => self.handle_action_key_key(k).await,
+                | Ok(k @ Key::ControlX)
+                | Ok(k @ Key::ControlF) => self.handle_action_key_key(k).await,
                 Ok(Key::Tab) => self.handle_action_key_tab().await,
+                Ok(k) if msgs::ALT_DIGITS.contains(&k) => {
+                    let idx = msgs::ALT_DIGITS.iter().position(|d| *d == k).unwrap();
+                    if idx < self.workspaces.len() {
+                        self.switch_workspace(Some(idx));
+                        self.redraw();
+                    }
+                }
                 _ => (),
             }
         }
     }
 
+    // the popup if any, else the topmost panel, the active one is drawn last
+    fn panel_at(&self, column: u16, row: u16) -> Option<usize> {
+        let position = Position::new(column, row);
+        let contains = |idx: usize| {
+            self.panels
+                .get(idx)
+                .and_then(|panel| self.get_plugin(panel))
+                .is_some_and(|plugin| plugin.panel_info().area.contains(position))
+        };
+
+        if let Some(active_popup) = self.active_popup {
+            return contains(active_popup).then_some(active_popup);
+        }
+
+        std::iter::once(self.active_panel)
+            .chain((0..self.panels.len()).rev())
+            .find(|idx| self.is_focusable(*idx) && contains(*idx))
+    }
+
+    async fn handle_mouse_down(&mut self, idx: usize, column: u16, row: u16) {
+        if self.active_popup.is_none() {
+            self.active_panel = idx;
+        }
+
+        let panel = self.panels[idx].clone();
+        let Some((area, placed)) = self
+            .get_plugin(&panel)
+            .map(|plugin| (plugin.panel_info().area, plugin.panel_info().rect.is_some()))
+        else {
+            return;
+        };
+
+        // placed by the layout otherwise
+        let right = area.x + area.width.saturating_sub(1);
+        let bottom = area.y + area.height.saturating_sub(1);
+        if !placed && (row == area.y || column == right || row == bottom) {
+            self.drag = Some(Drag {
+                panel: panel.clone(),
+                resize: row != area.y,
+                column,
+                row,
+                sent: (0, 0),
+            });
+        }
+
+        if let Some(plugin) = self.get_plugin_mut(&panel) {
+            plugin.handle_mouse(MouseKind::Down, column, row).await;
+        }
+    }
+
+    // as Alt+arrows or Alt+WASD, one key per percentage
+    async fn handle_mouse_drag(&mut self, column: u16, row: u16) {
+        let Some(size) = self.terminal.as_ref().and_then(|t| t.size().ok()) else {
+            return;
+        };
+        let Some(drag) = self.drag.as_mut() else {
+            return;
+        };
+
+        let width = size.width.max(1) as i32;
+        let height = size.height.saturating_sub(3).max(1) as i32;
+        let want = (
+            (column as i32 - drag.column as i32) * 100 / width,
+            (row as i32 - drag.row as i32) * 100 / height,
+        );
+
+        let (right, left, down, up) = if drag.resize {
+            (Key::AltD, Key::AltA, Key::AltS, Key::AltW)
+        } else {
+            (Key::AltRight, Key::AltLeft, Key::AltDown, Key::AltUp)
+        };
+
+        let mut keys = vec![];
+        for _ in drag.sent.0..want.0 {
+            keys.push(right.clone());
+        }
+        for _ in want.0..drag.sent.0 {
+            keys.push(left.clone());
+        }
+        for _ in drag.sent.1..want.1 {
+            keys.push(down.clone());
+        }
+        for _ in want.1..drag.sent.1 {
+            keys.push(up.clone());
+        }
+        drag.sent = want;
+
+        let panel = drag.panel.clone();
+        for key in keys {
+            self.cmd(format!("{} {panel} {} {key}", consts::P, Action::Key))
+                .await;
+        }
+    }
+
+    // p plugins mouse <mouse_kind> <column> <row>
+    async fn handle_action_mouse(&mut self, cmd_parts: &[String]) {
+        let (Some(Ok(kind)), Some(Ok(column)), Some(Ok(row))) = (
+            cmd_parts.get(3).map(|kind| kind.parse::<MouseKind>()),
+            cmd_parts.get(4).map(|column| column.parse::<u16>()),
+            cmd_parts.get(5).map(|row| row.parse::<u16>()),
+        ) else {
+            self.warn(common::MsgTemplate::InvalidParameters.format(
+                "<mouse_kind> <column> <row>",
+                Action::Mouse.as_ref(),
+                &cmd_parts.join(" "),
+            ))
+            .await;
+            return;
+        };
+
+        match kind {
+            MouseKind::Down => {
+                self.drag = None;
+                if let Some(idx) = self.panel_at(column, row) {
+                    self.handle_mouse_down(idx, column, row).await;
+                }
+            }
+            MouseKind::Drag => self.handle_mouse_drag(column, row).await,
+            MouseKind::Up => self.drag = None,
+            MouseKind::ScrollUp | MouseKind::ScrollDown => {
+                if let Some(idx) = self.panel_at(column, row) {
+                    let panel = self.panels[idx].clone();
+                    if let Some(plugin) = self.get_plugin_mut(&panel) {
+                        plugin.handle_mouse(kind, column, row).await;
+                    }
+                }
+            }
+        }
+
+        self.redraw();
+    }
+
+    // p plugins search [<pattern>], to the active panel
+    async fn handle_action_search(&mut self, cmd_parts: &[String]) {
+        let pattern = cmd_parts.get(3).cloned().unwrap_or_default();
+        // no panels in cli mode
+        let Some(panel) = self.panels.get(self.active_panel).cloned() else {
+            self.warn(format!("{} needs an active panel", Action::Search))
+                .await;
+            return;
+        };
+        if let Some(plugin) = self.get_plugin_mut(&panel) {
+            plugin.handle_search(&pattern).await;
+        }
+
+        self.redraw();
+    }
+
+    // p plugins theme <name|theme_file>
+    async fn handle_action_theme(&mut self, cmd_parts: &[String]) {
+        let Some(theme_name) = cmd_parts.get(3) else {
+            self.info(format!("Theme: {}", globals::get_theme_name()))
+                .await;
+            return;
+        };
+
+        match theme::load(theme_name) {
+            Ok(theme) => {
+                globals::set_theme(theme_name, theme);
+                self.info(format!("Theme: {theme_name}")).await;
+                self.redraw();
+            }
+            Err(e) => self.warn(e).await,
+        }
+    }
+
     async fn handle_action_popup(&mut self, cmd_parts: &[String]) {
         self.info(Action::Popup.to_string()).await;
 
@@ -502,9 +1046,14 @@ impl Plugins {
             Action::Show => self.handle_action_show().await,
             Action::Insert => self.handle_action_insert(cmd_parts).await,
             Action::InsertPanel => self.handle_action_insert_panel(cmd_parts).await,
-            Action::Redraw => self.handle_action_redraw().await,
+            Action::Redraw => self.handle_action_redraw(cmd_parts).await,
             Action::Key => self.handle_action_key(cmd_parts).await,
             Action::Popup => self.handle_action_popup(cmd_parts).await,
+            Action::Layout => self.handle_action_layout(cmd_parts).await,
+            Action::Workspace => self.handle_action_workspace(cmd_parts).await,
+            Action::Mouse => self.handle_action_mouse(cmd_parts).await,
+            Action::Search => self.handle_action_search(cmd_parts).await,
+            Action::Theme => self.handle_action_theme(cmd_parts).await,
             _ => {
                 self.warn(common::MsgTemplate::UnsupportedAction.format(action.as_ref(), "", ""))
                     .await
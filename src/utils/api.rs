@@ -1,6 +1,7 @@
 use std::fmt;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -26,21 +27,14 @@ pub async fn post_cmd(
     device_name: &str,
     module: &str,
     ip: &str,
+    port: u16,
     cmd: &CmdRequest,
 ) {
     msgs::info(msg_tx, module, &format!("-> `{device_name}`: `{cmd}`")).await;
 
-    let client = reqwest::Client::new();
-    let ret = client
-        .post(format!("http://{ip}:{}/{}", consts::WEB_PORT, Action::Cmd))
-        .json(cmd)
-        .send()
-        .await;
-
-    match ret {
-        Ok(response) => {
-            if response.status().is_success() {
-                let text = response.text().await.unwrap_or_default();
+    match request_cmd(ip, port, cmd, None).await {
+        Ok((status, text)) => {
+            if status.is_success() {
                 msgs::info(
                     msg_tx,
                     module,
@@ -51,10 +45,7 @@ pub async fn post_cmd(
                 msgs::warn(
                     msg_tx,
                     module,
-                    &format!(
-                        "Failed to post cmd to {ip} `{cmd}`: HTTP {}",
-                        response.status()
-                    ),
+                    &format!("Failed to post cmd to {ip} `{cmd}`: HTTP {status}"),
                 )
                 .await;
             }
@@ -70,6 +61,30 @@ pub async fn post_cmd(
     }
 }
 
+// returns the HTTP status and the response text, no timeout if `None`
+pub async fn request_cmd(
+    ip: &str,
+    port: u16,
+    cmd: &CmdRequest,
+    timeout: Option<u64>,
+) -> Result<(reqwest::StatusCode, String), reqwest::Error> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(Duration::from_secs(timeout));
+    }
+    let client = builder.build()?;
+    let response = client
+        .post(format!("http://{ip}:{port}/{}", Action::Cmd))
+        .json(cmd)
+        .send()
+        .await?;
+
+    let status = response.status();
+    let text = response.text().await.unwrap_or_default();
+
+    Ok((status, text))
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct UploadRequest {
     pub filename: String,
@@ -83,15 +98,17 @@ impl fmt::Display for UploadRequest {
     }
 }
 
-pub async fn post_upload(msg_tx: &Sender<Msg>, module: &str, ip: &str, upload: &UploadRequest) {
+pub async fn post_upload(
+    msg_tx: &Sender<Msg>,
+    module: &str,
+    ip: &str,
+    port: u16,
+    upload: &UploadRequest,
+) {
     msgs::info(msg_tx, module, &format!("POST /upload to {ip} `{upload}`")).await;
     let client = reqwest::Client::new();
     let ret = client
-        .post(format!(
-            "http://{ip}:{}/{}",
-            consts::WEB_PORT,
-            Action::Upload
-        ))
+        .post(format!("http://{ip}:{port}/{}", Action::Upload))
         .json(upload)
         .send()
         .await;
@@ -132,6 +149,7 @@ pub async fn upload_file(
     msg_tx: &Sender<Msg>,
     module: &str,
     ip: &str,
+    port: u16,
     source_path: &str,
     filename: &str,
 ) {
@@ -150,6 +168,7 @@ pub async fn upload_file(
         msg_tx,
         module,
         ip,
+        port,
         &UploadRequest {
             filename: filename.to_string(),
             content: encoded,
@@ -197,6 +216,7 @@ pub async fn post_download(
     msg_tx: &Sender<Msg>,
     module: &str,
     ip: &str,
+    port: u16,
     download: &DownloadRequest,
 ) -> anyhow::Result<DownloadResponse> {
     msgs::info(
@@ -208,7 +228,7 @@ pub async fn post_download(
 
     let client = reqwest::Client::new();
     let ret = client
-        .post(format!("http://{ip}:{}/download", consts::WEB_PORT,))
+        .post(format!("http://{ip}:{port}/download"))
         .json(download)
         .send()
         .await;
@@ -258,11 +278,18 @@ pub async fn post_download(
     }
 }
 
-pub async fn download_file(msg_tx: &Sender<Msg>, module: &str, ip: &str, remote_path: &str) {
+pub async fn download_file(
+    msg_tx: &Sender<Msg>,
+    module: &str,
+    ip: &str,
+    port: u16,
+    remote_path: &str,
+) {
     match post_download(
         msg_tx,
         module,
         ip,
+        port,
         &DownloadRequest {
             data: DownloadData {
                 filename: remote_path.to_string(),
@@ -367,11 +394,12 @@ pub async fn post_get_folder_meta(
     msg_tx: &Sender<Msg>,
     module: &str,
     ip: &str,
+    port: u16,
     folder_meta: &GetFolderMetaRequest,
 ) -> anyhow::Result<nas::FolderMeta> {
     let client = reqwest::Client::new();
     let ret = client
-        .post(format!("http://{ip}:{}/get/folder_meta", consts::WEB_PORT,))
+        .post(format!("http://{ip}:{port}/get/folder_meta"))
         .json(folder_meta)
         .send()
         .await;
@@ -420,7 +448,13 @@ impl fmt::Display for RemoveRequest {
     }
 }
 
-pub async fn post_remove(msg_tx: &Sender<Msg>, module: &str, ip: &str, remove_req: &RemoveRequest) {
+pub async fn post_remove(
+    msg_tx: &Sender<Msg>,
+    module: &str,
+    ip: &str,
+    port: u16,
+    remove_req: &RemoveRequest,
+) {
     msgs::info(
         msg_tx,
         module,
@@ -430,11 +464,7 @@ pub async fn post_remove(msg_tx: &Sender<Msg>, module: &str, ip: &str, remove_re
 
     let client = reqwest::Client::new();
     let ret = client
-        .post(format!(
-            "http://{ip}:{}/{}",
-            consts::WEB_PORT,
-            Action::Remove
-        ))
+        .post(format!("http://{ip}:{port}/{}", Action::Remove))
         .json(remove_req)
         .send()
         .await;
@@ -0,0 +1,129 @@
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::globals;
+
+// https://en.wikipedia.org/wiki/Sunrise_equation
+const J2000: f64 = 2_451_545.0;
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+const OBLIQUITY: f64 = 23.4397;
+// refraction and the solar disc
+const SUN_ALTITUDE: f64 = -0.833;
+
+// a new moon, 2000-01-06 18:14 UTC
+const NEW_MOON_JD: f64 = 2_451_550.26;
+const SYNODIC_MONTH: f64 = 29.530_588_853;
+
+fn julian_day(date: NaiveDate) -> f64 {
+    // noon UTC of the date
+    let days = (date - NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()).num_days();
+    J2000 + days as f64
+}
+
+fn julian_to_utc(jd: f64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(((jd - UNIX_EPOCH_JD) * 86_400.0).round() as i64, 0)
+}
+
+// (sunrise, sunset) of the date, None in polar day or night
+pub fn sun_times(
+    date: NaiveDate,
+    latitude: f32,
+    longitude: f32,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let (latitude, longitude) = (latitude as f64, longitude as f64);
+
+    let n = (julian_day(date) - J2000 + 0.0008).round();
+    let mean_solar_noon = n - longitude / 360.0;
+    let mean_anomaly = (357.5291 + 0.985_600_28 * mean_solar_noon).rem_euclid(360.0);
+    let m = mean_anomaly.to_radians();
+    let center = 1.9148 * m.sin() + 0.02 * (2.0 * m).sin() + 0.0003 * (3.0 * m).sin();
+    let ecliptic_longitude = (mean_anomaly + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit =
+        J2000 + mean_solar_noon + 0.0053 * m.sin() - 0.0069 * (2.0 * ecliptic_longitude).sin();
+
+    let declination = (ecliptic_longitude.sin() * OBLIQUITY.to_radians().sin()).asin();
+    let phi = latitude.to_radians();
+    let cos_hour_angle = (SUN_ALTITUDE.to_radians().sin() - phi.sin() * declination.sin())
+        / (phi.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+
+    Some((
+        julian_to_utc(transit - hour_angle / 360.0)?,
+        julian_to_utc(transit + hour_angle / 360.0)?,
+    ))
+}
+
+// 0.0 new moon, 0.5 full moon
+pub fn moon_phase(date: NaiveDate) -> f32 {
+    ((julian_day(date) - NEW_MOON_JD).rem_euclid(SYNODIC_MONTH) / SYNODIC_MONTH) as f32
+}
+
+const MOON_PHASES: [(&str, [&str; 4]); 8] = [
+    ("🌑", ["New moon", "新月", "Nieuwe maan", "新月"]),
+    (
+        "🌒",
+        ["Waxing crescent", "眉月", "Wassende sikkel", "三日月"],
+    ),
+    (
+        "🌓",
+        ["First quarter", "上弦月", "Eerste kwartier", "上弦の月"],
+    ),
+    (
+        "🌔",
+        ["Waxing gibbous", "盈凸月", "Wassende maan", "十三夜月"],
+    ),
+    ("🌕", ["Full moon", "滿月", "Volle maan", "満月"]),
+    (
+        "🌖",
+        ["Waning gibbous", "虧凸月", "Afnemende maan", "寝待月"],
+    ),
+    (
+        "🌗",
+        ["Last quarter", "下弦月", "Laatste kwartier", "下弦の月"],
+    ),
+    (
+        "🌘",
+        ["Waning crescent", "殘月", "Afnemende sikkel", "有明月"],
+    ),
+];
+
+fn moon_phase_idx(phase: f32) -> usize {
+    ((phase * 8.0).round() as usize) % MOON_PHASES.len()
+}
+
+pub fn moon_phase_emoji(phase: f32) -> &'static str {
+    MOON_PHASES[moon_phase_idx(phase)].0
+}
+
+pub fn moon_phase_str(phase: f32) -> &'static str {
+    MOON_PHASES[moon_phase_idx(phase)].1[globals::get_locale() as usize]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sun_times_taipei() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        let (sunrise, sunset) = sun_times(date, 25.033, 121.5654).unwrap();
+
+        // 06:38 and 17:15 in UTC+8, the sunrise on the previous day in UTC
+        let near = |time: DateTime<Utc>, expected: &str| {
+            let expected = expected.parse::<DateTime<Utc>>().unwrap();
+            (time - expected).num_minutes().abs() <= 2
+        };
+        assert!(near(sunrise, "2024-12-31T22:38:00Z"), "{sunrise}");
+        assert!(near(sunset, "2025-01-01T09:15:00Z"), "{sunset}");
+    }
+
+    #[test]
+    fn sun_times_polar_night() {
+        let date = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap();
+        assert!(sun_times(date, 69.65, 18.96).is_none());
+    }
+}
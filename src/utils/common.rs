@@ -5,7 +5,7 @@ use std::process::Command;
 use anyhow::Result;
 use regex::Regex;
 use sysinfo::Networks;
-use unicode_width::UnicodeWidthStr;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 use walkdir::WalkDir;
 
 use crate::consts;
@@ -30,6 +30,20 @@ pub fn temperature_str(temperature: Option<f32>) -> String {
     }
 }
 
+// pad_str() but cut to the width, e.g. for long localized text in a column
+pub fn fit_str(s: &str, total_width: usize) -> String {
+    let mut width = 0;
+    let fitted: String = s
+        .chars()
+        .take_while(|c| {
+            width += UnicodeWidthChar::width(*c).unwrap_or(0);
+            width <= total_width
+        })
+        .collect();
+
+    pad_str(&fitted, total_width)
+}
+
 pub fn pad_str(s: &str, total_width: usize) -> String {
     let display_width = UnicodeWidthStr::width(s);
     let padding = total_width.saturating_sub(display_width);
@@ -191,3 +205,69 @@ pub fn parse_mac(s: &str) -> Result<[u8; 6]> {
 
     Ok([parts[0], parts[1], parts[2], parts[3], parts[4], parts[5]])
 }
+
+// a name from outside (e.g. the network) used as a file name, never a path
+pub fn check_file_name(name: &str) -> Result<&str> {
+    if name.is_empty()
+        || name.contains("..")
+        || name
+            .chars()
+            .any(|c| matches!(c, '/' | '\\' | ':') || c.is_control())
+    {
+        return Err(anyhow::anyhow!("Invalid file name `{name}`"));
+    }
+
+    Ok(name)
+}
+
+// averaged down to at most `points` values, e.g. to fit a sparkline in a width
+pub fn downsample(values: &[f32], points: usize) -> Vec<f32> {
+    if points == 0 || values.len() <= points {
+        return values.to_vec();
+    }
+
+    let chunk = values.len().div_ceil(points);
+    values
+        .chunks(chunk)
+        .map(|chunk| chunk.iter().sum::<f32>() / chunk.len() as f32)
+        .collect()
+}
+
+pub fn sparkline(values: &[f32]) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    if values.is_empty() {
+        return consts::NA.to_string();
+    }
+
+    let min = values.iter().cloned().fold(f32::MAX, f32::min);
+    let max = values.iter().cloned().fold(f32::MIN, f32::max);
+    let range = max - min;
+
+    values
+        .iter()
+        .map(|value| {
+            if range <= f32::EPSILON {
+                BARS[BARS.len() / 2]
+            } else {
+                let idx = ((value - min) / range * (BARS.len() - 1) as f32).round() as usize;
+                BARS[idx.min(BARS.len() - 1)]
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        for name in ["dev1", "node-2_a", "台北", "a.b"] {
+            assert!(check_file_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "..", "../etc", "a/b", "a\\b", "c:", "a\nb"] {
+            assert!(check_file_name(name).is_err(), "{name}");
+        }
+    }
+}
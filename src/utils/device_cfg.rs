@@ -0,0 +1,31 @@
+use serde::Deserialize;
+
+use crate::consts;
+
+// a device of the inventory in cfg.toml
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct DeviceCfg {
+    pub name: String,
+    pub alias: Option<String>,
+    pub groups: Vec<String>,
+    pub location: Option<String>,
+    // validated when cfg.toml is loaded
+    pub mac: Option<String>,
+    pub expected_online: bool,
+    pub web_port: u16,
+}
+
+impl Default for DeviceCfg {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            alias: None,
+            groups: Vec::new(),
+            location: None,
+            mac: None,
+            expected_online: true,
+            web_port: consts::WEB_PORT,
+        }
+    }
+}
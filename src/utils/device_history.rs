@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DeviceRecord {
+    Onboard { ts: u64, online: bool },
+    Temperature { ts: u64, value: f32 },
+    AppUptime { ts: u64, value: u64 },
+}
+
+impl DeviceRecord {
+    pub fn ts(&self) -> u64 {
+        match self {
+            DeviceRecord::Onboard { ts, .. }
+            | DeviceRecord::Temperature { ts, .. }
+            | DeviceRecord::AppUptime { ts, .. } => *ts,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Outage {
+    pub start: u64,
+    pub end: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+pub struct DeviceHistory {
+    pub known: u64,
+    pub online: u64,
+    pub outages: Vec<Outage>,
+    pub temperature: Option<(f32, f32, f32)>,
+    pub restarts: usize,
+}
+
+impl DeviceHistory {
+    // records must be in time order, as they are appended
+    pub fn new(records: &[DeviceRecord], from: u64, to: u64) -> Self {
+        let mut history = Self::default();
+
+        // status at the window start is the last transition before it
+        let mut online = records
+            .iter()
+            .filter(|record| record.ts() < from)
+            .filter_map(|record| match record {
+                DeviceRecord::Onboard { online, .. } => Some(*online),
+                _ => None,
+            })
+            .next_back();
+        let mut since = from;
+        if online == Some(false) {
+            history.outages.push(Outage {
+                start: from,
+                end: None,
+            });
+        }
+
+        let mut temperatures = vec![];
+        let mut last_uptime = None;
+        for record in records
+            .iter()
+            .filter(|record| record.ts() >= from && record.ts() <= to)
+        {
+            match record {
+                DeviceRecord::Onboard { ts, online: now } => {
+                    history.add_span(online, since, *ts);
+                    if online != Some(false) && !now {
+                        history.outages.push(Outage {
+                            start: *ts,
+                            end: None,
+                        });
+                    }
+                    if online == Some(false)
+                        && *now
+                        && let Some(outage) = history.outages.last_mut()
+                    {
+                        outage.end = Some(*ts);
+                    }
+                    online = Some(*now);
+                    since = *ts;
+                }
+                DeviceRecord::Temperature { value, .. } => temperatures.push(*value),
+                DeviceRecord::AppUptime { value, .. } => {
+                    if last_uptime.is_some_and(|last| *value < last) {
+                        history.restarts += 1;
+                    }
+                    last_uptime = Some(*value);
+                }
+            }
+        }
+        history.add_span(online, since, to);
+
+        if !temperatures.is_empty() {
+            let min = temperatures.iter().cloned().fold(f32::MAX, f32::min);
+            let max = temperatures.iter().cloned().fold(f32::MIN, f32::max);
+            let avg = temperatures.iter().sum::<f32>() / temperatures.len() as f32;
+            history.temperature = Some((min, avg, max));
+        }
+
+        history
+    }
+
+    fn add_span(&mut self, online: Option<bool>, start: u64, end: u64) {
+        // unknown status (before the first record) is not counted
+        if let Some(online) = online {
+            self.known += end.saturating_sub(start);
+            if online {
+                self.online += end.saturating_sub(start);
+            }
+        }
+    }
+
+    pub fn availability(&self) -> Option<f64> {
+        if self.known == 0 {
+            return None;
+        }
+
+        Some(self.online as f64 * 100.0 / self.known as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn onboard(ts: u64, online: bool) -> DeviceRecord {
+        DeviceRecord::Onboard { ts, online }
+    }
+
+    fn outages(history: &DeviceHistory) -> Vec<(u64, Option<u64>)> {
+        history
+            .outages
+            .iter()
+            .map(|outage| (outage.start, outage.end))
+            .collect()
+    }
+
+    #[test]
+    fn flapping() {
+        let records = [
+            onboard(50, true),
+            onboard(110, false),
+            onboard(120, true),
+            DeviceRecord::AppUptime {
+                ts: 125,
+                value: 500,
+            },
+            onboard(130, false),
+            onboard(150, true),
+            // restarted
+            DeviceRecord::AppUptime { ts: 160, value: 10 },
+            DeviceRecord::Temperature {
+                ts: 170,
+                value: 40.0,
+            },
+            DeviceRecord::Temperature {
+                ts: 180,
+                value: 50.0,
+            },
+            onboard(190, false),
+            // after the window
+            onboard(250, true),
+        ];
+
+        let history = DeviceHistory::new(&records, 100, 200);
+        assert_eq!(history.known, 100);
+        assert_eq!(history.online, 60);
+        assert_eq!(history.availability(), Some(60.0));
+        assert_eq!(
+            outages(&history),
+            [(110, Some(120)), (130, Some(150)), (190, None)]
+        );
+        assert_eq!(history.restarts, 1);
+        assert_eq!(history.temperature, Some((40.0, 45.0, 50.0)));
+    }
+
+    #[test]
+    fn offline_at_start() {
+        let records = [onboard(50, false), onboard(150, true)];
+
+        let history = DeviceHistory::new(&records, 100, 200);
+        assert_eq!(history.availability(), Some(50.0));
+        assert_eq!(outages(&history), [(100, Some(150))]);
+    }
+
+    #[test]
+    fn unknown() {
+        let history = DeviceHistory::new(&[], 100, 200);
+        assert_eq!(history.availability(), None);
+
+        // not counted before the first record
+        let history = DeviceHistory::new(&[onboard(150, true)], 100, 200);
+        assert_eq!(history.known, 50);
+        assert_eq!(history.availability(), Some(100.0));
+    }
+}
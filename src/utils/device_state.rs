@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+use crate::consts;
+
+// bump when a field changes meaning; new optional fields do not need a bump
+pub const SCHEMA: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct DeviceState {
+    pub schema: u32,
+    pub version: String,
+    pub tailscale_ip: Option<String>,
+    pub temperature: Option<f32>,
+    pub app_uptime: u64,
+    pub cpu_load: f32,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub disk_used: u64,
+    pub disk_total: u64,
+    pub load_average: [f64; 3],
+    pub os: String,
+    pub arch: String,
+    // release platform, e.g. linux-aarch64, empty before rollouts
+    pub platform: String,
+    pub ips: Vec<String>,
+}
+
+impl DeviceState {
+    pub fn cpu_load_str(&self) -> String {
+        format!("{:.0}%", self.cpu_load)
+    }
+
+    pub fn memory_str(&self) -> String {
+        usage_str(self.memory_used, self.memory_total)
+    }
+
+    pub fn disk_str(&self) -> String {
+        usage_str(self.disk_used, self.disk_total)
+    }
+
+    pub fn load_average_str(&self) -> String {
+        format!(
+            "{:.2} {:.2} {:.2}",
+            self.load_average[0], self.load_average[1], self.load_average[2]
+        )
+    }
+}
+
+fn usage_str(used: u64, total: u64) -> String {
+    if total == 0 {
+        return consts::NA.to_string();
+    }
+
+    format!("{:.0}%", used as f64 * 100.0 / total as f64)
+}
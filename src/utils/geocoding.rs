@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+pub const OPEN_METEO: &str = "open-meteo";
+pub const FIXTURE: &str = "fixture";
+pub const FIXTURE_PATH: &str = "./test/geocoding.json";
+pub const DEFAULT_GEOCODER: &str = OPEN_METEO;
+pub const GEOCODERS: [&str; 2] = [OPEN_METEO, FIXTURE];
+
+const TIMEOUT: u64 = 5;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Place {
+    pub name: String,
+    pub latitude: f32,
+    pub longitude: f32,
+    #[serde(default)]
+    pub country: Option<String>,
+}
+
+#[async_trait]
+pub trait Geocoder {
+    fn name(&self) -> &str;
+    async fn geocode(&self, place: &str) -> Result<Place, String>;
+}
+
+// <geocoder>, or fixture:<path> as the weather providers
+pub fn geocoder(spec: &str) -> Option<Box<dyn Geocoder + Send + Sync>> {
+    let (name, arg) = match spec.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (spec, None),
+    };
+
+    match name {
+        OPEN_METEO => Some(Box::new(OpenMeteo)),
+        FIXTURE => Some(Box::new(Fixture {
+            path: arg.unwrap_or(FIXTURE_PATH).to_string(),
+        })),
+        _ => None,
+    }
+}
+
+//
+// Open-Meteo
+//
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoResponse {
+    #[serde(default)]
+    results: Vec<Place>,
+}
+
+pub struct OpenMeteo;
+
+#[async_trait]
+impl Geocoder for OpenMeteo {
+    fn name(&self) -> &str {
+        OPEN_METEO
+    }
+
+    async fn geocode(&self, place: &str) -> Result<Place, String> {
+        let client = reqwest::Client::new();
+
+        let response = client
+            .get("https://geocoding-api.open-meteo.com/v1/search")
+            .query(&[("name", place), ("count", "1")])
+            .timeout(tokio::time::Duration::from_secs(TIMEOUT))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to geocode: {e}"))?;
+
+        let response: OpenMeteoResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse geocoding data: {e}"))?;
+
+        response
+            .results
+            .into_iter()
+            .next()
+            .ok_or(format!("`{place}` not found"))
+    }
+}
+
+//
+// Fixture, a JSON object of place name to place for offline testing
+//
+
+pub struct Fixture {
+    pub path: String,
+}
+
+#[async_trait]
+impl Geocoder for Fixture {
+    fn name(&self) -> &str {
+        FIXTURE
+    }
+
+    async fn geocode(&self, place: &str) -> Result<Place, String> {
+        let body = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| format!("Failed to read `{}`: {e}", self.path))?;
+
+        let mut places: HashMap<String, Place> = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse geocoding data: {e}"))?;
+
+        places.remove(place).ok_or(format!("`{place}` not found"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fixture_geocode() {
+        let geocoder = geocoder(FIXTURE).unwrap();
+
+        let place = geocoder.geocode("東京").await.unwrap();
+        assert_eq!(place.name, "Tokyo");
+        assert_eq!(place.latitude, 35.6895);
+        assert_eq!(place.longitude, 139.6917);
+        assert_eq!(place.country.as_deref(), Some("Japan"));
+
+        assert!(geocoder.geocode("Atlantis").await.is_err());
+    }
+}
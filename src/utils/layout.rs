@@ -0,0 +1,244 @@
+use std::fmt;
+use std::str::FromStr;
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    prelude::Rect,
+};
+
+const ROWS: &str = "rows";
+const COLS: &str = "cols";
+
+// e.g. `rows(60%: cols(70%: log, 30%: mqtt), 40%: infos)`
+//
+// constraint before `:`, fills the rest if omitted
+//   60%  percentage
+//   20   length in cells
+//   >10  at least
+//   <10  at most
+#[derive(Debug, Clone)]
+pub enum LayoutNode {
+    Panel(String),
+    Split {
+        direction: Direction,
+        children: Vec<(Constraint, LayoutNode)>,
+    },
+}
+
+impl LayoutNode {
+    // panel name and its area, in the order of the spec
+    pub fn areas(&self, area: Rect) -> Vec<(String, Rect)> {
+        match self {
+            LayoutNode::Panel(name) => vec![(name.clone(), area)],
+            LayoutNode::Split {
+                direction,
+                children,
+            } => {
+                let rects = Layout::default()
+                    .direction(*direction)
+                    .constraints(children.iter().map(|(constraint, _)| *constraint))
+                    .split(area);
+
+                children
+                    .iter()
+                    .zip(rects.iter())
+                    .flat_map(|((_, child), rect)| child.areas(*rect))
+                    .collect()
+            }
+        }
+    }
+
+    pub fn panels(&self) -> Vec<String> {
+        self.areas(Rect::default())
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect()
+    }
+}
+
+fn parse_constraint(s: &str) -> Result<Constraint, String> {
+    let invalid = || format!("Invalid constraint `{s}`");
+
+    if let Some(value) = s.strip_suffix('%') {
+        let value = value.parse::<u16>().map_err(|_| invalid())?;
+        if value > 100 {
+            return Err(invalid());
+        }
+        Ok(Constraint::Percentage(value))
+    } else if let Some(value) = s.strip_prefix('>') {
+        Ok(Constraint::Min(
+            value.parse::<u16>().map_err(|_| invalid())?,
+        ))
+    } else if let Some(value) = s.strip_prefix('<') {
+        Ok(Constraint::Max(
+            value.parse::<u16>().map_err(|_| invalid())?,
+        ))
+    } else {
+        Ok(Constraint::Length(s.parse::<u16>().map_err(|_| invalid())?))
+    }
+}
+
+fn constraint_str(constraint: &Constraint) -> Option<String> {
+    match constraint {
+        Constraint::Percentage(value) => Some(format!("{value}%")),
+        Constraint::Min(value) => Some(format!(">{value}")),
+        Constraint::Max(value) => Some(format!("<{value}")),
+        Constraint::Length(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+// split at top level commas only
+fn split_items(s: &str) -> Result<Vec<&str>, String> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err(format!("Unbalanced `)` in `{s}`")),
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(s[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => (),
+        }
+    }
+    if depth != 0 {
+        return Err(format!("Unbalanced `(` in `{s}`"));
+    }
+    items.push(s[start..].trim());
+
+    Ok(items)
+}
+
+impl FromStr for LayoutNode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        let Some((name, rest)) = s.split_once('(') else {
+            if s.is_empty() || !s.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(format!("Invalid panel name `{s}`"));
+            }
+            return Ok(LayoutNode::Panel(s.to_string()));
+        };
+
+        let direction = match name.trim() {
+            ROWS => Direction::Vertical,
+            COLS => Direction::Horizontal,
+            name => return Err(format!("Unknown split `{name}`, `{ROWS}` or `{COLS}`")),
+        };
+        let Some(inner) = rest.strip_suffix(')') else {
+            return Err(format!("Missing `)` in `{s}`"));
+        };
+
+        let mut children = vec![];
+        for item in split_items(inner)? {
+            // a `:` inside a nested split is not ours
+            let (constraint, node) = match item.split_once(':') {
+                Some((constraint, node)) if !constraint.contains('(') => {
+                    (parse_constraint(constraint.trim())?, node)
+                }
+                _ => (Constraint::Fill(1), item),
+            };
+            children.push((constraint, node.parse::<LayoutNode>()?));
+        }
+
+        let mut panels = vec![];
+        for (_, child) in &children {
+            panels.extend(child.panels());
+        }
+        for (idx, panel) in panels.iter().enumerate() {
+            if panels[..idx].contains(panel) {
+                return Err(format!("Panel `{panel}` appears more than once"));
+            }
+        }
+
+        Ok(LayoutNode::Split {
+            direction,
+            children,
+        })
+    }
+}
+
+impl fmt::Display for LayoutNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutNode::Panel(name) => write!(f, "{name}"),
+            LayoutNode::Split {
+                direction,
+                children,
+            } => {
+                let name = match direction {
+                    Direction::Vertical => ROWS,
+                    Direction::Horizontal => COLS,
+                };
+                let children = children
+                    .iter()
+                    .map(|(constraint, child)| match constraint_str(constraint) {
+                        Some(constraint) => format!("{constraint}: {child}"),
+                        None => child.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "{name}({children})")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // as in script.toml
+    const MAIN: &str = "rows(60%: cols(70%: log, 30%: mqtt), 40%: infos)";
+
+    #[test]
+    fn parse() {
+        let layout = MAIN.parse::<LayoutNode>().unwrap();
+        assert_eq!(layout.to_string(), MAIN);
+        assert_eq!(layout.panels(), ["log", "mqtt", "infos"]);
+
+        let layout = "cols(>10: log,mqtt, <20: infos, 5: devices)"
+            .parse::<LayoutNode>()
+            .unwrap();
+        assert_eq!(
+            layout.to_string(),
+            "cols(>10: log, mqtt, <20: infos, 5: devices)"
+        );
+    }
+
+    #[test]
+    fn areas() {
+        let layout = MAIN.parse::<LayoutNode>().unwrap();
+        let areas = layout.areas(Rect::new(0, 0, 100, 50));
+        assert_eq!(
+            areas,
+            [
+                ("log".to_string(), Rect::new(0, 0, 70, 30)),
+                ("mqtt".to_string(), Rect::new(70, 0, 30, 30)),
+                ("infos".to_string(), Rect::new(0, 30, 100, 20)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for spec in [
+            "",
+            "log mqtt",
+            "grid(log)",
+            "rows(log, mqtt",
+            "rows(log), mqtt)",
+            "rows(101%: log)",
+            "rows(x: log)",
+            "rows(log, cols(mqtt, log))",
+        ] {
+            assert!(spec.parse::<LayoutNode>().is_err(), "{spec}");
+        }
+    }
+}
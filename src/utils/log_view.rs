@@ -0,0 +1,315 @@
+use log::Level;
+use ratatui::{
+    style::Modifier,
+    text::{Line, Span},
+};
+use strum_macros::{AsRefStr, Display, EnumString};
+
+use crate::messages::Key;
+use crate::utils::theme::Theme;
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Copy, Debug)]
+pub enum LevelFilter {
+    #[strum(serialize = "all")]
+    All,
+    // warnings and errors
+    #[strum(serialize = "warn")]
+    Warn,
+    #[strum(serialize = "error")]
+    Error,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ViewLine<'a> {
+    pub level: Option<Level>,
+    pub text: &'a str,
+}
+
+// scrollback, level filter and search over the lines of a log-style panel
+#[derive(Debug)]
+pub struct LogView {
+    // the latest lines, else from `top`
+    follow: bool,
+    top: usize,
+    // as last drawn
+    offset: usize,
+    height: usize,
+    total: usize,
+    pub level: LevelFilter,
+    search: Option<String>,
+    // line of the current match
+    matched: Option<usize>,
+}
+
+impl LogView {
+    pub fn new() -> Self {
+        Self {
+            follow: true,
+            top: 0,
+            offset: 0,
+            height: 0,
+            total: 0,
+            level: LevelFilter::All,
+            search: None,
+            matched: None,
+        }
+    }
+
+    pub fn shows(&self, level: Option<Level>) -> bool {
+        match self.level {
+            LevelFilter::All => true,
+            LevelFilter::Warn => level.is_some_and(|level| level <= Level::Warn),
+            LevelFilter::Error => level == Some(Level::Error),
+        }
+    }
+
+    pub fn set_follow(&mut self, follow: bool) {
+        self.follow = follow;
+        self.top = self.offset;
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.follow = false;
+        self.top = self.offset.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.top = self.offset + lines;
+        // back to the latest
+        if self.top + self.height >= self.total {
+            self.follow = true;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.follow = true;
+        self.top = 0;
+        self.matched = None;
+    }
+
+    // shown lines dropped from the front of the buffer
+    pub fn trimmed(&mut self, lines: usize) {
+        self.top = self.top.saturating_sub(lines);
+        self.matched = self.matched.and_then(|matched| matched.checked_sub(lines));
+    }
+
+    // PageUp/PageDown/Home/End and Ctrl+F, false if not for the view
+    pub fn handle_key(&mut self, key: &Key) -> bool {
+        let page = self.height.saturating_sub(1).max(1);
+        match key {
+            Key::PageUp => self.scroll_up(page),
+            Key::PageDown => self.scroll_down(page),
+            Key::Home => {
+                self.follow = false;
+                self.top = 0;
+            }
+            Key::End => self.follow = true,
+            Key::ControlF => self.set_follow(!self.follow),
+            _ => return false,
+        }
+
+        true
+    }
+
+    // incremental, from the current match or the bottom of the view upwards
+    pub fn search(&mut self, pattern: &str, lines: &[ViewLine]) {
+        if pattern.is_empty() {
+            self.search = None;
+            self.matched = None;
+            return;
+        }
+
+        self.search = Some(pattern.to_string());
+        let from = self.matched.unwrap_or(
+            (self.offset + self.height)
+                .min(lines.len())
+                .saturating_sub(1),
+        );
+        self.find(lines, from);
+    }
+
+    // the previous (older) match, wrapped around
+    pub fn next_match(&mut self, lines: &[ViewLine]) {
+        match self.matched {
+            Some(0) | None => self.find(lines, lines.len().saturating_sub(1)),
+            Some(matched) => self.find(lines, matched - 1),
+        }
+    }
+
+    fn find(&mut self, lines: &[ViewLine], from: usize) {
+        let Some(pattern) = &self.search else {
+            return;
+        };
+
+        let from = from.min(lines.len().saturating_sub(1));
+        let matched = (0..=from)
+            .rev()
+            .chain((from + 1..lines.len()).rev())
+            .find(|idx| {
+                lines
+                    .get(*idx)
+                    .is_some_and(|line| line.text.contains(pattern))
+            });
+
+        self.matched = matched;
+        if let Some(matched) = matched
+            && (matched < self.offset || matched >= self.offset + self.height)
+        {
+            self.follow = false;
+            self.top = matched.saturating_sub(self.height / 2);
+        }
+    }
+
+    pub fn title(&self, module: &str) -> String {
+        let mut title = vec![module.to_string()];
+        if !self.follow {
+            title.push(format!(
+                "{}-{}/{}",
+                (self.offset + 1).min(self.total),
+                (self.offset + self.height).min(self.total),
+                self.total
+            ));
+        }
+        if self.level != LevelFilter::All {
+            title.push(format!("level {}", self.level));
+        }
+        if let Some(pattern) = &self.search {
+            title.push(format!(
+                "/{pattern}{}",
+                if self.matched.is_none() {
+                    " (not found)"
+                } else {
+                    ""
+                }
+            ));
+        }
+
+        title.join(" - ")
+    }
+
+    // the lines in view, colored and highlighted
+    pub fn render(
+        &mut self,
+        lines: &[ViewLine],
+        height: usize,
+        theme: &Theme,
+    ) -> Vec<Line<'static>> {
+        self.height = height;
+        self.total = lines.len();
+        let max_offset = lines.len().saturating_sub(height);
+        self.offset = if self.follow {
+            max_offset
+        } else {
+            self.top.min(max_offset)
+        };
+        self.top = self.offset;
+
+        lines
+            .iter()
+            .enumerate()
+            .skip(self.offset)
+            .take(height)
+            .map(|(idx, line)| self.render_line(line, self.matched == Some(idx), theme))
+            .collect()
+    }
+
+    fn render_line(&self, line: &ViewLine, matched: bool, theme: &Theme) -> Line<'static> {
+        let style = theme.level(line.level);
+        let style = if matched {
+            style.add_modifier(Modifier::REVERSED)
+        } else {
+            style
+        };
+
+        let Some(pattern) = self.search.as_deref() else {
+            return Line::from(Span::styled(line.text.to_string(), style));
+        };
+
+        let mut spans = vec![];
+        let mut rest = line.text;
+        while let Some(pos) = rest.find(pattern) {
+            spans.push(Span::styled(rest[..pos].to_string(), style));
+            spans.push(Span::styled(pattern.to_string(), theme.search));
+            rest = &rest[pos + pattern.len()..];
+        }
+        spans.push(Span::styled(rest.to_string(), style));
+
+        Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LINES: [(Option<Level>, &str); 4] = [
+        (Some(Level::Info), "[I] started"),
+        (Some(Level::Warn), "[W] disk almost full"),
+        (None, "  [E] quoted, not an error"),
+        (Some(Level::Error), "[E] disk full"),
+    ];
+
+    fn lines() -> Vec<ViewLine<'static>> {
+        LINES
+            .iter()
+            .map(|(level, text)| ViewLine {
+                level: *level,
+                text,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn level_filter() {
+        let mut view = LogView::new();
+        let shown = |view: &LogView| {
+            lines()
+                .into_iter()
+                .filter(|line| view.shows(line.level))
+                .map(|line| line.text)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(shown(&view).len(), 4);
+        view.level = LevelFilter::Warn;
+        assert_eq!(shown(&view), ["[W] disk almost full", "[E] disk full"]);
+        view.level = LevelFilter::Error;
+        assert_eq!(shown(&view), ["[E] disk full"]);
+    }
+
+    #[test]
+    fn search() {
+        let lines = lines();
+        let mut view = LogView::new();
+        view.render(&lines, 2, &Theme::default());
+
+        // upwards from the bottom, then wrapped around
+        view.search("disk", &lines);
+        assert_eq!(view.matched, Some(3));
+        view.next_match(&lines);
+        assert_eq!(view.matched, Some(1));
+        view.next_match(&lines);
+        assert_eq!(view.matched, Some(3));
+
+        view.search("nothing", &lines);
+        assert_eq!(view.matched, None);
+        assert!(view.title("log").ends_with("(not found)"));
+    }
+
+    #[test]
+    fn scroll() {
+        let lines = lines();
+        let mut view = LogView::new();
+        assert_eq!(view.render(&lines, 2, &Theme::default()).len(), 2);
+        assert_eq!(view.offset, 2);
+
+        view.scroll_up(1);
+        view.render(&lines, 2, &Theme::default());
+        assert_eq!(view.offset, 1);
+        assert_eq!(view.title("log"), "log - 2-3/4");
+
+        // back to following at the bottom
+        view.scroll_down(1);
+        assert!(view.follow);
+    }
+}
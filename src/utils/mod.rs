@@ -1,8 +1,21 @@
 pub mod api;
+pub mod astro;
 pub mod common;
+pub mod device_cfg;
+pub mod device_history;
+pub mod device_state;
 pub mod ffmpeg;
+pub mod geocoding;
+pub mod layout;
+pub mod log_view;
 pub mod nas;
 pub mod panel;
+pub mod prompt;
+pub mod release;
+pub mod store;
+pub mod theme;
 pub mod time;
 pub mod weather;
+pub mod weather_history;
+pub mod weather_providers;
 pub mod yt_dlp;
@@ -1,6 +1,9 @@
 use ratatui::{Frame, prelude::Rect};
 use strum_macros::{AsRefStr, Display, EnumString};
 
+// per mouse wheel step
+pub const SCROLL_LINES: usize = 3;
+
 #[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Debug)]
 pub enum PanelType {
     #[strum(serialize = "normal")]
@@ -16,6 +19,10 @@ pub struct PanelInfo {
     pub y: u16,
     pub w: u16,
     pub h: u16,
+    // assigned by the active layout, over x, y, w and h
+    pub rect: Option<Rect>,
+    // as last drawn, for the mouse
+    pub area: Rect,
 }
 
 impl PanelInfo {
@@ -26,6 +33,8 @@ impl PanelInfo {
             y: 0,
             w: 0,
             h: 0,
+            rect: None,
+            area: Rect::default(),
         }
     }
 }
@@ -43,20 +52,31 @@ pub fn panel_rect(x: u16, y: u16, width: u16, height: u16, area: Rect) -> Rect {
     }
 }
 
-pub fn caculate_position(
-    frame: &mut Frame,
-    x: u16,
-    y: u16,
-    w: u16,
-    h: u16,
-) -> (u16, u16, u16, u16) {
+// the area above the command panel
+pub fn layout_area(frame: &Frame) -> Rect {
+    let area = frame.area();
+    Rect {
+        height: area.height.saturating_sub(3),
+        ..area
+    }
+}
+
+pub fn caculate_position(frame: &mut Frame, panel_info: &PanelInfo) -> (u16, u16, u16, u16) {
+    if let Some(rect) = panel_info.rect {
+        return (rect.x, rect.y, rect.width, rect.height);
+    }
+
     let width = frame.area().width;
-    let height = frame.area().height - 3;
+    let height = layout_area(frame).height;
+    let (x, y, w, h) = (panel_info.x, panel_info.y, panel_info.w, panel_info.h);
 
+    // kept in the layout area, e.g. for x + w over 100 in script.toml
+    let x = ((width as f32 * x as f32 / 100.0).round() as u16).min(width);
+    let y = ((height as f32 * y as f32 / 100.0).round() as u16).min(height);
     (
-        (width as f32 * x as f32 / 100.0).round() as u16,
-        (height as f32 * y as f32 / 100.0).round() as u16,
-        (width as f32 * w as f32 / 100.0).round() as u16,
-        (height as f32 * h as f32 / 100.0).round() as u16,
+        x,
+        y,
+        ((width as f32 * w as f32 / 100.0).round() as u16).min(width - x),
+        ((height as f32 * h as f32 / 100.0).round() as u16).min(height - y),
     )
 }
@@ -0,0 +1,370 @@
+use unicode_width::UnicodeWidthStr;
+
+pub const HISTORY_CAPACITY: usize = 1000;
+
+// Ctrl-R, the latest history entry containing the pattern
+#[derive(Debug)]
+struct ReverseSearch {
+    pattern: String,
+    matched: Option<usize>,
+    // the input to restore on cancel
+    saved: String,
+}
+
+// the input line of the GUI, the cursor counts chars, not bytes
+#[derive(Debug)]
+pub struct Prompt {
+    input: String,
+    cursor: usize,
+    history: Vec<String>,
+    history_index: usize,
+    reverse_search: Option<ReverseSearch>,
+}
+
+impl Prompt {
+    pub fn new(mut history: Vec<String>) -> Self {
+        if history.len() > HISTORY_CAPACITY {
+            history.drain(..history.len() - HISTORY_CAPACITY);
+        }
+        let history_index = history.len();
+
+        Self {
+            input: String::new(),
+            cursor: 0,
+            history,
+            history_index,
+            reverse_search: None,
+        }
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    fn byte_idx(&self, cursor: usize) -> usize {
+        self.input
+            .char_indices()
+            .nth(cursor)
+            .map_or(self.input.len(), |(idx, _)| idx)
+    }
+
+    fn len(&self) -> usize {
+        self.input.chars().count()
+    }
+
+    pub fn set(&mut self, input: &str) {
+        self.input = input.to_string();
+        self.cursor = self.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set("");
+    }
+
+    pub fn insert(&mut self, c: char) {
+        if let Some(reverse_search) = &mut self.reverse_search {
+            reverse_search.pattern.push(c);
+            reverse_search.matched = None;
+            self.find_reverse(self.history.len());
+            return;
+        }
+
+        let idx = self.byte_idx(self.cursor);
+        self.input.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(reverse_search) = &mut self.reverse_search {
+            reverse_search.pattern.pop();
+            reverse_search.matched = None;
+            self.find_reverse(self.history.len());
+            return;
+        }
+
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            let idx = self.byte_idx(self.cursor);
+            self.input.remove(idx);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.len() {
+            let idx = self.byte_idx(self.cursor);
+            self.input.remove(idx);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.len();
+    }
+
+    // Ctrl-W, the word before the cursor and the spaces after it
+    pub fn delete_word(&mut self) {
+        let chars: Vec<char> = self.input.chars().collect();
+        let mut start = self.cursor;
+        while start > 0 && chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !chars[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        let (from, to) = (self.byte_idx(start), self.byte_idx(self.cursor));
+        self.input.replace_range(from..to, "");
+        self.cursor = start;
+    }
+
+    // Ctrl-U
+    pub fn delete_to_start(&mut self) {
+        let idx = self.byte_idx(self.cursor);
+        self.input.replace_range(..idx, "");
+        self.cursor = 0;
+    }
+
+    // the prompt and the column of the cursor in it
+    pub fn display(&self, prompt: &str) -> (String, usize) {
+        match &self.reverse_search {
+            Some(reverse_search) => {
+                let head = format!("(reverse-i-search)`{}'", reverse_search.pattern);
+                let matched = reverse_search
+                    .matched
+                    .map_or("", |idx| self.history[idx].as_str());
+                (format!("{head}: {matched}"), head.width() - 1)
+            }
+            None => {
+                let before = &self.input[..self.byte_idx(self.cursor)];
+                (
+                    format!("{prompt}{}", self.input),
+                    prompt.width() + before.width(),
+                )
+            }
+        }
+    }
+
+    //
+    // history
+    //
+
+    pub fn history_up(&mut self) {
+        if self.history_index > 0 {
+            self.history_index -= 1;
+            self.set(&self.history[self.history_index].clone());
+        }
+    }
+
+    pub fn history_down(&mut self) {
+        if self.history_index < self.history.len() {
+            self.history_index += 1;
+            match self.history.get(self.history_index) {
+                Some(input) => self.set(&input.clone()),
+                None => self.clear(),
+            }
+        }
+    }
+
+    // false if as the same as the last one
+    pub fn push_history(&mut self, input: &str) -> bool {
+        let added = self.history.last().is_none_or(|last| last != input);
+        if added {
+            self.history.push(input.to_string());
+            if self.history.len() > HISTORY_CAPACITY {
+                self.history.remove(0);
+            }
+        }
+        self.history_index = self.history.len();
+
+        added
+    }
+
+    //
+    // Ctrl-R
+    //
+
+    pub fn is_reverse_search(&self) -> bool {
+        self.reverse_search.is_some()
+    }
+
+    // again for an older match
+    pub fn reverse_search(&mut self) {
+        match &self.reverse_search {
+            Some(reverse_search) => {
+                let from = reverse_search.matched.unwrap_or(self.history.len());
+                self.find_reverse(from);
+            }
+            None => {
+                self.reverse_search = Some(ReverseSearch {
+                    pattern: String::new(),
+                    matched: None,
+                    saved: self.input.clone(),
+                });
+            }
+        }
+    }
+
+    // before `from`, the current match is kept if none
+    fn find_reverse(&mut self, from: usize) {
+        let Some(reverse_search) = &mut self.reverse_search else {
+            return;
+        };
+        if reverse_search.pattern.is_empty() {
+            reverse_search.matched = None;
+            return;
+        }
+
+        if let Some(idx) = self.history[..from.min(self.history.len())]
+            .iter()
+            .rposition(|input| input.contains(&reverse_search.pattern))
+        {
+            reverse_search.matched = Some(idx);
+        }
+    }
+
+    // the match to the input
+    pub fn reverse_search_accept(&mut self) {
+        if let Some(reverse_search) = self.reverse_search.take() {
+            match reverse_search.matched {
+                Some(idx) => self.set(&self.history[idx].clone()),
+                None => self.set(&reverse_search.saved),
+            }
+        }
+    }
+
+    pub fn reverse_search_cancel(&mut self) {
+        if let Some(reverse_search) = self.reverse_search.take() {
+            self.set(&reverse_search.saved);
+        }
+    }
+
+    //
+    // Tab
+    //
+
+    // the word before the cursor by the candidates for the words before it,
+    // the matches if ambiguous
+    pub fn complete(&mut self, candidates: impl Fn(&[&str]) -> Vec<String>) -> Vec<String> {
+        let before = self.input[..self.byte_idx(self.cursor)].to_string();
+        let start = before.rfind(char::is_whitespace).map_or(0, |idx| {
+            idx + before[idx..].chars().next().unwrap().len_utf8()
+        });
+        let words: Vec<&str> = before[..start].split_whitespace().collect();
+        let prefix = &before[start..];
+
+        let matches: Vec<String> = candidates(&words)
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(prefix))
+            .collect();
+
+        let completion = match matches.as_slice() {
+            [] => return vec![],
+            [matched] => format!("{matched} "),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.as_str(), |common, matched| {
+                    let len = common
+                        .char_indices()
+                        .zip(matched.chars())
+                        .take_while(|((_, a), b)| a == b)
+                        .last()
+                        .map_or(0, |((idx, a), _)| idx + a.len_utf8());
+                    &common[..len]
+                });
+                if common.len() == prefix.len() {
+                    return matches;
+                }
+                common.to_string()
+            }
+        };
+
+        for c in completion[prefix.len()..].chars() {
+            self.insert(c);
+        }
+
+        vec![]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_input(input: &str) -> Prompt {
+        let mut prompt = Prompt::new(vec![]);
+        prompt.set(input);
+        prompt
+    }
+
+    #[test]
+    fn delete_word() {
+        let mut prompt = with_input("p weather add 台北  ");
+        prompt.delete_word();
+        assert_eq!(prompt.input(), "p weather add ");
+        prompt.delete_word();
+        assert_eq!(prompt.input(), "p weather ");
+
+        // only before the cursor
+        prompt.left();
+        prompt.left();
+        prompt.delete_word();
+        assert_eq!(prompt.input(), "p r ");
+    }
+
+    #[test]
+    fn complete() {
+        let candidates = |words: &[&str]| match words {
+            ["p"] => vec!["devices".to_string(), "weather".to_string()],
+            ["p", "devices"] => vec!["update".to_string(), "upgrade".to_string()],
+            _ => vec![],
+        };
+
+        // unique
+        let mut prompt = with_input("p we");
+        assert!(prompt.complete(candidates).is_empty());
+        assert_eq!(prompt.input(), "p weather ");
+
+        // the common prefix, then the matches
+        let mut prompt = with_input("p devices u");
+        assert!(prompt.complete(candidates).is_empty());
+        assert_eq!(prompt.input(), "p devices up");
+        assert_eq!(prompt.complete(candidates), ["update", "upgrade"]);
+        assert_eq!(prompt.input(), "p devices up");
+
+        let mut prompt = with_input("p x");
+        assert!(prompt.complete(candidates).is_empty());
+        assert_eq!(prompt.input(), "p x");
+    }
+
+    #[test]
+    fn history() {
+        let mut prompt = Prompt::new(vec!["p wol show".to_string()]);
+        assert!(prompt.push_history("p devices"));
+        assert!(!prompt.push_history("p devices"));
+
+        prompt.history_up();
+        assert_eq!(prompt.input(), "p devices");
+        prompt.history_up();
+        assert_eq!(prompt.input(), "p wol show");
+        prompt.history_down();
+        prompt.history_down();
+        assert_eq!(prompt.input(), "");
+
+        prompt.reverse_search();
+        for c in "wol".chars() {
+            prompt.insert(c);
+        }
+        prompt.reverse_search_accept();
+        assert_eq!(prompt.input(), "p wol show");
+    }
+}
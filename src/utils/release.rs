@@ -0,0 +1,171 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Result, anyhow};
+use ratatui::crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{self, EnterAlternateScreen},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc::Sender;
+
+use crate::consts;
+use crate::messages::Msg;
+use crate::utils::{api, common, nas};
+
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub version: String,
+    pub filename: String,
+    pub sha256: String,
+}
+
+// published by a node over MQTT once `p system upgrade` is done
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpgradeReport {
+    pub ok: bool,
+    pub version: String,
+    pub msg: String,
+}
+
+// a release swapped in for the running binary
+pub struct Installed {
+    pub manifest: Manifest,
+    // as before the swap, after it `current_exe()` is `<exe> (deleted)` on Linux
+    pub exe: PathBuf,
+}
+
+// e.g. linux-aarch64, a binary only runs on the same platform
+pub fn platform() -> String {
+    format!("{}-{}", env::consts::OS, env::consts::ARCH)
+}
+
+fn release_folder() -> PathBuf {
+    PathBuf::from(consts::NAS_RELEASE_FOLDER).join(platform())
+}
+
+fn sha256_str(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+// copy the running binary to the release folder, it is served by the web plugin
+pub fn release() -> Result<Manifest> {
+    let bytes = fs::read(env::current_exe()?)?;
+    let manifest = Manifest {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        filename: common::get_binary_name(),
+        sha256: sha256_str(&bytes),
+    };
+
+    let folder = release_folder();
+    fs::create_dir_all(&folder)?;
+    fs::write(folder.join(&manifest.filename), &bytes)?;
+    fs::write(
+        folder.join(MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+
+    Ok(manifest)
+}
+
+async fn download(msg_tx: &Sender<Msg>, module: &str, ip: &str, path: PathBuf) -> Result<Vec<u8>> {
+    let response = api::post_download(
+        msg_tx,
+        module,
+        ip,
+        consts::WEB_PORT,
+        &api::DownloadRequest {
+            data: api::DownloadData {
+                filename: path.to_string_lossy().to_string(),
+            },
+        },
+    )
+    .await?;
+
+    nas::decode(&response.data.content)
+}
+
+// the manifest of this platform on the server
+pub async fn manifest(msg_tx: &Sender<Msg>, module: &str, ip: &str) -> Result<Manifest> {
+    Ok(serde_json::from_slice(
+        &download(msg_tx, module, ip, release_folder().join(MANIFEST)).await?,
+    )?)
+}
+
+// download the release from the server, verify and swap the running binary.
+// returns None if already up to date.
+pub async fn upgrade(msg_tx: &Sender<Msg>, module: &str, ip: &str) -> Result<Option<Installed>> {
+    let folder = release_folder();
+    let exe = env::current_exe()?;
+
+    let manifest = manifest(msg_tx, module, ip).await?;
+    if manifest.version == env!("CARGO_PKG_VERSION") {
+        return Ok(None);
+    }
+
+    let bytes = download(msg_tx, module, ip, folder.join(&manifest.filename)).await?;
+    let sha256 = sha256_str(&bytes);
+    if sha256 != manifest.sha256 {
+        return Err(anyhow!(
+            "SHA-256 mismatch: expected {}, got {sha256}",
+            manifest.sha256
+        ));
+    }
+
+    // write next to the binary then rename, so the swap is atomic
+    let exe_new = exe.with_extension("new");
+    fs::write(&exe_new, &bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&exe_new, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(&exe_new, &exe)?;
+
+    Ok(Some(Installed { manifest, exe }))
+}
+
+// replace the process with the binary at `exe`, with the same arguments.
+// the terminal of the GUI (`tui`) is only given up right before the exec
+// and taken back if it fails.
+pub fn restart(exe: &Path, tui: bool) -> Result<()> {
+    if !exe.is_file() {
+        return Err(anyhow!("`{}` not found", exe.display()));
+    }
+
+    if tui {
+        let _ = execute!(io::stdout(), DisableMouseCapture);
+        ratatui::restore();
+    }
+
+    #[cfg(unix)]
+    let e = {
+        use std::os::unix::process::CommandExt;
+        std::process::Command::new(exe)
+            .args(env::args().skip(1))
+            .exec()
+    };
+
+    #[cfg(not(unix))]
+    let e = match std::process::Command::new(exe)
+        .args(env::args().skip(1))
+        .spawn()
+    {
+        Ok(_) => std::process::exit(0),
+        Err(e) => e,
+    };
+
+    // still this process, the GUI is redrawn in full by `p plugins redraw clear`
+    if tui {
+        let _ = terminal::enable_raw_mode();
+        let _ = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture);
+    }
+
+    Err(e.into())
+}
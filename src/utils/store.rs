@@ -0,0 +1,86 @@
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Serialize, de::DeserializeOwned};
+
+// append-only JSON lines file, one record per line
+pub fn append<T: Serialize>(path: &Path, record: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(record)?)?;
+
+    Ok(())
+}
+
+// unreadable lines (e.g. a torn last write) are skipped
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Vec<T>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file = fs::File::open(path)?;
+    let records = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<T>(&line).ok())
+        .collect();
+
+    Ok(records)
+}
+
+// a single JSON document, replaced as a whole
+pub fn save<T: Serialize>(path: &Path, record: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // write then rename, a reader never sees a half-written file
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_string(record)?)?;
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+pub fn read<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    Ok(Some(serde_json::from_str(&fs::read_to_string(path)?)?))
+}
+
+// replace a JSON lines file with the records, e.g. after pruning
+pub fn rewrite<T: Serialize>(path: &Path, records: &[T]) -> Result<()> {
+    let mut lines = String::new();
+    for record in records {
+        lines.push_str(&serde_json::to_string(record)?);
+        lines.push('\n');
+    }
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, lines)?;
+    fs::rename(&tmp, path)?;
+
+    Ok(())
+}
+
+// the records to keep, the number dropped
+pub fn retain<T: Serialize + DeserializeOwned>(
+    path: &Path,
+    keep: impl Fn(&T) -> bool,
+) -> Result<usize> {
+    let records = load::<T>(path)?;
+    let total = records.len();
+    let records: Vec<T> = records.into_iter().filter(keep).collect();
+    if records.len() < total {
+        rewrite(path, &records)?;
+    }
+
+    Ok(total - records.len())
+}
@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+use log::Level;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+use strum_macros::{AsRefStr, Display, EnumString};
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Copy, Debug)]
+pub enum ThemeName {
+    #[strum(serialize = "dark")]
+    Dark,
+    #[strum(serialize = "light")]
+    Light,
+    #[strum(serialize = "high_contrast")]
+    HighContrast,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub border_active: Style,
+    pub border_inactive: Style,
+    pub title_active: Style,
+    pub title_inactive: Style,
+    pub text_active: Style,
+    pub text_inactive: Style,
+    pub info: Style,
+    pub warn: Style,
+    pub error: Style,
+    // search matches
+    pub search: Style,
+    // selected rows, buttons and tabs
+    pub selected: Style,
+    pub table_header: Style,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::builtin(ThemeName::Dark)
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: ThemeName) -> Self {
+        let fg = |color: Color| Style::default().fg(color);
+        let reversed = Style::default().add_modifier(Modifier::REVERSED);
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+
+        match name {
+            ThemeName::Dark => Self {
+                border_active: fg(Color::Cyan),
+                border_inactive: fg(Color::White),
+                title_active: fg(Color::Cyan),
+                title_inactive: fg(Color::White),
+                text_active: fg(Color::Cyan),
+                text_inactive: fg(Color::White),
+                info: Style::default(),
+                warn: fg(Color::Yellow),
+                error: fg(Color::Red),
+                search: fg(Color::Black).bg(Color::Yellow),
+                selected: reversed,
+                table_header: Style::default(),
+            },
+            // dark text for a light terminal background
+            ThemeName::Light => Self {
+                border_active: fg(Color::Blue),
+                border_inactive: fg(Color::DarkGray),
+                title_active: fg(Color::Blue).add_modifier(Modifier::BOLD),
+                title_inactive: fg(Color::DarkGray),
+                text_active: fg(Color::Black),
+                text_inactive: fg(Color::DarkGray),
+                info: Style::default(),
+                warn: fg(Color::Indexed(130)),
+                error: fg(Color::Red),
+                search: fg(Color::White).bg(Color::Blue),
+                selected: reversed,
+                table_header: bold,
+            },
+            ThemeName::HighContrast => Self {
+                border_active: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                border_inactive: fg(Color::White),
+                title_active: fg(Color::LightYellow).add_modifier(Modifier::BOLD),
+                title_inactive: fg(Color::White).add_modifier(Modifier::BOLD),
+                text_active: fg(Color::White),
+                text_inactive: fg(Color::Gray),
+                info: Style::default(),
+                warn: fg(Color::Black).bg(Color::LightYellow),
+                error: fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+                search: fg(Color::Black).bg(Color::LightCyan),
+                selected: reversed.add_modifier(Modifier::BOLD),
+                table_header: bold.add_modifier(Modifier::UNDERLINED),
+            },
+        }
+    }
+
+    pub fn border(&self, active: bool) -> Style {
+        if active {
+            self.border_active
+        } else {
+            self.border_inactive
+        }
+    }
+
+    pub fn title(&self, active: bool) -> Style {
+        if active {
+            self.title_active
+        } else {
+            self.title_inactive
+        }
+    }
+
+    pub fn text(&self, active: bool) -> Style {
+        if active {
+            self.text_active
+        } else {
+            self.text_inactive
+        }
+    }
+
+    // lines without a level are as info
+    pub fn level(&self, level: Option<Level>) -> Style {
+        match level {
+            Some(Level::Error) => self.error,
+            Some(Level::Warn) => self.warn,
+            _ => self.info,
+        }
+    }
+
+    fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+        Some(match name {
+            "border_active" => &mut self.border_active,
+            "border_inactive" => &mut self.border_inactive,
+            "title_active" => &mut self.title_active,
+            "title_inactive" => &mut self.title_inactive,
+            "text_active" => &mut self.text_active,
+            "text_inactive" => &mut self.text_inactive,
+            "info" => &mut self.info,
+            "warn" => &mut self.warn,
+            "error" => &mut self.error,
+            "search" => &mut self.search,
+            "selected" => &mut self.selected,
+            "table_header" => &mut self.table_header,
+            _ => return None,
+        })
+    }
+}
+
+// a theme file, e.g.
+//
+//   base = "light"
+//
+//   [styles]
+//   border_active = { fg = "magenta", modifiers = ["bold"] }
+//   warn = { fg = "#af8700" }
+#[derive(Debug, Deserialize)]
+struct ThemeCfg {
+    base: Option<String>,
+    #[serde(default)]
+    styles: HashMap<String, StyleCfg>,
+}
+
+// colors as `red`, `light-blue`, `#rrggbb` or an index `208`
+#[derive(Debug, Deserialize)]
+struct StyleCfg {
+    fg: Option<String>,
+    bg: Option<String>,
+    #[serde(default)]
+    modifiers: Vec<String>,
+}
+
+fn parse_color(color: &str) -> Result<Color, String> {
+    Color::from_str(color).map_err(|_| format!("Invalid color `{color}`"))
+}
+
+fn parse_modifier(modifier: &str) -> Result<Modifier, String> {
+    match modifier {
+        "bold" => Ok(Modifier::BOLD),
+        "dim" => Ok(Modifier::DIM),
+        "italic" => Ok(Modifier::ITALIC),
+        "underlined" => Ok(Modifier::UNDERLINED),
+        "reversed" => Ok(Modifier::REVERSED),
+        "crossed_out" => Ok(Modifier::CROSSED_OUT),
+        _ => Err(format!("Invalid modifier `{modifier}`")),
+    }
+}
+
+impl StyleCfg {
+    fn style(&self) -> Result<Style, String> {
+        let mut style = Style::default();
+        if let Some(fg) = &self.fg {
+            style = style.fg(parse_color(fg)?);
+        }
+        if let Some(bg) = &self.bg {
+            style = style.bg(parse_color(bg)?);
+        }
+        for modifier in &self.modifiers {
+            style = style.add_modifier(parse_modifier(modifier)?);
+        }
+
+        Ok(style)
+    }
+}
+
+// a built-in theme name or the path of a theme file
+pub fn load(theme: &str) -> Result<Theme, String> {
+    if let Ok(name) = theme.parse::<ThemeName>() {
+        return Ok(Theme::builtin(name));
+    }
+
+    let theme_str =
+        fs::read_to_string(theme).map_err(|e| format!("Failed to read `{theme}`: {e}"))?;
+    let theme_cfg: ThemeCfg =
+        toml::from_str(&theme_str).map_err(|e| format!("Failed to parse `{theme}`: {e}"))?;
+
+    let mut theme = match &theme_cfg.base {
+        Some(base) => Theme::builtin(
+            base.parse::<ThemeName>()
+                .map_err(|_| format!("Unknown base theme `{base}`"))?,
+        ),
+        None => Theme::default(),
+    };
+    for (name, style_cfg) in &theme_cfg.styles {
+        let style = style_cfg.style()?;
+        *theme
+            .style_mut(name)
+            .ok_or_else(|| format!("Unknown style `{name}`"))? = style;
+    }
+
+    Ok(theme)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn theme_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("cng4_theme_{name}.toml"));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn colors() {
+        assert_eq!(parse_color("red"), Ok(Color::Red));
+        assert_eq!(parse_color("light-blue"), Ok(Color::LightBlue));
+        assert_eq!(parse_color("#af8700"), Ok(Color::Rgb(0xaf, 0x87, 0x00)));
+        assert_eq!(parse_color("208"), Ok(Color::Indexed(208)));
+        assert!(parse_color("nope").is_err());
+
+        assert_eq!(parse_modifier("bold"), Ok(Modifier::BOLD));
+        assert!(parse_modifier("blink").is_err());
+    }
+
+    #[test]
+    fn load_builtin() {
+        let theme = load("light").unwrap();
+        assert_eq!(theme.warn, Theme::builtin(ThemeName::Light).warn);
+    }
+
+    #[test]
+    fn load_file() {
+        let path = theme_file(
+            "ok",
+            r##"
+base = "light"
+
+[styles]
+border_active = { fg = "magenta", modifiers = ["bold"] }
+warn = { fg = "#af8700" }
+"##,
+        );
+
+        let theme = load(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            theme.border_active,
+            Style::default()
+                .fg(Color::Magenta)
+                .add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(
+            theme.warn,
+            Style::default().fg(Color::Rgb(0xaf, 0x87, 0x00))
+        );
+        // the rest from the base
+        assert_eq!(theme.search, Theme::builtin(ThemeName::Light).search);
+    }
+
+    #[test]
+    fn load_invalid() {
+        for (name, content) in [
+            ("style", "[styles]\nborders = { fg = \"red\" }\n"),
+            ("base", "base = \"solarized\"\n"),
+            ("color", "[styles]\nwarn = { fg = \"nope\" }\n"),
+            ("modifier", "[styles]\nwarn = { modifiers = [\"blink\"] }\n"),
+            ("toml", "[styles\n"),
+        ] {
+            let path = theme_file(name, content);
+            assert!(load(path.to_str().unwrap()).is_err(), "{name}");
+        }
+
+        assert!(load("./test/missing_theme.toml").is_err());
+    }
+}
@@ -48,6 +48,15 @@ pub fn datetime_str_to_ts(datetime_str: &str) -> i64 {
     naive_datetime.and_utc().timestamp()
 }
 
+// e.g. Mon 13:00, the input as datetime_str_to_ts() but never panics,
+// in the zone of the input
+pub fn datetime_str_format(datetime_str: &str, format: &str) -> String {
+    match NaiveDateTime::parse_from_str(datetime_str, "%Y-%m-%dT%H:%M") {
+        Ok(naive_datetime) => naive_datetime.format(format).to_string(),
+        Err(_) => datetime_str.to_string(),
+    }
+}
+
 //
 // uptime
 //
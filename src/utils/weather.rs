@@ -1,20 +1,69 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::{AsRefStr, Display, EnumIter, EnumString};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::consts;
+use crate::globals;
+use crate::utils::{self, store, weather_providers};
+
+const CACHE_FOLDER: &str = "weather";
+// shorter than the polling, so every poll refreshes
+const CACHE_TTL: u64 = 10 * 60;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
 pub struct WeatherDaily {
     pub time: String,
     pub temperature_2m_max: f32,
     pub temperature_2m_min: f32,
     pub precipitation_probability_max: u8,
     pub weather_code: u8,
+    // not every provider has these
+    pub sunrise: Option<String>,
+    pub sunset: Option<String>,
+    pub wind_speed_10m_max: f32,
+    pub uv_index_max: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct WeatherHourly {
+    pub time: String,
+    pub temperature_2m: f32,
+    pub precipitation_probability: u8,
+    pub precipitation: f32,
+    pub wind_speed_10m: f32,
+    pub relative_humidity_2m: u8,
+    pub uv_index: f32,
 }
 
+// all times are local to the city, `utc_offset_seconds` from UTC
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Weather {
     pub time: String,
     pub temperature: f32,
     pub weathercode: u8,
+    #[serde(default)]
+    pub utc_offset_seconds: i32,
     pub daily: Vec<WeatherDaily>,
+    // next 24 hours
+    #[serde(default)]
+    pub hourly: Vec<WeatherHourly>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct AirQuality {
+    pub time: String,
+    pub us_aqi: u16,
+    pub pm2_5: f32,
+    pub pm10: f32,
 }
 
 #[derive(Debug, Clone)]
@@ -22,129 +71,481 @@ pub struct City {
     pub name: String,
     pub latitude: f32,
     pub longitude: f32,
+    pub provider: String,
+    pub weather: Option<Weather>,
+    // the last fetch failed, `weather` is stale if any
+    pub error: Option<String>,
+    pub air_quality: Option<AirQuality>,
+}
+
+impl City {
+    // as reported with the weather, else estimated from the longitude
+    pub fn utc_offset_seconds(&self) -> i32 {
+        self.weather.as_ref().map_or_else(
+            || solar_utc_offset(self.longitude),
+            |weather| weather.utc_offset_seconds,
+        )
+    }
+}
+
+#[async_trait]
+pub trait WeatherProvider {
+    fn name(&self) -> &str;
+    async fn get_weather(&self, latitude: f32, longitude: f32) -> Result<Weather, String>;
+
+    // not every provider has air quality
+    async fn get_air_quality(&self, _latitude: f32, _longitude: f32) -> Result<AirQuality, String> {
+        Err(format!("No air quality from `{}`", self.name()))
+    }
+
+    // e.g. a fixture is cheap and edited by hand
+    fn cacheable(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct CacheEntry {
+    ts: u64,
+    weather: Weather,
+}
+
+#[derive(Debug)]
+pub struct Fetched {
     pub weather: Option<Weather>,
+    pub error: Option<String>,
 }
 
-pub async fn get_weather(latitude: f32, longitude: f32) -> Result<Weather, String> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "https://api.open-meteo.com/v1/forecast?latitude={latitude}&longitude={longitude}&daily=temperature_2m_max,temperature_2m_min,precipitation_probability_max,weather_code&current_weather=true"
-    );
-
-    let response = client
-        .get(url)
-        .timeout(tokio::time::Duration::from_secs(5))
-        .send()
-        .await
-        .map_err(|e| format!("Failed to get weather: {e}"))?;
-
-    let body = response
-        .text()
-        .await
-        .map_err(|e| format!("Failed to get weather: {e}"))?;
-
-    let weather_data: serde_json::Value =
-        serde_json::from_str(&body).map_err(|e| format!("Failed to parse weather data: {e}"))?;
-
-    let current = &weather_data["current_weather"];
-    let time = current["time"]
-        .as_str()
-        .ok_or("Missing current_weather.time")?
-        .to_string();
-    let temperature = current["temperature"]
-        .as_f64()
-        .ok_or("Missing or invalid current_weather.temperature")? as f32;
-    let weathercode = current["weathercode"]
-        .as_u64()
-        .ok_or("Missing or invalid current_weather.weathercode")? as u8;
-
-    let (max_temps, min_temps, precip_probs, weather_codes, dates) = (
-        weather_data["daily"]["temperature_2m_max"]
-            .as_array()
-            .ok_or("Missing daily.temperature_2m_max")?,
-        weather_data["daily"]["temperature_2m_min"]
-            .as_array()
-            .ok_or("Missing daily.temperature_2m_min")?,
-        weather_data["daily"]["precipitation_probability_max"]
-            .as_array()
-            .ok_or("Missing daily.precipitation_probability_max")?,
-        weather_data["daily"]["weather_code"]
-            .as_array()
-            .ok_or("Missing daily.weather_code")?,
-        weather_data["daily"]["time"]
-            .as_array()
-            .ok_or("Missing daily.time")?,
-    );
-
-    let len = max_temps.len();
-    if min_temps.len() != len
-        || precip_probs.len() != len
-        || weather_codes.len() != len
-        || dates.len() != len
+fn cache_path(provider: &str, latitude: f32, longitude: f32) -> PathBuf {
+    PathBuf::from(consts::DATA_FOLDER)
+        .join(CACHE_FOLDER)
+        .join(format!("{provider}_{latitude:.4}_{longitude:.4}.json"))
+}
+
+// fresh cache first, then the provider, then stale cache with the error
+pub async fn get_weather(
+    provider: &(dyn WeatherProvider + Send + Sync),
+    latitude: f32,
+    longitude: f32,
+) -> Fetched {
+    if !provider.cacheable() {
+        return match provider.get_weather(latitude, longitude).await {
+            Ok(weather) => Fetched {
+                weather: Some(weather),
+                error: None,
+            },
+            Err(e) => Fetched {
+                weather: None,
+                error: Some(e),
+            },
+        };
+    }
+
+    let path = cache_path(provider.name(), latitude, longitude);
+    let cached = store::read::<CacheEntry>(&path).ok().flatten();
+
+    if let Some(cached) = &cached
+        && utils::time::ts().saturating_sub(cached.ts) < CACHE_TTL
     {
-        return Err("Mismatch in forecast array lengths".to_string());
+        return Fetched {
+            weather: Some(cached.weather.clone()),
+            error: None,
+        };
     }
 
-    let mut daily_forecast = Vec::new();
-    for i in 0..len {
-        let daily = WeatherDaily {
-            time: dates[i].as_str().ok_or("Invalid daily.time")?.to_string(),
-            temperature_2m_max: max_temps[i].as_f64().ok_or("Invalid temperature_2m_max")? as f32,
-            temperature_2m_min: min_temps[i].as_f64().ok_or("Invalid temperature_2m_min")? as f32,
-            precipitation_probability_max: precip_probs[i]
-                .as_f64()
-                .ok_or("Invalid precipitation_probability_max")?
-                as u8,
-            weather_code: weather_codes[i].as_u64().ok_or("Invalid weather_code")? as u8,
+    match provider.get_weather(latitude, longitude).await {
+        Ok(weather) => {
+            let entry = CacheEntry {
+                ts: utils::time::ts(),
+                weather,
+            };
+            // only a cache, the next poll tries again
+            let _ = store::save(&path, &entry);
+            Fetched {
+                weather: Some(entry.weather),
+                error: None,
+            }
+        }
+        Err(e) => Fetched {
+            weather: cached.map(|cached| cached.weather),
+            error: Some(e),
+        },
+    }
+}
+
+pub const DEFAULT_PROVIDER: &str = weather_providers::OPEN_METEO;
+pub const PROVIDERS: [&str; 3] = [
+    weather_providers::OPEN_METEO,
+    weather_providers::MET_NO,
+    weather_providers::FIXTURE,
+];
+
+// <provider>, or fixture:<path> for a fixture file other than the default one
+pub fn provider(spec: &str) -> Option<Box<dyn WeatherProvider + Send + Sync>> {
+    let (name, arg) = match spec.split_once(':') {
+        Some((name, arg)) => (name, Some(arg)),
+        None => (spec, None),
+    };
+
+    match name {
+        weather_providers::OPEN_METEO => Some(Box::new(weather_providers::OpenMeteo)),
+        weather_providers::MET_NO => Some(Box::new(weather_providers::MetNo)),
+        weather_providers::FIXTURE => Some(Box::new(weather_providers::Fixture {
+            path: arg.unwrap_or(weather_providers::FIXTURE_PATH).to_string(),
+        })),
+        _ => None,
+    }
+}
+
+// the offset of the mean solar time, for providers that report UTC only
+pub fn solar_utc_offset(longitude: f32) -> i32 {
+    (longitude / 15.0).round() as i32 * 60 * 60
+}
+
+// alert conditions on the daily forecast, e.g. `temperature_2m_max > 35`
+#[derive(EnumString, AsRefStr, Display, EnumIter, PartialEq, Clone, Copy, Debug)]
+pub enum ConditionField {
+    #[strum(serialize = "temperature_2m_max")]
+    Temperature2mMax,
+    #[strum(serialize = "temperature_2m_min")]
+    Temperature2mMin,
+    #[strum(serialize = "precipitation_probability_max")]
+    PrecipitationProbabilityMax,
+    #[strum(serialize = "weather_code")]
+    WeatherCode,
+    #[strum(serialize = "wind_speed_10m_max")]
+    WindSpeed10mMax,
+    #[strum(serialize = "uv_index_max")]
+    UvIndexMax,
+}
+
+// two-char operators first, they are tried in this order
+#[derive(EnumString, AsRefStr, Display, EnumIter, PartialEq, Clone, Copy, Debug)]
+pub enum ConditionOp {
+    #[strum(serialize = ">=")]
+    Ge,
+    #[strum(serialize = "<=")]
+    Le,
+    #[strum(serialize = "==")]
+    Eq,
+    #[strum(serialize = ">")]
+    Gt,
+    #[strum(serialize = "<")]
+    Lt,
+}
+
+#[derive(Debug, Clone)]
+pub struct WeatherCondition {
+    pub field: ConditionField,
+    pub op: ConditionOp,
+    pub value: f32,
+}
+
+impl WeatherCondition {
+    fn field_value(&self, daily: &WeatherDaily) -> f32 {
+        match self.field {
+            ConditionField::Temperature2mMax => daily.temperature_2m_max,
+            ConditionField::Temperature2mMin => daily.temperature_2m_min,
+            ConditionField::PrecipitationProbabilityMax => {
+                daily.precipitation_probability_max as f32
+            }
+            ConditionField::WeatherCode => daily.weather_code as f32,
+            ConditionField::WindSpeed10mMax => daily.wind_speed_10m_max,
+            ConditionField::UvIndexMax => daily.uv_index_max,
+        }
+    }
+
+    // the value of the field if the condition holds
+    pub fn matches(&self, daily: &WeatherDaily) -> Option<f32> {
+        let value = self.field_value(daily);
+        let matched = match self.op {
+            ConditionOp::Ge => value >= self.value,
+            ConditionOp::Le => value <= self.value,
+            ConditionOp::Eq => value == self.value,
+            ConditionOp::Gt => value > self.value,
+            ConditionOp::Lt => value < self.value,
         };
-        daily_forecast.push(daily);
-    }
-
-    Ok(Weather {
-        time,
-        temperature,
-        weathercode,
-        daily: daily_forecast,
-    })
-}
-
-const WEATHER_CODES: [(u8, &str); 28] = [
-    (0, "晴天"),
-    (1, "多雲時晴"),
-    (2, "局部多雲"),
-    (3, "陰天"),
-    (45, "有霧"),
-    (48, "凍霧"),
-    (51, "毛毛雨（小）"),
-    (53, "毛毛雨（中）"),
-    (55, "毛毛雨（大）"),
-    (56, "凍雨（小）"),
-    (57, "凍雨（大）"),
-    (61, "小雨"),
-    (63, "中雨"),
-    (65, "大雨"),
-    (66, "凍雨（小雨）"),
-    (67, "凍雨（大雨）"),
-    (71, "小雪"),
-    (73, "中雪"),
-    (75, "大雪"),
-    (77, "雪粒"),
-    (80, "小陣雨"),
-    (81, "中陣雨"),
-    (82, "強陣雨"),
-    (85, "小陣雪"),
-    (86, "大陣雪"),
-    (95, "雷雨"),
-    (96, "雷雨夾小冰雹"),
-    (99, "雷雨夾大冰雹"),
+
+        matched.then_some(value)
+    }
+}
+
+impl FromStr for WeatherCondition {
+    type Err = String;
+
+    // spaces around the operator are optional
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (field, op, value) = ConditionOp::iter()
+            .find_map(|op| {
+                s.split_once(op.as_ref())
+                    .map(|(field, value)| (field, op, value))
+            })
+            .ok_or(format!(
+                "Missing operator, one of {}",
+                ConditionOp::iter()
+                    .map(|op| op.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ))?;
+
+        let field = field.trim();
+        let field = field
+            .parse::<ConditionField>()
+            .map_err(|_| format!("Unknown field `{field}`"))?;
+
+        let value = value
+            .trim()
+            .parse::<f32>()
+            .map_err(|e| format!("Invalid value `{}`: {e}", value.trim()))?;
+
+        Ok(Self { field, op, value })
+    }
+}
+
+impl fmt::Display for WeatherCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.field, self.op, self.value)
+    }
+}
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Copy, Debug)]
+pub enum Locale {
+    #[strum(serialize = "en")]
+    En,
+    #[strum(serialize = "zh-TW")]
+    ZhTw,
+    #[strum(serialize = "nl")]
+    Nl,
+    #[strum(serialize = "ja")]
+    Ja,
+}
+
+#[derive(EnumString, AsRefStr, Display, PartialEq, Clone, Copy, Debug)]
+pub enum Units {
+    // °C, km/h
+    #[strum(serialize = "metric")]
+    Metric,
+    // °F, mph
+    #[strum(serialize = "imperial")]
+    Imperial,
+}
+
+// data from the providers are always metric
+pub fn temperature_str(celsius: f32) -> String {
+    match globals::get_units() {
+        Units::Metric => format!("{celsius:.1}°C"),
+        Units::Imperial => format!("{:.1}°F", celsius * 9.0 / 5.0 + 32.0),
+    }
+}
+
+// a difference of temperatures, no offset in fahrenheit
+pub fn temperature_delta_str(celsius: f32) -> String {
+    match globals::get_units() {
+        Units::Metric => format!("{celsius:.1}°C"),
+        Units::Imperial => format!("{:.1}°F", celsius * 9.0 / 5.0),
+    }
+}
+
+// max/min without decimals and unit, for narrow columns
+pub fn temperature_range_str(max: f32, min: f32) -> String {
+    match globals::get_units() {
+        Units::Metric => format!("{max:.0}/{min:.0}"),
+        Units::Imperial => format!(
+            "{:.0}/{:.0}",
+            max * 9.0 / 5.0 + 32.0,
+            min * 9.0 / 5.0 + 32.0
+        ),
+    }
+}
+
+pub fn wind_speed_str(kmh: f32) -> String {
+    match globals::get_units() {
+        Units::Metric => format!("{kmh:.0} km/h"),
+        Units::Imperial => format!("{:.0} mph", kmh / 1.609_344),
+    }
+}
+
+const WEEKDAYS: [[&str; 7]; 4] = [
+    ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    ["週一", "週二", "週三", "週四", "週五", "週六", "週日"],
+    ["ma", "di", "wo", "do", "vr", "za", "zo"],
+    ["月", "火", "水", "木", "金", "土", "日"],
 ];
 
+// 2025-01-01 -> 01/01 Wed
+pub fn date_str(date: &str) -> String {
+    match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+        Ok(date) => format!(
+            "{} {}",
+            date.format("%m/%d"),
+            WEEKDAYS[globals::get_locale() as usize]
+                [date.weekday().num_days_from_monday() as usize]
+        ),
+        Err(_) => date.to_string(),
+    }
+}
+
+// en, zh-TW, nl, ja, in the order of `Locale`
+const WEATHER_CODES: [(u8, [&str; 4]); 28] = [
+    (0, ["Clear sky", "晴天", "Onbewolkt", "快晴"]),
+    (1, ["Mainly clear", "多雲時晴", "Overwegend helder", "晴れ"]),
+    (2, ["Partly cloudy", "局部多雲", "Half bewolkt", "一部曇り"]),
+    (3, ["Overcast", "陰天", "Bewolkt", "曇り"]),
+    (45, ["Fog", "有霧", "Mist", "霧"]),
+    (48, ["Rime fog", "凍霧", "Aanvriezende mist", "着氷性の霧"]),
+    (
+        51,
+        [
+            "Light drizzle",
+            "毛毛雨（小）",
+            "Lichte motregen",
+            "弱い霧雨",
+        ],
+    ),
+    (53, ["Drizzle", "毛毛雨（中）", "Motregen", "霧雨"]),
+    (
+        55,
+        [
+            "Dense drizzle",
+            "毛毛雨（大）",
+            "Dichte motregen",
+            "強い霧雨",
+        ],
+    ),
+    (
+        56,
+        [
+            "Freezing drizzle",
+            "凍雨（小）",
+            "Lichte ijzel",
+            "弱い着氷性霧雨",
+        ],
+    ),
+    (
+        57,
+        [
+            "Dense freezing drizzle",
+            "凍雨（大）",
+            "IJzel",
+            "強い着氷性霧雨",
+        ],
+    ),
+    (61, ["Light rain", "小雨", "Lichte regen", "小雨"]),
+    (63, ["Rain", "中雨", "Regen", "雨"]),
+    (65, ["Heavy rain", "大雨", "Zware regen", "大雨"]),
+    (
+        66,
+        [
+            "Freezing rain",
+            "凍雨（小雨）",
+            "Lichte ijsregen",
+            "弱い着氷性の雨",
+        ],
+    ),
+    (
+        67,
+        [
+            "Heavy freezing rain",
+            "凍雨（大雨）",
+            "IJsregen",
+            "強い着氷性の雨",
+        ],
+    ),
+    (71, ["Light snow", "小雪", "Lichte sneeuw", "小雪"]),
+    (73, ["Snow", "中雪", "Sneeuw", "雪"]),
+    (75, ["Heavy snow", "大雪", "Zware sneeuw", "大雪"]),
+    (77, ["Snow grains", "雪粒", "Motsneeuw", "霧雪"]),
+    (
+        80,
+        ["Light showers", "小陣雨", "Lichte buien", "弱いにわか雨"],
+    ),
+    (81, ["Showers", "中陣雨", "Buien", "にわか雨"]),
+    (
+        82,
+        ["Violent showers", "強陣雨", "Zware buien", "激しいにわか雨"],
+    ),
+    (
+        85,
+        [
+            "Snow showers",
+            "小陣雪",
+            "Lichte sneeuwbuien",
+            "弱いにわか雪",
+        ],
+    ),
+    (
+        86,
+        [
+            "Heavy snow showers",
+            "大陣雪",
+            "Zware sneeuwbuien",
+            "強いにわか雪",
+        ],
+    ),
+    (95, ["Thunderstorm", "雷雨", "Onweer", "雷雨"]),
+    (
+        96,
+        [
+            "Thunderstorm, hail",
+            "雷雨夾小冰雹",
+            "Onweer met hagel",
+            "雷雨・ひょう",
+        ],
+    ),
+    (
+        99,
+        [
+            "Thunderstorm, heavy hail",
+            "雷雨夾大冰雹",
+            "Onweer met zware hagel",
+            "雷雨・大粒のひょう",
+        ],
+    ),
+];
+const UNKNOWN_WEATHER: [&str; 4] = ["Unknown", "未知天氣", "Onbekend", "不明"];
+
+// US AQI categories by upper bound
+const AQI_CATEGORIES: [(u16, [&str; 4]); 6] = [
+    (50, ["Good", "良好", "Goed", "良い"]),
+    (100, ["Moderate", "普通", "Matig", "普通"]),
+    (
+        150,
+        [
+            "Unhealthy for sensitive groups",
+            "對敏感族群不健康",
+            "Ongezond voor gevoeligen",
+            "敏感な人に不健康",
+        ],
+    ),
+    (200, ["Unhealthy", "對所有族群不健康", "Ongezond", "不健康"]),
+    (
+        300,
+        [
+            "Very unhealthy",
+            "非常不健康",
+            "Zeer ongezond",
+            "非常に不健康",
+        ],
+    ),
+    (u16::MAX, ["Hazardous", "危害", "Gevaarlijk", "危険"]),
+];
+
+pub fn aqi_str(us_aqi: u16) -> &'static str {
+    let locale = globals::get_locale() as usize;
+
+    AQI_CATEGORIES
+        .iter()
+        .find(|&&(max, _)| us_aqi <= max)
+        .map_or(UNKNOWN_WEATHER[locale], |&(_, desc)| desc[locale])
+}
+
 pub fn weather_code_str(code: u8) -> &'static str {
+    let locale = globals::get_locale() as usize;
+
     WEATHER_CODES
         .iter()
         .find(|&&(c, _)| c == code)
-        .map(|&(_, desc)| desc)
-        .unwrap_or("未知天氣")
+        .map_or(UNKNOWN_WEATHER[locale], |&(_, desc)| desc[locale])
 }
 
 const WEATHER_CODES_EMOJI: [(u8, &str); 28] = [
@@ -183,5 +584,70 @@ pub fn weather_code_emoji(code: u8) -> &'static str {
         .iter()
         .find(|&&(c, _)| c == code)
         .map(|&(_, desc)| desc)
-        .unwrap_or("未知天氣")
+        .unwrap_or("❓")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(s: &str) -> WeatherCondition {
+        s.parse::<WeatherCondition>().unwrap()
+    }
+
+    #[test]
+    fn parse_condition() {
+        let parsed = condition("temperature_2m_max >= 35");
+        assert_eq!(parsed.field.to_string(), "temperature_2m_max");
+        assert_eq!(parsed.op.to_string(), ">=");
+        assert_eq!(parsed.value, 35.0);
+
+        // `>=` is not `>` followed by `= 35`
+        let parsed = condition("uv_index_max>=8");
+        assert_eq!(parsed.op.to_string(), ">=");
+        let parsed = condition("uv_index_max>8");
+        assert_eq!(parsed.op.to_string(), ">");
+        assert_eq!(parsed.value, 8.0);
+
+        let parsed = condition("weather_code == 61");
+        assert_eq!(parsed.to_string(), "weather_code == 61");
+    }
+
+    #[test]
+    fn parse_condition_invalid() {
+        for s in [
+            "temperature_2m_max 35",
+            "temperature > 35",
+            "temperature_2m_max => 35",
+            "temperature_2m_max > hot",
+            "> 35",
+        ] {
+            assert!(s.parse::<WeatherCondition>().is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn condition_matches() {
+        let daily = WeatherDaily {
+            temperature_2m_max: 35.0,
+            precipitation_probability_max: 80,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            condition("temperature_2m_max >= 35").matches(&daily),
+            Some(35.0)
+        );
+        assert_eq!(condition("temperature_2m_max > 35").matches(&daily), None);
+        assert_eq!(
+            condition("temperature_2m_max <= 35").matches(&daily),
+            Some(35.0)
+        );
+        assert_eq!(condition("temperature_2m_max < 35").matches(&daily), None);
+        assert_eq!(
+            condition("precipitation_probability_max == 80").matches(&daily),
+            Some(80.0)
+        );
+        assert_eq!(condition("uv_index_max > 0").matches(&daily), None);
+    }
 }
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::consts;
+use crate::utils::common;
+
+const HISTORY_FOLDER: &str = "weather/history";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum WeatherRecord {
+    // `time` as reported by the provider, one record per time
+    Observation {
+        ts: u64,
+        time: String,
+        temperature: f32,
+        weathercode: u8,
+    },
+    // the first forecast seen for a day, i.e. about a day ahead
+    Forecast {
+        ts: u64,
+        date: String,
+        temperature_2m_max: f32,
+        temperature_2m_min: f32,
+    },
+}
+
+impl WeatherRecord {
+    pub fn ts(&self) -> u64 {
+        match self {
+            WeatherRecord::Observation { ts, .. } | WeatherRecord::Forecast { ts, .. } => *ts,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DayComparison {
+    pub date: String,
+    // (max, min)
+    pub forecast: (f32, f32),
+    pub actual: (f32, f32),
+}
+
+#[derive(Debug, Default)]
+pub struct WeatherHistory {
+    pub observations: usize,
+    // (min, avg, max)
+    pub temperature: Option<(f32, f32, f32)>,
+    // in time order, for the trend
+    pub temperatures: Vec<f32>,
+    // complete days only
+    pub days: Vec<DayComparison>,
+}
+
+// the city name is the file name, so no separators or `..`
+pub fn history_path(city: &str) -> Result<PathBuf> {
+    Ok(PathBuf::from(consts::DATA_FOLDER)
+        .join(HISTORY_FOLDER)
+        .join(format!("{}.jsonl", common::check_file_name(city)?)))
+}
+
+impl WeatherHistory {
+    // records must be in time order, as they are appended
+    pub fn new(records: &[WeatherRecord], from: u64, to: u64) -> Self {
+        let mut history = Self::default();
+
+        let mut observed: BTreeMap<&str, (f32, f32)> = BTreeMap::new();
+        let mut forecasts: BTreeMap<&str, (f32, f32)> = BTreeMap::new();
+        let mut last_time: Option<&str> = None;
+        for record in records
+            .iter()
+            .filter(|record| record.ts() >= from && record.ts() <= to)
+        {
+            match record {
+                WeatherRecord::Observation {
+                    time, temperature, ..
+                } => {
+                    // the same observation again after a restart
+                    if last_time == Some(time.as_str()) {
+                        continue;
+                    }
+                    last_time = Some(time.as_str());
+                    history.temperatures.push(*temperature);
+
+                    let Some(date) = time.get(..10) else {
+                        continue;
+                    };
+                    let day = observed.entry(date).or_insert((*temperature, *temperature));
+                    day.0 = day.0.max(*temperature);
+                    day.1 = day.1.min(*temperature);
+                }
+                WeatherRecord::Forecast {
+                    date,
+                    temperature_2m_max,
+                    temperature_2m_min,
+                    ..
+                } => {
+                    forecasts
+                        .entry(date.as_str())
+                        .or_insert((*temperature_2m_max, *temperature_2m_min));
+                }
+            }
+        }
+
+        history.observations = history.temperatures.len();
+        if !history.temperatures.is_empty() {
+            let min = history
+                .temperatures
+                .iter()
+                .cloned()
+                .fold(f32::MAX, f32::min);
+            let max = history
+                .temperatures
+                .iter()
+                .cloned()
+                .fold(f32::MIN, f32::max);
+            let avg = history.temperatures.iter().sum::<f32>() / history.observations as f32;
+            history.temperature = Some((min, avg, max));
+        }
+
+        // the day of the last observation is not over yet
+        let today = last_time.and_then(|time| time.get(..10));
+        history.days = observed
+            .iter()
+            .filter(|(date, _)| Some(**date) != today)
+            .filter_map(|(date, actual)| {
+                forecasts.get(date).map(|forecast| DayComparison {
+                    date: date.to_string(),
+                    forecast: *forecast,
+                    actual: *actual,
+                })
+            })
+            .collect();
+
+        history
+    }
+
+    // mean absolute error of the forecast (max, min)
+    pub fn forecast_error(&self) -> Option<(f32, f32)> {
+        if self.days.is_empty() {
+            return None;
+        }
+
+        let len = self.days.len() as f32;
+        let max = self
+            .days
+            .iter()
+            .map(|day| (day.forecast.0 - day.actual.0).abs())
+            .sum::<f32>();
+        let min = self
+            .days
+            .iter()
+            .map(|day| (day.forecast.1 - day.actual.1).abs())
+            .sum::<f32>();
+
+        Some((max / len, min / len))
+    }
+
+    // averaged down to at most `points` values
+    pub fn trend(&self, points: usize) -> Vec<f32> {
+        common::downsample(&self.temperatures, points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(ts: u64, time: &str, temperature: f32) -> WeatherRecord {
+        WeatherRecord::Observation {
+            ts,
+            time: time.to_string(),
+            temperature,
+            weathercode: 0,
+        }
+    }
+
+    #[test]
+    fn new() {
+        let records = [
+            WeatherRecord::Forecast {
+                ts: 0,
+                date: "2025-01-01".to_string(),
+                temperature_2m_max: 24.0,
+                temperature_2m_min: 17.0,
+            },
+            observation(1, "2025-01-01T06:00", 16.0),
+            observation(2, "2025-01-01T14:00", 25.0),
+            // again after a restart
+            observation(3, "2025-01-01T14:00", 25.0),
+            observation(4, "2025-01-02T06:00", 19.0),
+            // out of range
+            observation(10, "2025-01-02T14:00", 30.0),
+        ];
+
+        let history = WeatherHistory::new(&records, 0, 5);
+        assert_eq!(history.observations, 3);
+        assert_eq!(history.temperatures, [16.0, 25.0, 19.0]);
+        assert_eq!(history.temperature, Some((16.0, 20.0, 25.0)));
+
+        // 2025-01-02 is not over yet
+        assert_eq!(history.days.len(), 1);
+        assert_eq!(history.days[0].date, "2025-01-01");
+        assert_eq!(history.days[0].forecast, (24.0, 17.0));
+        assert_eq!(history.days[0].actual, (25.0, 16.0));
+    }
+
+    #[test]
+    fn trend() {
+        let history = WeatherHistory {
+            temperatures: vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0],
+            ..Default::default()
+        };
+
+        assert_eq!(history.trend(3), [1.5, 3.5, 5.5]);
+        assert_eq!(history.trend(10), history.temperatures);
+    }
+
+    #[test]
+    fn history_path_in_folder() {
+        assert!(history_path("台北").is_ok());
+        for city in ["", "..", "../devices", "a/b", "a\\b", "C:"] {
+            assert!(history_path(city).is_err(), "{city}");
+        }
+    }
+}
@@ -0,0 +1,493 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration};
+use serde::Deserialize;
+
+use crate::utils::weather::{
+    self, AirQuality, Weather, WeatherDaily, WeatherHourly, WeatherProvider,
+};
+
+pub const OPEN_METEO: &str = "open-meteo";
+pub const MET_NO: &str = "met-no";
+pub const FIXTURE: &str = "fixture";
+pub const FIXTURE_PATH: &str = "./test/weather.json";
+
+const TIMEOUT: u64 = 5;
+const DAYS: usize = 7;
+const HOURS: usize = 24;
+// met.no reports m/s, Open-Meteo km/h
+const MS_TO_KMH: f32 = 3.6;
+
+async fn get_body(url: &str) -> Result<String, String> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .get(url)
+        // met.no rejects requests without an identifying user agent
+        .header(
+            reqwest::header::USER_AGENT,
+            format!(
+                "{}/{} github.com/timlin1972/cng4",
+                env!("CARGO_PKG_NAME"),
+                env!("CARGO_PKG_VERSION")
+            ),
+        )
+        .timeout(tokio::time::Duration::from_secs(TIMEOUT))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to get weather: {e}"))?;
+
+    response
+        .text()
+        .await
+        .map_err(|e| format!("Failed to get weather: {e}"))
+}
+
+//
+// Open-Meteo
+//
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoResponse {
+    utc_offset_seconds: i32,
+    current_weather: OpenMeteoCurrent,
+    daily: OpenMeteoDaily,
+    hourly: OpenMeteoHourly,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoCurrent {
+    time: String,
+    temperature: f32,
+    weathercode: u8,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoDaily {
+    time: Vec<String>,
+    temperature_2m_max: Vec<f32>,
+    temperature_2m_min: Vec<f32>,
+    precipitation_probability_max: Vec<Option<f32>>,
+    weather_code: Vec<u8>,
+    sunrise: Vec<Option<String>>,
+    sunset: Vec<Option<String>>,
+    wind_speed_10m_max: Vec<Option<f32>>,
+    uv_index_max: Vec<Option<f32>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoHourly {
+    time: Vec<String>,
+    temperature_2m: Vec<Option<f32>>,
+    precipitation_probability: Vec<Option<f32>>,
+    precipitation: Vec<Option<f32>>,
+    wind_speed_10m: Vec<Option<f32>>,
+    relative_humidity_2m: Vec<Option<f32>>,
+    uv_index: Vec<Option<f32>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoAirQualityResponse {
+    current: OpenMeteoAirQualityCurrent,
+}
+
+#[derive(Deserialize, Debug)]
+struct OpenMeteoAirQualityCurrent {
+    time: String,
+    us_aqi: Option<f32>,
+    pm2_5: Option<f32>,
+    pm10: Option<f32>,
+}
+
+pub struct OpenMeteo;
+
+#[async_trait]
+impl WeatherProvider for OpenMeteo {
+    fn name(&self) -> &str {
+        OPEN_METEO
+    }
+
+    async fn get_weather(&self, latitude: f32, longitude: f32) -> Result<Weather, String> {
+        let body = get_body(&format!(
+            "https://api.open-meteo.com/v1/forecast?latitude={latitude}&longitude={longitude}&daily=temperature_2m_max,temperature_2m_min,precipitation_probability_max,weather_code,sunrise,sunset,wind_speed_10m_max,uv_index_max&hourly=temperature_2m,precipitation_probability,precipitation,wind_speed_10m,relative_humidity_2m,uv_index&forecast_hours={HOURS}&current_weather=true&timezone=auto"
+        ))
+        .await?;
+
+        let response: OpenMeteoResponse = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse weather data: {e}"))?;
+        let daily = response.daily;
+
+        let len = daily.time.len();
+        if daily.temperature_2m_max.len() != len
+            || daily.temperature_2m_min.len() != len
+            || daily.precipitation_probability_max.len() != len
+            || daily.weather_code.len() != len
+            || daily.sunrise.len() != len
+            || daily.sunset.len() != len
+            || daily.wind_speed_10m_max.len() != len
+            || daily.uv_index_max.len() != len
+        {
+            return Err("Mismatch in forecast array lengths".to_string());
+        }
+
+        let daily = (0..len)
+            .map(|i| WeatherDaily {
+                time: daily.time[i].clone(),
+                temperature_2m_max: daily.temperature_2m_max[i],
+                temperature_2m_min: daily.temperature_2m_min[i],
+                precipitation_probability_max: daily.precipitation_probability_max[i]
+                    .unwrap_or_default() as u8,
+                weather_code: daily.weather_code[i],
+                sunrise: daily.sunrise[i].clone(),
+                sunset: daily.sunset[i].clone(),
+                wind_speed_10m_max: daily.wind_speed_10m_max[i].unwrap_or_default(),
+                uv_index_max: daily.uv_index_max[i].unwrap_or_default(),
+            })
+            .collect();
+
+        let hourly = response.hourly;
+        let len = hourly.time.len();
+        if hourly.temperature_2m.len() != len
+            || hourly.precipitation_probability.len() != len
+            || hourly.precipitation.len() != len
+            || hourly.wind_speed_10m.len() != len
+            || hourly.relative_humidity_2m.len() != len
+            || hourly.uv_index.len() != len
+        {
+            return Err("Mismatch in hourly array lengths".to_string());
+        }
+
+        let hourly = (0..len)
+            .map(|i| WeatherHourly {
+                time: hourly.time[i].clone(),
+                temperature_2m: hourly.temperature_2m[i].unwrap_or_default(),
+                precipitation_probability: hourly.precipitation_probability[i].unwrap_or_default()
+                    as u8,
+                precipitation: hourly.precipitation[i].unwrap_or_default(),
+                wind_speed_10m: hourly.wind_speed_10m[i].unwrap_or_default(),
+                relative_humidity_2m: hourly.relative_humidity_2m[i].unwrap_or_default() as u8,
+                uv_index: hourly.uv_index[i].unwrap_or_default(),
+            })
+            .collect();
+
+        Ok(Weather {
+            time: response.current_weather.time,
+            temperature: response.current_weather.temperature,
+            weathercode: response.current_weather.weathercode,
+            utc_offset_seconds: response.utc_offset_seconds,
+            daily,
+            hourly,
+        })
+    }
+
+    async fn get_air_quality(&self, latitude: f32, longitude: f32) -> Result<AirQuality, String> {
+        let body = get_body(&format!(
+            "https://air-quality-api.open-meteo.com/v1/air-quality?latitude={latitude}&longitude={longitude}&current=us_aqi,pm2_5,pm10&timezone=auto"
+        ))
+        .await?;
+
+        let response: OpenMeteoAirQualityResponse = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse air quality data: {e}"))?;
+        let current = response.current;
+
+        Ok(AirQuality {
+            time: current.time,
+            us_aqi: current.us_aqi.ok_or("No US AQI".to_string())? as u16,
+            pm2_5: current.pm2_5.unwrap_or_default(),
+            pm10: current.pm10.unwrap_or_default(),
+        })
+    }
+}
+
+//
+// MET Norway (api.met.no), hourly timeseries folded into days
+//
+
+#[derive(Deserialize, Debug)]
+struct MetNoResponse {
+    properties: MetNoProperties,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoProperties {
+    timeseries: Vec<MetNoTimeseries>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoTimeseries {
+    time: String,
+    data: MetNoData,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoData {
+    instant: MetNoInstant,
+    next_1_hours: Option<MetNoNext>,
+    next_6_hours: Option<MetNoNext>,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoInstant {
+    details: MetNoInstantDetails,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoInstantDetails {
+    air_temperature: f32,
+    #[serde(default)]
+    relative_humidity: f32,
+    #[serde(default)]
+    wind_speed: f32,
+    #[serde(default)]
+    ultraviolet_index_clear_sky: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoNext {
+    summary: MetNoSummary,
+    #[serde(default)]
+    details: MetNoNextDetails,
+}
+
+#[derive(Deserialize, Debug)]
+struct MetNoSummary {
+    symbol_code: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct MetNoNextDetails {
+    probability_of_precipitation: Option<f32>,
+    precipitation_amount: Option<f32>,
+}
+
+impl MetNoData {
+    fn next(&self) -> Option<&MetNoNext> {
+        self.next_1_hours.as_ref().or(self.next_6_hours.as_ref())
+    }
+}
+
+// 2025-01-01T12:00:00Z -> 2025-01-01T20:00 at +08:00
+fn met_no_local_time(time: &str, utc_offset_seconds: i32) -> Option<String> {
+    let time = DateTime::parse_from_rfc3339(time).ok()?;
+    Some(
+        (time.naive_utc() + Duration::seconds(utc_offset_seconds as i64))
+            .format("%Y-%m-%dT%H:%M")
+            .to_string(),
+    )
+}
+
+// met.no symbol codes to WMO weather codes, as used by Open-Meteo
+fn met_no_weather_code(symbol_code: &str) -> Option<u8> {
+    let symbol = symbol_code.split('_').next().unwrap_or(symbol_code);
+
+    if symbol.contains("thunder") {
+        return Some(95);
+    }
+
+    let code = match symbol {
+        "clearsky" => 0,
+        "fair" => 1,
+        "partlycloudy" => 2,
+        "cloudy" => 3,
+        "fog" => 45,
+        "lightrain" => 61,
+        "rain" => 63,
+        "heavyrain" => 65,
+        "lightsleet" | "sleet" | "lightsleetshowers" | "sleetshowers" => 66,
+        "heavysleet" | "heavysleetshowers" => 67,
+        "lightsnow" => 71,
+        "snow" => 73,
+        "heavysnow" => 75,
+        "lightrainshowers" => 80,
+        "rainshowers" => 81,
+        "heavyrainshowers" => 82,
+        "lightsnowshowers" | "snowshowers" => 85,
+        "heavysnowshowers" => 86,
+        _ => return None,
+    };
+
+    Some(code)
+}
+
+pub struct MetNo;
+
+#[async_trait]
+impl WeatherProvider for MetNo {
+    fn name(&self) -> &str {
+        MET_NO
+    }
+
+    async fn get_weather(&self, latitude: f32, longitude: f32) -> Result<Weather, String> {
+        let body = get_body(&format!(
+            "https://api.met.no/weatherapi/locationforecast/2.0/complete?lat={latitude:.4}&lon={longitude:.4}"
+        ))
+        .await?;
+
+        let response: MetNoResponse = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse weather data: {e}"))?;
+        let timeseries = response.properties.timeseries;
+
+        // met.no reports UTC only, days are folded by the local date of the city
+        let utc_offset_seconds = weather::solar_utc_offset(longitude);
+        let times = timeseries
+            .iter()
+            .map(|entry| met_no_local_time(&entry.time, utc_offset_seconds))
+            .collect::<Option<Vec<String>>>()
+            .ok_or("Invalid timeseries.time")?;
+
+        let current = timeseries.first().ok_or("Missing timeseries")?;
+        let time = &times[0];
+        let weathercode = current
+            .data
+            .next()
+            .and_then(|next| met_no_weather_code(&next.summary.symbol_code))
+            .ok_or("Missing or invalid symbol_code")?;
+
+        let mut days: BTreeMap<&str, WeatherDaily> = BTreeMap::new();
+        for (entry, time) in timeseries.iter().zip(&times) {
+            let date = &time[..10];
+            let details = &entry.data.instant.details;
+            let temperature = details.air_temperature;
+            let precipitation_probability = entry
+                .data
+                .next()
+                .and_then(|next| next.details.probability_of_precipitation)
+                .unwrap_or_default() as u8;
+            // the most severe weather of the day, same as Open-Meteo
+            let weather_code = entry
+                .data
+                .next()
+                .and_then(|next| met_no_weather_code(&next.summary.symbol_code));
+
+            let daily = days.entry(date).or_insert_with(|| WeatherDaily {
+                time: date.to_string(),
+                temperature_2m_max: temperature,
+                temperature_2m_min: temperature,
+                ..Default::default()
+            });
+            daily.temperature_2m_max = daily.temperature_2m_max.max(temperature);
+            daily.temperature_2m_min = daily.temperature_2m_min.min(temperature);
+            daily.precipitation_probability_max = daily
+                .precipitation_probability_max
+                .max(precipitation_probability);
+            if let Some(weather_code) = weather_code {
+                daily.weather_code = daily.weather_code.max(weather_code);
+            }
+            daily.wind_speed_10m_max = daily.wind_speed_10m_max.max(details.wind_speed * MS_TO_KMH);
+            daily.uv_index_max = daily.uv_index_max.max(details.ultraviolet_index_clear_sky);
+        }
+
+        // hourly steps come first, then 6-hourly ones
+        let hourly = timeseries
+            .iter()
+            .zip(&times)
+            .filter_map(|(entry, time)| {
+                let next = entry.data.next_1_hours.as_ref()?;
+                let details = &entry.data.instant.details;
+                Some(WeatherHourly {
+                    time: time.clone(),
+                    temperature_2m: details.air_temperature,
+                    precipitation_probability: next
+                        .details
+                        .probability_of_precipitation
+                        .unwrap_or_default() as u8,
+                    precipitation: next.details.precipitation_amount.unwrap_or_default(),
+                    wind_speed_10m: details.wind_speed * MS_TO_KMH,
+                    relative_humidity_2m: details.relative_humidity as u8,
+                    uv_index: details.ultraviolet_index_clear_sky,
+                })
+            })
+            .take(HOURS)
+            .collect();
+
+        Ok(Weather {
+            time: time.to_string(),
+            temperature: current.data.instant.details.air_temperature,
+            weathercode,
+            utc_offset_seconds,
+            daily: days.into_values().take(DAYS).collect(),
+            hourly,
+        })
+    }
+}
+
+//
+// Fixture, a serialized `Weather` for offline testing, with optional `air_quality`
+//
+
+#[derive(Deserialize, Debug)]
+struct FixtureAirQuality {
+    air_quality: Option<AirQuality>,
+}
+
+pub struct Fixture {
+    pub path: String,
+}
+
+#[async_trait]
+impl WeatherProvider for Fixture {
+    fn name(&self) -> &str {
+        FIXTURE
+    }
+
+    fn cacheable(&self) -> bool {
+        false
+    }
+
+    async fn get_weather(&self, _latitude: f32, _longitude: f32) -> Result<Weather, String> {
+        let body = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| format!("Failed to read `{}`: {e}", self.path))?;
+
+        serde_json::from_str(&body).map_err(|e| format!("Failed to parse weather data: {e}"))
+    }
+
+    async fn get_air_quality(&self, _latitude: f32, _longitude: f32) -> Result<AirQuality, String> {
+        let body = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| format!("Failed to read `{}`: {e}", self.path))?;
+
+        let fixture: FixtureAirQuality = serde_json::from_str(&body)
+            .map_err(|e| format!("Failed to parse air quality data: {e}"))?;
+
+        fixture
+            .air_quality
+            .ok_or(format!("No air quality in `{}`", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn fixture_weather() {
+        let fixture = Fixture {
+            path: FIXTURE_PATH.to_string(),
+        };
+
+        let weather = fixture.get_weather(0.0, 0.0).await.unwrap();
+        assert_eq!(weather.time, "2025-01-01T12:00");
+        assert_eq!(weather.temperature, 21.5);
+        assert_eq!(weather.utc_offset_seconds, 28800);
+        assert_eq!(weather.daily[0].time, "2025-01-01");
+        assert_eq!(
+            weather.daily[0].sunrise.as_deref(),
+            Some("2025-01-01T06:35")
+        );
+        assert_eq!(weather.daily[1].weather_code, 61);
+
+        let air_quality = fixture.get_air_quality(0.0, 0.0).await.unwrap();
+        assert_eq!(air_quality.us_aqi, 72);
+    }
+
+    #[tokio::test]
+    async fn fixture_missing() {
+        let fixture = Fixture {
+            path: "./test/missing.json".to_string(),
+        };
+
+        assert!(fixture.get_weather(0.0, 0.0).await.is_err());
+    }
+}